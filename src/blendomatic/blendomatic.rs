@@ -0,0 +1,279 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::io;
+
+use crate::blendomatic::definitions::BLENDOMATIC_BIT_MASKS;
+use crate::blendomatic::definitions::BLENDOMATIC_HEADER_SIZE;
+use crate::blendomatic::definitions::BLENDOMATIC_MAX_ALPHA;
+use crate::blendomatic::definitions::BLENDOMATIC_ROW_STEP;
+
+/// Blend mode with the masks for all blend directions.
+#[derive(Debug, Clone)]
+pub struct BlendMode {
+	/// Number of pixels in a tile.
+	pub pixel_count: u32,
+	/// Flags of the tiles.
+	pub tile_flags: Vec<u8>,
+	/// Bit masks of the tiles. Covered pixels have the value 255.
+	pub bit_masks: Vec<image::GrayImage>,
+	/// Alpha masks of the tiles, scaled to 0..=255.
+	pub alpha_masks: Vec<image::GrayImage>,
+}
+
+/// Blendomatic file.
+#[derive(Debug, Clone)]
+pub struct BlendomaticFile {
+	/// Number of tiles per blend mode.
+	pub num_tiles: u32,
+	/// Blend modes.
+	pub blend_modes: Vec<BlendMode>,
+}
+
+/// Shape of the diamond shaped tile.
+#[derive(Debug, Clone, Copy)]
+struct TileShape {
+	/// Width of the tile.
+	width: usize,
+	/// Height of the tile.
+	height: usize,
+}
+
+impl TileShape {
+	/// Find the tile shape for a number of pixels.
+	///
+	/// Rows grow by 4 pixels from a single pixel at the top to the full width
+	/// in the middle and shrink again towards the bottom.
+	///
+	/// # Arguments
+	///
+	/// * `pixel_count` - Number of pixels in the tile.
+	///
+	/// # Returns
+	///
+	/// Tile shape or `None` if no diamond has this number of pixels.
+	fn from_pixel_count(pixel_count: usize) -> Option<Self> {
+		let mut height = 1;
+		loop {
+			let shape = Self {
+				width: 2 * height - 1,
+				height,
+			};
+			let count = shape.get_pixel_count();
+			if count == pixel_count {
+				return Some(shape);
+			}
+			if count > pixel_count {
+				return None;
+			}
+			height += 2;
+		}
+	}
+
+	/// Get the width of a row.
+	///
+	/// # Arguments
+	///
+	/// * `row` - Index of the row.
+	///
+	/// # Returns
+	///
+	/// Number of pixels in the row.
+	const fn get_row_width(
+		self,
+		row: usize,
+	) -> usize {
+		let half = self.height / 2;
+		let distance = row.abs_diff(half);
+
+		return 1 + BLENDOMATIC_ROW_STEP * (half - distance);
+	}
+
+	/// Get the number of pixels in the tile.
+	///
+	/// # Returns
+	///
+	/// Number of pixels.
+	fn get_pixel_count(self) -> usize {
+		return (0..self.height).map(|row| self.get_row_width(row)).sum();
+	}
+
+	/// Get the image positions of the tile pixels in storage order.
+	///
+	/// # Returns
+	///
+	/// Iterator over the pixel positions.
+	fn get_positions(self) -> impl Iterator<Item = (u32, u32)> {
+		return (0..self.height).flat_map(move |row| {
+			let row_width = self.get_row_width(row);
+			let start = (self.width - row_width) / 2;
+			(start..start + row_width)
+				.map(move |x| (u32::try_from(x).unwrap(), u32::try_from(row).unwrap()))
+		});
+	}
+
+	/// Build a tile image from the stored pixel values.
+	///
+	/// # Arguments
+	///
+	/// * `values` - Pixel values in storage order.
+	///
+	/// # Returns
+	///
+	/// Image with the size of the tile. Pixels outside the diamond are 0.
+	fn to_image<I>(
+		self,
+		values: I,
+	) -> image::GrayImage
+	where
+		I: IntoIterator<Item = u8>,
+	{
+		let mut image = image::GrayImage::new(
+			u32::try_from(self.width).unwrap(),
+			u32::try_from(self.height).unwrap(),
+		);
+		for ((x, y), value) in self.get_positions().zip(values) {
+			image.put_pixel(x, y, image::Luma([value]));
+		}
+
+		return image;
+	}
+}
+
+/// Parse a blendomatic file.
+///
+/// The header stores the number of blend modes and tiles. Every blend mode
+/// stores the number of pixels in a tile, one flag byte per tile, 32 bit
+/// masks interleaved as one 32-bit value per pixel, and one byte mask per
+/// tile with alpha values from 0 to 128.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the blendomatic file.
+///
+/// # Returns
+///
+/// The parsed blendomatic file.
+///
+/// # Errors
+///
+/// Returns an error if the file is truncated or the tiles are not diamond
+/// shaped.
+pub fn parse_blendomatic(bytes: &[u8]) -> io::Result<BlendomaticFile> {
+	let truncated = || io::Error::new(io::ErrorKind::InvalidData, "Blendomatic file is truncated");
+	if bytes.len() < BLENDOMATIC_HEADER_SIZE {
+		return Err(truncated());
+	}
+	let read_u32 = |pos: usize| {
+		u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]])
+	};
+
+	let num_modes = read_u32(0);
+	let num_tiles = read_u32(4);
+	let tiles = num_tiles as usize;
+
+	let mut blend_modes = Vec::<BlendMode>::new();
+	let mut pos = BLENDOMATIC_HEADER_SIZE;
+	for _ in 0..num_modes {
+		if bytes.len() < pos + 4 {
+			return Err(truncated());
+		}
+		let pixel_count = read_u32(pos);
+		let pixels = pixel_count as usize;
+		pos += 4;
+		// tile flags, bit masks and one alpha mask per tile
+		let mode_size = (tiles + 4).saturating_mul(pixels).saturating_add(tiles);
+		if bytes.len() - pos < mode_size {
+			return Err(truncated());
+		}
+		let shape = TileShape::from_pixel_count(pixels).ok_or_else(|| {
+			io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("{pixel_count} pixels do not form a diamond tile"),
+			)
+		})?;
+
+		let tile_flags = bytes[pos..pos + tiles].to_vec();
+		pos += tiles;
+
+		let bits = &bytes[pos..pos + pixels * 4];
+		pos += pixels * 4;
+		let bit_masks = (0..BLENDOMATIC_BIT_MASKS)
+			.map(|tile| {
+				shape.to_image(bits.chunks_exact(4).map(|value| {
+					let value = u32::from_le_bytes([value[0], value[1], value[2], value[3]]);
+					if value & (1 << tile) == 0 {
+						0
+					}
+					else {
+						u8::MAX
+					}
+				}))
+			})
+			.collect();
+
+		let mut alpha_masks = Vec::<image::GrayImage>::with_capacity(tiles);
+		for _ in 0..tiles {
+			let values = bytes[pos..pos + pixels].iter().map(|&alpha| {
+				let scaled = u16::from(alpha.min(BLENDOMATIC_MAX_ALPHA)) * 255
+					/ u16::from(BLENDOMATIC_MAX_ALPHA);
+				u8::try_from(scaled).unwrap_or(u8::MAX)
+			});
+			alpha_masks.push(shape.to_image(values));
+			pos += pixels;
+		}
+
+		blend_modes.push(BlendMode {
+			pixel_count,
+			tile_flags,
+			bit_masks,
+			alpha_masks,
+		});
+	}
+
+	return Ok(BlendomaticFile {
+		num_tiles,
+		blend_modes,
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::blendomatic::blendomatic::parse_blendomatic;
+
+	/// Test parsing a blend mode with 3x5 diamond tiles.
+	#[test]
+	fn parse_blend_mode() {
+		let mut bytes = Vec::<u8>::new();
+		bytes.extend_from_slice(&1u32.to_le_bytes());
+		bytes.extend_from_slice(&2u32.to_le_bytes());
+		bytes.extend_from_slice(&7u32.to_le_bytes());
+		bytes.extend_from_slice(&[1, 2]);
+		for pixel in 0..7u32 {
+			// tile 0 covers the first pixel, tile 1 all pixels
+			let value = u32::from(pixel == 0) | 0b10;
+			bytes.extend_from_slice(&value.to_le_bytes());
+		}
+		bytes.extend_from_slice(&[128, 0, 64, 128, 0, 0, 0]);
+		bytes.extend_from_slice(&[0; 7]);
+
+		let blendomatic = parse_blendomatic(&bytes).unwrap();
+		let mode = &blendomatic.blend_modes[0];
+		assert_eq!(mode.tile_flags, [1, 2]);
+		assert_eq!(mode.bit_masks.len(), 32);
+		assert_eq!(mode.alpha_masks.len(), 2);
+
+		let mask = &mode.alpha_masks[0];
+		assert_eq!(mask.dimensions(), (5, 3));
+		// top row has a single pixel in the center
+		assert_eq!(mask.get_pixel(2, 0).0, [255]);
+		assert_eq!(mask.get_pixel(0, 0).0, [0]);
+		assert_eq!(mask.get_pixel(1, 1).0, [127]);
+		assert_eq!(mask.get_pixel(2, 1).0, [255]);
+
+		assert_eq!(mode.bit_masks[0].get_pixel(2, 0).0, [255]);
+		assert_eq!(mode.bit_masks[0].get_pixel(0, 1).0, [0]);
+		assert_eq!(mode.bit_masks[1].get_pixel(4, 1).0, [255]);
+
+		assert!(parse_blendomatic(&bytes[..bytes.len() - 1]).is_err());
+	}
+}
@@ -0,0 +1,17 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+/// Size of the blendomatic file header.
+pub const BLENDOMATIC_HEADER_SIZE: usize = 8;
+
+/// Numbers of tiles per blend mode used by the games.
+pub const BLENDOMATIC_TILE_COUNTS: [u32; 2] = [31, 32];
+
+/// Number of bit masks in a blend mode.
+pub const BLENDOMATIC_BIT_MASKS: usize = 32;
+
+/// Largest alpha value in the byte masks.
+pub const BLENDOMATIC_MAX_ALPHA: u8 = 128;
+
+/// Rows are 4 pixels wider than the previous row in the upper half of the
+/// diamond.
+pub const BLENDOMATIC_ROW_STEP: usize = 4;
@@ -0,0 +1,4 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+pub mod blendomatic;
+pub mod definitions;
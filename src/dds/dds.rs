@@ -0,0 +1,429 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::collections::HashMap;
+use std::io;
+
+use crate::dds::definitions::DDS_FOURCC_DX10;
+use crate::dds::definitions::DDS_HEADER_DX10_SIZE;
+use crate::dds::definitions::DDS_HEADER_SIZE;
+use crate::dds::definitions::DDS_MAGIC;
+use crate::dds::definitions::DDS_MAGIC_SIZE;
+use crate::dds::definitions::DDS_PF_FOURCC;
+use crate::dds::definitions::DXGI_FORMAT_BC1;
+use crate::dds::definitions::DXGI_FORMAT_BC2;
+use crate::dds::definitions::DXGI_FORMAT_BC3;
+use crate::dds::definitions::DXGI_FORMAT_BC4;
+use crate::dds::definitions::DXGI_FORMAT_BC5;
+use crate::dds::header::DDSHeader;
+use crate::dds::header::DDSHeaderDX10;
+use crate::slp::unpack::UnpackFixedSize;
+use crate::util::bcn::decode_bc1_block;
+use crate::util::bcn::decode_bc2_block;
+use crate::util::bcn::decode_bc3_block;
+use crate::util::bcn::decode_bc4_block;
+use crate::util::bcn::decode_bc5_block;
+use crate::util::bcn::BC1_BLOCK_SIZE;
+use crate::util::bcn::BC2_BLOCK_SIZE;
+use crate::util::bcn::BC4_BLOCK_SIZE;
+use crate::util::bcn::BC5_BLOCK_SIZE;
+use crate::util::bcn::BC_BLOCK_DIM;
+use crate::util::bcn::BC_BLOCK_PIXELS;
+use crate::util::image::RGBAImageConvertible;
+use crate::util::matrix::Matrix2D;
+
+/// Block compression format of a DDS texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DDSFormat {
+	/// BC1 (DXT1), RGB with 1-bit alpha.
+	BC1,
+	/// BC2 (DXT3), RGB with explicit 4-bit alpha.
+	BC2,
+	/// BC3 (DXT5), RGB with interpolated alpha.
+	BC3,
+	/// BC4 (ATI1), single channel.
+	BC4,
+	/// BC5 (ATI2), two channels.
+	BC5,
+}
+
+impl DDSFormat {
+	/// Get the format from a `FourCC` code.
+	///
+	/// # Arguments
+	///
+	/// * `four_cc` - `FourCC` code.
+	///
+	/// # Returns
+	///
+	/// Format or `None` if the code is not supported.
+	pub const fn from_four_cc(four_cc: &[u8; 4]) -> Option<Self> {
+		match four_cc {
+			b"DXT1" => Some(Self::BC1),
+			b"DXT2" | b"DXT3" => Some(Self::BC2),
+			b"DXT4" | b"DXT5" => Some(Self::BC3),
+			b"ATI1" | b"BC4U" => Some(Self::BC4),
+			b"ATI2" | b"BC5U" => Some(Self::BC5),
+			_ => None,
+		}
+	}
+
+	/// Get the format from a DXGI format.
+	///
+	/// # Arguments
+	///
+	/// * `dxgi_format` - DXGI format.
+	///
+	/// # Returns
+	///
+	/// Format or `None` if the DXGI format is not supported.
+	pub const fn from_dxgi(dxgi_format: u32) -> Option<Self> {
+		match dxgi_format {
+			format if format == DXGI_FORMAT_BC1[0] || format == DXGI_FORMAT_BC1[1] => {
+				Some(Self::BC1)
+			}
+			format if format == DXGI_FORMAT_BC2[0] || format == DXGI_FORMAT_BC2[1] => {
+				Some(Self::BC2)
+			}
+			format if format == DXGI_FORMAT_BC3[0] || format == DXGI_FORMAT_BC3[1] => {
+				Some(Self::BC3)
+			}
+			DXGI_FORMAT_BC4 => Some(Self::BC4),
+			DXGI_FORMAT_BC5 => Some(Self::BC5),
+			_ => None,
+		}
+	}
+
+	/// Get the size of a compressed block.
+	///
+	/// # Returns
+	///
+	/// Size of a block in bytes.
+	pub const fn get_block_size(self) -> usize {
+		match self {
+			Self::BC1 => BC1_BLOCK_SIZE,
+			Self::BC2 | Self::BC3 => BC2_BLOCK_SIZE,
+			Self::BC4 => BC4_BLOCK_SIZE,
+			Self::BC5 => BC5_BLOCK_SIZE,
+		}
+	}
+
+	/// Decode a compressed block.
+	///
+	/// Single channel BC4 blocks are decoded to gray colors.
+	///
+	/// # Arguments
+	///
+	/// * `block` - Block data.
+	///
+	/// # Returns
+	///
+	/// RGBA colors of the 16 pixels in row-major order.
+	pub fn decode_block(
+		self,
+		block: &[u8],
+	) -> [[u8; 4]; BC_BLOCK_PIXELS] {
+		match self {
+			Self::BC1 => decode_bc1_block(block),
+			Self::BC2 => decode_bc2_block(block),
+			Self::BC3 => decode_bc3_block(block),
+			Self::BC4 => decode_bc4_block(block).map(|value| [value, value, value, 255]),
+			Self::BC5 => decode_bc5_block(block),
+		}
+	}
+}
+
+/// Decoded surface of a DDS texture.
+#[derive(Debug, Clone)]
+pub struct DDSImage {
+	/// Width of the surface.
+	pub width: usize,
+	/// Height of the surface.
+	pub height: usize,
+	/// RGBA colors in row-major order.
+	pub pixels: Vec<[u8; 4]>,
+}
+
+impl RGBAImageConvertible for DDSImage {
+	/// Convert to a matrix of RGBA values. The lookup table is not used.
+	fn to_rgba_matrix(
+		&self,
+		_lookup: &HashMap<usize, [u8; 4]>,
+	) -> Matrix2D<[u8; 4]> {
+		let mut matrix = Matrix2D::<[u8; 4]>::zeros(self.height, self.width);
+		for (i, pixel) in self.pixels.iter().enumerate() {
+			matrix[(i / self.width, i % self.width)] = *pixel;
+		}
+
+		return matrix;
+	}
+
+	/// Convert to a vector of bytes. The lookup table is not used.
+	fn to_rgba_bytes(
+		&self,
+		_lookup: &HashMap<usize, [u8; 4]>,
+	) -> Vec<u8> {
+		return self.pixels.iter().flatten().copied().collect();
+	}
+
+	/// Convert to an RGBA image. The lookup table is not used.
+	fn to_image(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+	) -> image::RgbaImage {
+		let image = image::RgbaImage::from_raw(
+			u32::try_from(self.width).unwrap(),
+			u32::try_from(self.height).unwrap(),
+			self.to_rgba_bytes(lookup),
+		);
+
+		return image.unwrap();
+	}
+}
+
+/// DDS file.
+#[derive(Debug, Clone)]
+pub struct DDSFile {
+	/// DDS header.
+	pub header: DDSHeader,
+	/// DX10 header extension.
+	pub header_dx10: Option<DDSHeaderDX10>,
+	/// Block compression format.
+	pub format: DDSFormat,
+	/// Compressed surface data.
+	data: Vec<u8>,
+}
+
+impl DDSFile {
+	/// Get the number of mipmap levels.
+	///
+	/// # Returns
+	///
+	/// Number of mipmap levels, at least 1.
+	pub fn get_mipmap_count(&self) -> usize {
+		return (self.header.mipmap_count as usize).max(1);
+	}
+
+	/// Get the size of a mipmap level.
+	///
+	/// # Arguments
+	///
+	/// * `level` - Mipmap level.
+	///
+	/// # Returns
+	///
+	/// Width and height of the level.
+	pub fn get_mipmap_size(
+		&self,
+		level: usize,
+	) -> (usize, usize) {
+		let width = (self.header.width as usize >> level).max(1);
+		let height = (self.header.height as usize >> level).max(1);
+
+		return (width, height);
+	}
+
+	/// Get the size of the compressed data of a mipmap level.
+	///
+	/// # Arguments
+	///
+	/// * `level` - Mipmap level.
+	///
+	/// # Returns
+	///
+	/// Size in bytes.
+	fn get_mipmap_data_size(
+		&self,
+		level: usize,
+	) -> usize {
+		let (width, height) = self.get_mipmap_size(level);
+
+		return width.div_ceil(BC_BLOCK_DIM)
+			* height.div_ceil(BC_BLOCK_DIM)
+			* self.format.get_block_size();
+	}
+
+	/// Decode a mipmap level of the first surface.
+	///
+	/// # Arguments
+	///
+	/// * `level` - Mipmap level, 0 for the full size texture.
+	///
+	/// # Returns
+	///
+	/// Decoded surface.
+	///
+	/// # Panics
+	///
+	/// Panics if the level does not exist or the data is truncated.
+	pub fn decode_mipmap(
+		&self,
+		level: usize,
+	) -> DDSImage {
+		assert!(
+			level < self.get_mipmap_count(),
+			"DDS texture has no mipmap level {level}"
+		);
+
+		let offset = (0..level)
+			.map(|i| self.get_mipmap_data_size(i))
+			.sum::<usize>();
+		let (width, height) = self.get_mipmap_size(level);
+		let blocks_width = width.div_ceil(BC_BLOCK_DIM);
+		let block_size = self.format.get_block_size();
+		let data = &self.data[offset..offset + self.get_mipmap_data_size(level)];
+
+		let mut pixels = vec![[0; 4]; width * height];
+		for (i, block) in data.chunks_exact(block_size).enumerate() {
+			let colors = self.format.decode_block(block);
+			let x = (i % blocks_width) * BC_BLOCK_DIM;
+			let y = (i / blocks_width) * BC_BLOCK_DIM;
+
+			for (j, color) in colors.iter().enumerate() {
+				let px = x + j % BC_BLOCK_DIM;
+				let py = y + j / BC_BLOCK_DIM;
+				if px < width && py < height {
+					pixels[py * width + px] = *color;
+				}
+			}
+		}
+
+		return DDSImage {
+			width,
+			height,
+			pixels,
+		};
+	}
+
+	/// Decode the full size texture.
+	///
+	/// # Returns
+	///
+	/// Decoded surface.
+	pub fn decode(&self) -> DDSImage {
+		return self.decode_mipmap(0);
+	}
+}
+
+/// Parse a DDS file with block compressed data.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the DDS file.
+///
+/// # Returns
+///
+/// The parsed DDS file.
+///
+/// # Errors
+///
+/// Returns an error if the file is not a DDS file, is truncated or uses an
+/// unsupported format.
+pub fn parse_dds(bytes: &[u8]) -> io::Result<DDSFile> {
+	if bytes.len() < DDS_MAGIC_SIZE + DDS_HEADER_SIZE || &bytes[0..4] != DDS_MAGIC {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"File is not a DDS file",
+		));
+	}
+	let header = DDSHeader::from_buffer(bytes, DDS_MAGIC_SIZE);
+	let mut offset = DDS_MAGIC_SIZE + DDS_HEADER_SIZE;
+
+	let four_cc = header.pixel_format.four_cc;
+	if header.pixel_format.flags & DDS_PF_FOURCC == 0 {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"Uncompressed DDS textures are not supported",
+		));
+	}
+
+	let mut header_dx10 = None;
+	let format = if &four_cc == DDS_FOURCC_DX10 {
+		if bytes.len() < offset + DDS_HEADER_DX10_SIZE {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				"DDS DX10 header is truncated",
+			));
+		}
+		let dx10 = DDSHeaderDX10::from_buffer(bytes, offset);
+		offset += DDS_HEADER_DX10_SIZE;
+		header_dx10 = Some(dx10);
+		DDSFormat::from_dxgi(dx10.dxgi_format).ok_or_else(|| {
+			io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("Unsupported DXGI format {}", dx10.dxgi_format),
+			)
+		})?
+	}
+	else {
+		DDSFormat::from_four_cc(&four_cc).ok_or_else(|| {
+			io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!(
+					"Unsupported DDS format {}",
+					String::from_utf8_lossy(&four_cc)
+				),
+			)
+		})?
+	};
+
+	return Ok(DDSFile {
+		header,
+		header_dx10,
+		format,
+		// keep only the surface data
+		data: bytes[offset..].to_vec(),
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use crate::dds::dds::parse_dds;
+	use crate::dds::dds::DDSFormat;
+	use crate::util::image::RGBAImageConvertible;
+
+	/// Build a DDS file with the given pixel format and data.
+	fn build_dds(
+		width: u32,
+		height: u32,
+		four_cc: [u8; 4],
+		dx10_format: Option<u32>,
+		data: &[u8],
+	) -> Vec<u8> {
+		let mut bytes = b"DDS ".to_vec();
+		bytes.extend_from_slice(&124u32.to_le_bytes());
+		bytes.extend_from_slice(&0u32.to_le_bytes());
+		bytes.extend_from_slice(&height.to_le_bytes());
+		bytes.extend_from_slice(&width.to_le_bytes());
+		bytes.resize(4 + 72, 0);
+		bytes.extend_from_slice(&32u32.to_le_bytes());
+		bytes.extend_from_slice(&4u32.to_le_bytes());
+		bytes.extend_from_slice(&four_cc);
+		bytes.resize(4 + 124, 0);
+		if let Some(format) = dx10_format {
+			bytes.extend_from_slice(&format.to_le_bytes());
+			bytes.extend_from_slice(&[0; 16]);
+		}
+		bytes.extend_from_slice(data);
+		return bytes;
+	}
+
+	/// Test decoding DDS textures with legacy and DX10 headers.
+	#[test]
+	fn decode_dds() {
+		let red = [0x00, 0xF8, 0x00, 0x00, 0, 0, 0, 0];
+		let dds = parse_dds(&build_dds(6, 4, *b"DXT1", None, &[red, red].concat())).unwrap();
+		assert_eq!(dds.format, DDSFormat::BC1);
+
+		let image = dds.decode().to_image(&HashMap::new());
+		assert_eq!(image.dimensions(), (6, 4));
+		assert_eq!(image.get_pixel(5, 3).0, [255, 0, 0, 255]);
+
+		let gray = [128, 0, 0, 0, 0, 0, 0, 0];
+		let dds = parse_dds(&build_dds(4, 4, *b"DX10", Some(80), &gray)).unwrap();
+		assert_eq!(dds.format, DDSFormat::BC4);
+		assert!(dds.header_dx10.is_some());
+		assert_eq!(dds.decode().pixels[0], [128, 128, 128, 255]);
+	}
+}
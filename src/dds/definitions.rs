@@ -0,0 +1,34 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+/// Magic number at the start of DDS files.
+pub const DDS_MAGIC: &[u8; 4] = b"DDS ";
+
+/// Size of the magic number.
+pub const DDS_MAGIC_SIZE: usize = 4;
+
+/// Size of the DDS header after the magic number.
+pub const DDS_HEADER_SIZE: usize = 124;
+
+/// Size of the DX10 header extension.
+pub const DDS_HEADER_DX10_SIZE: usize = 20;
+
+/// Pixel format flag for formats identified by a `FourCC` code.
+pub const DDS_PF_FOURCC: u32 = 0x04;
+
+/// `FourCC` code announcing the DX10 header extension.
+pub const DDS_FOURCC_DX10: &[u8; 4] = b"DX10";
+
+/// DXGI formats of BC1 textures.
+pub const DXGI_FORMAT_BC1: [u32; 2] = [71, 72];
+
+/// DXGI formats of BC2 textures.
+pub const DXGI_FORMAT_BC2: [u32; 2] = [74, 75];
+
+/// DXGI formats of BC3 textures.
+pub const DXGI_FORMAT_BC3: [u32; 2] = [77, 78];
+
+/// DXGI format of unsigned BC4 textures.
+pub const DXGI_FORMAT_BC4: u32 = 80;
+
+/// DXGI format of unsigned BC5 textures.
+pub const DXGI_FORMAT_BC5: u32 = 83;
@@ -0,0 +1,140 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::fmt;
+
+use crate::dds::definitions::DDS_HEADER_DX10_SIZE;
+use crate::dds::definitions::DDS_HEADER_SIZE;
+use crate::slp::unpack::UnpackFixedSize;
+
+/// Pixel format in a DDS header.
+#[derive(Debug, Clone, Copy)]
+pub struct DDSPixelFormat {
+	/// Size of the pixel format structure.
+	pub size: u32,
+	/// Pixel format flags.
+	pub flags: u32,
+	/// `FourCC` code of compressed formats.
+	pub four_cc: [u8; 4],
+	/// Number of bits per pixel of uncompressed formats.
+	pub rgb_bit_count: u32,
+	/// Red channel mask.
+	pub r_mask: u32,
+	/// Green channel mask.
+	pub g_mask: u32,
+	/// Blue channel mask.
+	pub b_mask: u32,
+	/// Alpha channel mask.
+	pub a_mask: u32,
+}
+
+/// Header in a DDS file, following the magic number.
+#[derive(Debug, Clone, Copy)]
+pub struct DDSHeader {
+	/// Size of the header.
+	pub size: u32,
+	/// Header flags.
+	pub flags: u32,
+	/// Height of the texture.
+	pub height: u32,
+	/// Width of the texture.
+	pub width: u32,
+	/// Pitch or size of the top level surface.
+	pub pitch_or_linear_size: u32,
+	/// Depth of volume textures.
+	pub depth: u32,
+	/// Number of mipmap levels.
+	pub mipmap_count: u32,
+	/// Pixel format.
+	pub pixel_format: DDSPixelFormat,
+	/// Surface capabilities.
+	pub caps: [u32; 4],
+}
+
+impl UnpackFixedSize for DDSHeader {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + DDS_HEADER_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		let read_u32 = |pos: usize| u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+
+		// 11 reserved values between the mipmap count and the pixel format
+		let pixel_format = DDSPixelFormat {
+			size: read_u32(72),
+			flags: read_u32(76),
+			four_cc: bytes[80..84].try_into().unwrap(),
+			rgb_bit_count: read_u32(84),
+			r_mask: read_u32(88),
+			g_mask: read_u32(92),
+			b_mask: read_u32(96),
+			a_mask: read_u32(100),
+		};
+
+		return Self {
+			size: read_u32(0),
+			flags: read_u32(4),
+			height: read_u32(8),
+			width: read_u32(12),
+			pitch_or_linear_size: read_u32(16),
+			depth: read_u32(20),
+			mipmap_count: read_u32(24),
+			pixel_format,
+			caps: [read_u32(104), read_u32(108), read_u32(112), read_u32(116)],
+		};
+	}
+}
+
+impl fmt::Display for DDSHeader {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"width: {}\nheight: {}\nmipmap_count: {}\nfour_cc: {}",
+			self.width,
+			self.height,
+			self.mipmap_count,
+			String::from_utf8_lossy(&self.pixel_format.four_cc)
+		)
+	}
+}
+
+/// DX10 extension of the DDS header.
+#[derive(Debug, Clone, Copy)]
+pub struct DDSHeaderDX10 {
+	/// DXGI format of the texture.
+	pub dxgi_format: u32,
+	/// Dimension of the texture resource.
+	pub resource_dimension: u32,
+	/// Miscellaneous flags.
+	pub misc_flag: u32,
+	/// Number of textures in a texture array.
+	pub array_size: u32,
+	/// Additional miscellaneous flags.
+	pub misc_flags2: u32,
+}
+
+impl UnpackFixedSize for DDSHeaderDX10 {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + DDS_HEADER_DX10_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		let read_u32 = |pos: usize| u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+
+		return Self {
+			dxgi_format: read_u32(0),
+			resource_dimension: read_u32(4),
+			misc_flag: read_u32(8),
+			array_size: read_u32(12),
+			misc_flags2: read_u32(16),
+		};
+	}
+}
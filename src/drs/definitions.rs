@@ -0,0 +1,22 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+/// Size of the copyright string in the DRS header.
+pub const DRS_COPYRIGHT_SIZE: usize = 40;
+
+/// Size of the version string in the DRS header.
+pub const DRS_VERSION_SIZE: usize = 4;
+
+/// Size of the archive type string in the DRS header.
+pub const DRS_ARCHIVE_TYPE_SIZE: usize = 12;
+
+/// Size of the DRS header.
+pub const DRS_HEADER_SIZE: usize = 64;
+
+/// Size of the extension in a DRS table info.
+pub const DRS_EXTENSION_SIZE: usize = 4;
+
+/// Size of a table info in the DRS table list.
+pub const DRS_TABLE_INFO_SIZE: usize = 12;
+
+/// Size of a file entry in a DRS file table.
+pub const DRS_FILE_ENTRY_SIZE: usize = 12;
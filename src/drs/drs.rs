@@ -0,0 +1,220 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::io;
+
+use crate::drs::definitions::DRS_FILE_ENTRY_SIZE;
+use crate::drs::definitions::DRS_HEADER_SIZE;
+use crate::drs::definitions::DRS_TABLE_INFO_SIZE;
+use crate::drs::header::DRSHeader;
+use crate::drs::table::DRSFileEntry;
+use crate::drs::table::DRSTable;
+use crate::drs::table::DRSTableInfo;
+use crate::slp::unpack::UnpackFixedSize;
+
+/// DRS archive.
+#[derive(Debug, Clone)]
+pub struct DRSFile {
+	/// DRS header.
+	pub header: DRSHeader,
+	/// File tables, one per file type.
+	pub tables: Vec<DRSTable>,
+	/// Content of the whole archive.
+	data: Vec<u8>,
+}
+
+impl DRSFile {
+	/// Get the file extensions of the tables in the archive.
+	///
+	/// # Returns
+	///
+	/// File extensions in table order.
+	pub fn get_extensions(&self) -> Vec<String> {
+		return self
+			.tables
+			.iter()
+			.map(|table| table.info.get_extension())
+			.collect();
+	}
+
+	/// Get the file entries with a file extension.
+	///
+	/// # Arguments
+	///
+	/// * `extension` - File extension, e.g. `slp`.
+	///
+	/// # Returns
+	///
+	/// File entries of all tables with the extension.
+	pub fn get_entries(
+		&self,
+		extension: &str,
+	) -> Vec<&DRSFileEntry> {
+		return self
+			.tables
+			.iter()
+			.filter(|table| table.info.get_extension() == extension)
+			.flat_map(|table| &table.entries)
+			.collect();
+	}
+
+	/// Find the entry of a file by its resource ID.
+	///
+	/// # Arguments
+	///
+	/// * `id` - Resource ID of the file.
+	///
+	/// # Returns
+	///
+	/// Extension and entry of the file or `None` if the ID is not in the
+	/// archive.
+	pub fn find_entry(
+		&self,
+		id: u32,
+	) -> Option<(String, &DRSFileEntry)> {
+		return self.tables.iter().find_map(|table| {
+			table
+				.entries
+				.iter()
+				.find(|entry| entry.id == id)
+				.map(|entry| (table.info.get_extension(), entry))
+		});
+	}
+
+	/// Get the content of a file in the archive.
+	///
+	/// # Arguments
+	///
+	/// * `entry` - Entry of the file.
+	///
+	/// # Returns
+	///
+	/// File content.
+	pub fn get_entry_bytes(
+		&self,
+		entry: &DRSFileEntry,
+	) -> &[u8] {
+		let start = entry.offset as usize;
+		let end = start + entry.size as usize;
+
+		return &self.data[start..end];
+	}
+
+	/// Get the content of a file by its resource ID.
+	///
+	/// The returned bytes can be passed to the parser of the file type, e.g.
+	/// [`parse_slp`](crate::slp::slp::parse_slp) for `slp` files.
+	///
+	/// # Arguments
+	///
+	/// * `id` - Resource ID of the file.
+	///
+	/// # Returns
+	///
+	/// File content or `None` if the ID is not in the archive.
+	pub fn get_file(
+		&self,
+		id: u32,
+	) -> Option<Vec<u8>> {
+		return self
+			.find_entry(id)
+			.map(|(_, entry)| self.get_entry_bytes(entry).to_vec());
+	}
+}
+
+/// Parse a DRS archive.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the archive.
+///
+/// # Returns
+///
+/// The parsed archive.
+///
+/// # Errors
+///
+/// Returns an error if the archive is truncated or an entry points outside
+/// of the archive.
+pub fn parse_drs(bytes: &[u8]) -> io::Result<DRSFile> {
+	let truncated = || io::Error::new(io::ErrorKind::InvalidData, "DRS archive is truncated");
+	if bytes.len() < DRS_HEADER_SIZE {
+		return Err(truncated());
+	}
+	let header = DRSHeader::from_buffer(bytes, 0);
+	if bytes.len() < DRS_HEADER_SIZE + header.num_tables as usize * DRS_TABLE_INFO_SIZE {
+		return Err(truncated());
+	}
+
+	let mut tables = Vec::<DRSTable>::with_capacity(header.num_tables as usize);
+	for i in 0..header.num_tables as usize {
+		let info = DRSTableInfo::from_buffer(bytes, DRS_HEADER_SIZE + i * DRS_TABLE_INFO_SIZE);
+
+		let mut entries = Vec::<DRSFileEntry>::with_capacity(info.num_files as usize);
+		for j in 0..info.num_files as usize {
+			let entry_offset = info.table_offset as usize + j * DRS_FILE_ENTRY_SIZE;
+			if bytes.len() < entry_offset + DRS_FILE_ENTRY_SIZE {
+				return Err(truncated());
+			}
+			let entry = DRSFileEntry::from_buffer(bytes, entry_offset);
+			if entry.offset as usize + entry.size as usize > bytes.len() {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					format!("DRS entry {} is outside of the archive", entry.id),
+				));
+			}
+			entries.push(entry);
+		}
+
+		tables.push(DRSTable { info, entries });
+	}
+
+	return Ok(DRSFile {
+		header,
+		tables,
+		data: bytes.to_vec(),
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::drs::drs::parse_drs;
+
+	/// Test reading the index and file contents of a DRS archive.
+	#[test]
+	fn read_drs_archive() {
+		let mut bytes = Vec::<u8>::new();
+		let mut copyright = b"Copyright (c) 1997 Ensemble Studios.".to_vec();
+		copyright.resize(40, 0x1A);
+		bytes.extend_from_slice(&copyright);
+		bytes.extend_from_slice(b"1.00tribe\0\0\0\0\0\0\0");
+		bytes.extend_from_slice(&2u32.to_le_bytes());
+		bytes.extend_from_slice(&124u32.to_le_bytes());
+		// table infos
+		bytes.extend_from_slice(b" pls");
+		bytes.extend_from_slice(&88u32.to_le_bytes());
+		bytes.extend_from_slice(&2u32.to_le_bytes());
+		bytes.extend_from_slice(b" vaw");
+		bytes.extend_from_slice(&112u32.to_le_bytes());
+		bytes.extend_from_slice(&1u32.to_le_bytes());
+		// file tables
+		for (id, offset, size) in [(10u32, 124u32, 3u32), (11, 127, 2), (500, 129, 1)] {
+			bytes.extend_from_slice(&id.to_le_bytes());
+			bytes.extend_from_slice(&offset.to_le_bytes());
+			bytes.extend_from_slice(&size.to_le_bytes());
+		}
+		bytes.extend_from_slice(b"abcdeW");
+
+		let drs = parse_drs(&bytes).unwrap();
+
+		assert_eq!(drs.header.get_version(), "1.00");
+		assert_eq!(drs.header.get_archive_type(), "tribe");
+		assert_eq!(drs.get_extensions(), ["slp", "wav"]);
+		assert_eq!(drs.get_entries("slp").len(), 2);
+		assert_eq!(drs.get_file(11), Some(b"de".to_vec()));
+		assert_eq!(
+			drs.find_entry(500).map(|(ext, _)| ext),
+			Some("wav".to_string())
+		);
+		assert_eq!(drs.get_file(12), None);
+	}
+}
@@ -0,0 +1,140 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::fmt;
+
+use crate::drs::definitions::DRS_ARCHIVE_TYPE_SIZE;
+use crate::drs::definitions::DRS_COPYRIGHT_SIZE;
+use crate::drs::definitions::DRS_HEADER_SIZE;
+use crate::drs::definitions::DRS_VERSION_SIZE;
+use crate::slp::unpack::UnpackFixedSize;
+
+/// Header in a DRS archive.
+#[derive(Debug, Clone, Copy)]
+pub struct DRSHeader {
+	/// Copyright string.
+	pub copyright: [u8; DRS_COPYRIGHT_SIZE],
+	/// Version string, e.g. `1.00`.
+	pub version: [u8; DRS_VERSION_SIZE],
+	/// Archive type string, e.g. `tribe`.
+	pub archive_type: [u8; DRS_ARCHIVE_TYPE_SIZE],
+	/// Number of file tables.
+	pub num_tables: u32,
+	/// Offset of the first file in the archive.
+	pub first_file_offset: u32,
+}
+
+impl DRSHeader {
+	/// Create a new DRS header.
+	///
+	/// # Arguments
+	///
+	/// * `copyright` - Copyright string.
+	/// * `version` - Version string.
+	/// * `archive_type` - Archive type string.
+	/// * `num_tables` - Number of file tables.
+	/// * `first_file_offset` - Offset of the first file in the archive.
+	///
+	/// # Returns
+	///
+	/// New DRS header.
+	pub const fn new(
+		copyright: [u8; DRS_COPYRIGHT_SIZE],
+		version: [u8; DRS_VERSION_SIZE],
+		archive_type: [u8; DRS_ARCHIVE_TYPE_SIZE],
+		num_tables: u32,
+		first_file_offset: u32,
+	) -> Self {
+		Self {
+			copyright,
+			version,
+			archive_type,
+			num_tables,
+			first_file_offset,
+		}
+	}
+
+	/// Get the copyright string.
+	///
+	/// # Returns
+	///
+	/// Copyright string without padding.
+	pub fn get_copyright(&self) -> String {
+		return trim_string(&self.copyright);
+	}
+
+	/// Get the version string.
+	///
+	/// # Returns
+	///
+	/// Version string.
+	pub fn get_version(&self) -> String {
+		return trim_string(&self.version);
+	}
+
+	/// Get the archive type string.
+	///
+	/// # Returns
+	///
+	/// Archive type string without padding.
+	pub fn get_archive_type(&self) -> String {
+		return trim_string(&self.archive_type);
+	}
+}
+
+/// Convert a padded string field to a string.
+///
+/// # Arguments
+///
+/// * `bytes` - Bytes of the field.
+///
+/// # Returns
+///
+/// String up to the first null byte without surrounding whitespace.
+fn trim_string(bytes: &[u8]) -> String {
+	let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+
+	return String::from_utf8_lossy(&bytes[..end]).trim().to_string();
+}
+
+impl UnpackFixedSize for DRSHeader {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + DRS_HEADER_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		let copyright = bytes[0..40].try_into().unwrap();
+		let version = bytes[40..44].try_into().unwrap();
+		let archive_type = bytes[44..56].try_into().unwrap();
+		let num_tables = u32::from_le_bytes(bytes[56..60].try_into().unwrap());
+		let first_file_offset = u32::from_le_bytes(bytes[60..64].try_into().unwrap());
+
+		return Self::new(
+			copyright,
+			version,
+			archive_type,
+			num_tables,
+			first_file_offset,
+		);
+	}
+}
+
+impl fmt::Display for DRSHeader {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"copyright: {}\nversion: {}\narchive_type: {}\nnum_tables: {}\nfirst_file_offset: \
+			 {:#x}",
+			self.get_copyright(),
+			self.get_version(),
+			self.get_archive_type(),
+			self.num_tables,
+			self.first_file_offset
+		)
+	}
+}
@@ -0,0 +1,7 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+pub mod definitions;
+pub mod drs;
+pub mod header;
+pub mod table;
+pub mod writer;
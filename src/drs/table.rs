@@ -0,0 +1,159 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::fmt;
+
+use crate::drs::definitions::DRS_EXTENSION_SIZE;
+use crate::drs::definitions::DRS_FILE_ENTRY_SIZE;
+use crate::drs::definitions::DRS_TABLE_INFO_SIZE;
+use crate::slp::unpack::UnpackFixedSize;
+
+/// Info about a file table in a DRS archive.
+#[derive(Debug, Clone, Copy)]
+pub struct DRSTableInfo {
+	/// File extension of the files in the table. The extension is stored
+	/// reversed and padded with spaces, e.g. ` pls` for `slp`.
+	pub extension: [u8; DRS_EXTENSION_SIZE],
+	/// Offset of the file table in the archive.
+	pub table_offset: u32,
+	/// Number of files in the table.
+	pub num_files: u32,
+}
+
+impl DRSTableInfo {
+	/// Create a new DRS table info.
+	///
+	/// # Arguments
+	///
+	/// * `extension` - Reversed file extension.
+	/// * `table_offset` - Offset of the file table in the archive.
+	/// * `num_files` - Number of files in the table.
+	///
+	/// # Returns
+	///
+	/// New DRS table info.
+	pub const fn new(
+		extension: [u8; DRS_EXTENSION_SIZE],
+		table_offset: u32,
+		num_files: u32,
+	) -> Self {
+		Self {
+			extension,
+			table_offset,
+			num_files,
+		}
+	}
+
+	/// Get the file extension of the files in the table.
+	///
+	/// # Returns
+	///
+	/// File extension, e.g. `slp`.
+	pub fn get_extension(&self) -> String {
+		let extension = self.extension.iter().rev().copied().collect::<Vec<u8>>();
+
+		return String::from_utf8_lossy(&extension).trim().to_string();
+	}
+}
+
+impl UnpackFixedSize for DRSTableInfo {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + DRS_TABLE_INFO_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		let extension = bytes[0..4].try_into().unwrap();
+		let table_offset = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+		let num_files = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+
+		return Self::new(extension, table_offset, num_files);
+	}
+}
+
+impl fmt::Display for DRSTableInfo {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"extension: {}\ntable_offset: {:#x}\nnum_files: {}",
+			self.get_extension(),
+			self.table_offset,
+			self.num_files
+		)
+	}
+}
+
+/// Entry of a file in a DRS file table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DRSFileEntry {
+	/// Resource ID of the file.
+	pub id: u32,
+	/// Offset of the file data in the archive.
+	pub offset: u32,
+	/// Size of the file data.
+	pub size: u32,
+}
+
+impl DRSFileEntry {
+	/// Create a new DRS file entry.
+	///
+	/// # Arguments
+	///
+	/// * `id` - Resource ID of the file.
+	/// * `offset` - Offset of the file data in the archive.
+	/// * `size` - Size of the file data.
+	///
+	/// # Returns
+	///
+	/// New DRS file entry.
+	pub const fn new(
+		id: u32,
+		offset: u32,
+		size: u32,
+	) -> Self {
+		Self { id, offset, size }
+	}
+}
+
+impl UnpackFixedSize for DRSFileEntry {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + DRS_FILE_ENTRY_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		let id = u32::from_le_bytes(bytes[0..4].try_into().unwrap());
+		let offset = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+		let size = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+
+		return Self::new(id, offset, size);
+	}
+}
+
+impl fmt::Display for DRSFileEntry {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"id: {}\noffset: {:#x}\nsize: {}",
+			self.id, self.offset, self.size
+		)
+	}
+}
+
+/// File table in a DRS archive.
+#[derive(Debug, Clone)]
+pub struct DRSTable {
+	/// Table info.
+	pub info: DRSTableInfo,
+	/// File entries in the table.
+	pub entries: Vec<DRSFileEntry>,
+}
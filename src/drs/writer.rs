@@ -0,0 +1,366 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+
+use crate::drs::definitions::DRS_ARCHIVE_TYPE_SIZE;
+use crate::drs::definitions::DRS_COPYRIGHT_SIZE;
+use crate::drs::definitions::DRS_EXTENSION_SIZE;
+use crate::drs::definitions::DRS_FILE_ENTRY_SIZE;
+use crate::drs::definitions::DRS_HEADER_SIZE;
+use crate::drs::definitions::DRS_TABLE_INFO_SIZE;
+use crate::drs::definitions::DRS_VERSION_SIZE;
+use crate::drs::drs::DRSFile;
+
+/// Copyright string used by the original game archives.
+pub const DRS_DEFAULT_COPYRIGHT: &[u8] = b"Copyright (c) 1997 Ensemble Studios.\x1A";
+
+/// Version string used by the original game archives.
+pub const DRS_DEFAULT_VERSION: &[u8; DRS_VERSION_SIZE] = b"1.00";
+
+/// Archive type string used by the original game archives.
+pub const DRS_DEFAULT_ARCHIVE_TYPE: &[u8] = b"tribe";
+
+/// Get the DRS table extension for a file extension.
+///
+/// The game stores `.bin` files in `bina` tables. Other extensions are
+/// lowercased and truncated to the 4 bytes of a table extension.
+///
+/// # Arguments
+///
+/// * `extension` - File extension, e.g. `slp`.
+///
+/// # Returns
+///
+/// Table extension or `None` if the extension is empty or not ASCII.
+pub fn get_table_extension(extension: &str) -> Option<String> {
+	if extension.is_empty() || !extension.is_ascii() {
+		return None;
+	}
+
+	let extension = extension.to_ascii_lowercase();
+	if extension == "bin" {
+		return Some("bina".to_string());
+	}
+
+	return Some(extension[..extension.len().min(DRS_EXTENSION_SIZE)].to_string());
+}
+
+/// Writer for DRS archives.
+///
+/// Files are grouped into one table per file extension. Tables are written
+/// in the order their extensions were first added and files are sorted by
+/// resource ID, so the same content always produces the same archive.
+#[derive(Debug, Clone)]
+pub struct DRSWriter {
+	/// Copyright string.
+	copyright: [u8; DRS_COPYRIGHT_SIZE],
+	/// Version string.
+	version: [u8; DRS_VERSION_SIZE],
+	/// Archive type string.
+	archive_type: [u8; DRS_ARCHIVE_TYPE_SIZE],
+	/// File contents by resource ID, grouped by file extension.
+	tables: Vec<(String, BTreeMap<u32, Vec<u8>>)>,
+}
+
+impl Default for DRSWriter {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl DRSWriter {
+	/// Create a new writer for an empty archive with the original
+	/// header strings.
+	///
+	/// # Returns
+	///
+	/// New DRS writer.
+	pub fn new() -> Self {
+		let mut copyright = [0; DRS_COPYRIGHT_SIZE];
+		copyright[..DRS_DEFAULT_COPYRIGHT.len()].copy_from_slice(DRS_DEFAULT_COPYRIGHT);
+		let mut archive_type = [0; DRS_ARCHIVE_TYPE_SIZE];
+		archive_type[..DRS_DEFAULT_ARCHIVE_TYPE.len()].copy_from_slice(DRS_DEFAULT_ARCHIVE_TYPE);
+
+		Self {
+			copyright,
+			version: *DRS_DEFAULT_VERSION,
+			archive_type,
+			tables: Vec::new(),
+		}
+	}
+
+	/// Create a writer containing the header and all files of an archive.
+	///
+	/// # Arguments
+	///
+	/// * `drs` - Archive to repack.
+	///
+	/// # Returns
+	///
+	/// New DRS writer.
+	pub fn from_drs(drs: &DRSFile) -> Self {
+		let mut writer = Self {
+			copyright: drs.header.copyright,
+			version: drs.header.version,
+			archive_type: drs.header.archive_type,
+			tables: Vec::new(),
+		};
+
+		for table in &drs.tables {
+			let extension = table.info.get_extension();
+			for entry in &table.entries {
+				writer.set_file(&extension, entry.id, drs.get_entry_bytes(entry).to_vec());
+			}
+		}
+
+		return writer;
+	}
+
+	/// Add a file or replace the file with the same extension and ID.
+	///
+	/// # Arguments
+	///
+	/// * `extension` - File extension, e.g. `slp`.
+	/// * `id` - Resource ID of the file.
+	/// * `content` - File content.
+	///
+	/// # Panics
+	///
+	/// Panics if the extension is empty or longer than 4 bytes.
+	pub fn set_file(
+		&mut self,
+		extension: &str,
+		id: u32,
+		content: Vec<u8>,
+	) {
+		assert!(
+			!extension.is_empty() && extension.len() <= DRS_EXTENSION_SIZE,
+			"DRS extensions must have 1 to {DRS_EXTENSION_SIZE} bytes, got '{extension}'"
+		);
+
+		let index = self
+			.tables
+			.iter()
+			.position(|(ext, _)| ext == extension)
+			.unwrap_or_else(|| {
+				self.tables.push((extension.to_string(), BTreeMap::new()));
+				self.tables.len() - 1
+			});
+		let files = &mut self.tables[index].1;
+
+		let _ = files.insert(id, content);
+	}
+
+	/// Remove a file.
+	///
+	/// Tables without files are not written.
+	///
+	/// # Arguments
+	///
+	/// * `extension` - File extension.
+	/// * `id` - Resource ID of the file.
+	///
+	/// # Returns
+	///
+	/// Content of the removed file or `None` if there is no such file.
+	pub fn remove_file(
+		&mut self,
+		extension: &str,
+		id: u32,
+	) -> Option<Vec<u8>> {
+		return self
+			.tables
+			.iter_mut()
+			.find(|(ext, _)| ext == extension)
+			.and_then(|(_, files)| files.remove(&id));
+	}
+
+	/// Add all files in a directory.
+	///
+	/// Files must be named `<id>.<extension>`, e.g. `15000.slp`. Other files
+	/// and subdirectories are skipped. Files are added in sorted name order
+	/// to the table given by [`get_table_extension`].
+	///
+	/// # Arguments
+	///
+	/// * `directory` - Directory to read.
+	///
+	/// # Errors
+	///
+	/// Returns an error if the directory or one of the files cannot be read.
+	pub fn add_directory(
+		&mut self,
+		directory: &Path,
+	) -> io::Result<()> {
+		let mut paths = fs::read_dir(directory)?
+			.map(|entry| entry.map(|e| e.path()))
+			.collect::<io::Result<Vec<_>>>()?;
+		paths.sort();
+
+		for path in paths {
+			if !path.is_file() {
+				continue;
+			}
+
+			let id = path
+				.file_stem()
+				.and_then(|stem| stem.to_str())
+				.and_then(|stem| stem.parse::<u32>().ok());
+			let extension = path
+				.extension()
+				.and_then(|ext| ext.to_str())
+				.and_then(get_table_extension);
+
+			if let (Some(id), Some(extension)) = (id, extension) {
+				self.set_file(&extension, id, fs::read(&path)?);
+			}
+		}
+
+		return Ok(());
+	}
+
+	/// Encode the archive.
+	///
+	/// The header is followed by the table infos, the file tables and the
+	/// file contents in table order.
+	///
+	/// # Returns
+	///
+	/// Bytes of the archive.
+	///
+	/// # Panics
+	///
+	/// Panics if the archive is larger than 4 GiB.
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let tables = self
+			.tables
+			.iter()
+			.filter(|(_, files)| !files.is_empty())
+			.collect::<Vec<_>>();
+		let num_files = tables.iter().map(|(_, files)| files.len()).sum::<usize>();
+
+		let tables_offset = DRS_HEADER_SIZE + tables.len() * DRS_TABLE_INFO_SIZE;
+		let first_file_offset = tables_offset + num_files * DRS_FILE_ENTRY_SIZE;
+		let to_u32 = |value: usize| u32::try_from(value).expect("DRS archive exceeds 4 GiB");
+
+		let mut bytes = Vec::<u8>::new();
+		bytes.extend_from_slice(&self.copyright);
+		bytes.extend_from_slice(&self.version);
+		bytes.extend_from_slice(&self.archive_type);
+		bytes.extend_from_slice(&to_u32(tables.len()).to_le_bytes());
+		bytes.extend_from_slice(&to_u32(first_file_offset).to_le_bytes());
+
+		// table infos
+		let mut table_offset = tables_offset;
+		for (extension, files) in &tables {
+			// extensions are stored reversed and padded with spaces
+			let mut stored = [b' '; DRS_EXTENSION_SIZE];
+			stored[..extension.len()].copy_from_slice(extension.as_bytes());
+			stored.reverse();
+
+			bytes.extend_from_slice(&stored);
+			bytes.extend_from_slice(&to_u32(table_offset).to_le_bytes());
+			bytes.extend_from_slice(&to_u32(files.len()).to_le_bytes());
+			table_offset += files.len() * DRS_FILE_ENTRY_SIZE;
+		}
+
+		// file tables
+		let mut file_offset = first_file_offset;
+		for (_, files) in &tables {
+			for (id, content) in files {
+				bytes.extend_from_slice(&id.to_le_bytes());
+				bytes.extend_from_slice(&to_u32(file_offset).to_le_bytes());
+				bytes.extend_from_slice(&to_u32(content.len()).to_le_bytes());
+				file_offset += content.len();
+			}
+		}
+
+		// file contents
+		for (_, files) in &tables {
+			for content in files.values() {
+				bytes.extend_from_slice(content);
+			}
+		}
+
+		return bytes;
+	}
+
+	/// Write the archive.
+	///
+	/// # Arguments
+	///
+	/// * `writer` - Output stream.
+	///
+	/// # Errors
+	///
+	/// Returns an error if writing to the stream fails.
+	pub fn write<W: Write>(
+		&self,
+		writer: &mut W,
+	) -> io::Result<()> {
+		return writer.write_all(&self.to_bytes());
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::drs::drs::parse_drs;
+	use crate::drs::writer::get_table_extension;
+	use crate::drs::writer::DRSWriter;
+
+	/// Test that written archives can be read and repacked unchanged.
+	#[test]
+	fn write_and_repack_drs() {
+		let mut writer = DRSWriter::new();
+		writer.set_file("slp", 11, b"second".to_vec());
+		writer.set_file("slp", 10, b"first".to_vec());
+		writer.set_file("bina", 50500, b"palette".to_vec());
+		let bytes = writer.to_bytes();
+
+		let drs = parse_drs(&bytes).unwrap();
+		assert_eq!(drs.header.get_archive_type(), "tribe");
+		assert_eq!(drs.get_extensions(), ["slp", "bina"]);
+		assert_eq!(drs.tables[0].entries[0].id, 10);
+		assert_eq!(drs.get_file(11), Some(b"second".to_vec()));
+		assert_eq!(drs.get_file(50500), Some(b"palette".to_vec()));
+
+		let mut repacked = DRSWriter::from_drs(&drs);
+		assert_eq!(repacked.to_bytes(), bytes);
+
+		repacked.set_file("slp", 10, b"replaced".to_vec());
+		assert_eq!(
+			repacked.remove_file("bina", 50500),
+			Some(b"palette".to_vec())
+		);
+		let drs = parse_drs(&repacked.to_bytes()).unwrap();
+		assert_eq!(drs.get_extensions(), ["slp"]);
+		assert_eq!(drs.get_file(10), Some(b"replaced".to_vec()));
+	}
+
+	/// Test that directory files are added to the tables the game uses.
+	#[test]
+	fn add_directory_tables() {
+		assert_eq!(get_table_extension("BIN"), Some("bina".to_string()));
+		assert_eq!(get_table_extension("slpx5"), Some("slpx".to_string()));
+		assert_eq!(get_table_extension(""), None);
+
+		let directory = std::env::temp_dir().join(format!("slutils_drs_{}", std::process::id()));
+		std::fs::create_dir_all(&directory).unwrap();
+		std::fs::write(directory.join("50500.bin"), b"palette").unwrap();
+		std::fs::write(directory.join("15000.slp"), b"unit").unwrap();
+		std::fs::write(directory.join("readme.txt"), b"skipped").unwrap();
+
+		let mut writer = DRSWriter::new();
+		let result = writer.add_directory(&directory);
+		std::fs::remove_dir_all(&directory).unwrap();
+		result.unwrap();
+
+		let drs = parse_drs(&writer.to_bytes()).unwrap();
+		assert_eq!(drs.get_extensions(), ["slp", "bina"]);
+		assert_eq!(drs.get_file(50500), Some(b"palette".to_vec()));
+	}
+}
@@ -0,0 +1,392 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::io;
+use std::io::Write;
+use std::ops::Range;
+
+use crate::palette::definitions::PALETTE_MAX_COLORS;
+use crate::palette::palette::Palette;
+use crate::slp::frame::SLPFrame;
+use crate::slp::frame_info::SLPFrameInfo;
+use crate::slp::pixel::PalettePixel;
+use crate::slp::pixel::SLPPixelType;
+use crate::slp::slp::SLPFile;
+use crate::util::canvas::AnchoredCanvas;
+
+/// Options for exporting frames as an animation.
+#[derive(Debug, Clone, Copy)]
+pub struct AnimationOptions {
+	/// Delay between two frames (in milliseconds).
+	pub frame_delay: u16,
+	/// Number of times the animation is played. 0 loops forever.
+	pub loop_count: u16,
+	/// Player number used for drawing player color pixels.
+	pub player: u8,
+	/// Palette index used for drawing shadow pixels. If `None`, shadows are
+	/// left transparent.
+	pub shadow_index: Option<u8>,
+}
+
+impl Default for AnimationOptions {
+	fn default() -> Self {
+		Self {
+			frame_delay: 100,
+			loop_count: 0,
+			player: 1,
+			shadow_index: None,
+		}
+	}
+}
+
+/// Get the palette index a pixel is drawn with in an animation.
+///
+/// # Arguments
+///
+/// * `pixel` - Pixel to draw.
+/// * `options` - Animation options.
+///
+/// # Returns
+///
+/// Palette index or `None` if the pixel is transparent.
+const fn get_animation_index(
+	pixel: PalettePixel,
+	options: AnimationOptions,
+) -> Option<u8> {
+	match pixel.pixel_type {
+		SLPPixelType::Palette => Some(pixel.index),
+		SLPPixelType::Player | SLPPixelType::PlayerV4 => {
+			Some(pixel.get_player_index(options.player))
+		}
+		SLPPixelType::Shadow | SLPPixelType::ShadowV4 => options.shadow_index,
+		// outlines are only visible when the unit is occluded
+		SLPPixelType::Transparent | SLPPixelType::Special1 | SLPPixelType::Special2 => None,
+	}
+}
+
+/// Draw a frame onto an animation canvas.
+///
+/// # Arguments
+///
+/// * `frame` - Frame to draw.
+/// * `frame_info` - Frame info of the frame.
+/// * `canvas` - Canvas the frame is drawn on.
+/// * `options` - Animation options.
+///
+/// # Returns
+///
+/// Palette indices of the canvas pixels, `None` for transparent pixels.
+fn draw_frame(
+	frame: &SLPFrame<PalettePixel>,
+	frame_info: &SLPFrameInfo,
+	canvas: &AnchoredCanvas,
+	options: AnimationOptions,
+) -> Vec<Option<u8>> {
+	let mut indices = vec![None; canvas.width * canvas.height];
+	// the canvas fits every anchor, so frame positions are never negative
+	let (frame_x, frame_y) = canvas.get_frame_position(frame_info);
+	let frame_x = usize::try_from(frame_x).unwrap_or(0);
+	let frame_y = usize::try_from(frame_y).unwrap_or(0);
+
+	for (i, row) in frame.get_pixels().iter().enumerate() {
+		let y = frame_y + i;
+		if y >= canvas.height {
+			break;
+		}
+		// pixels beyond the size stored in the frame info are clipped
+		for (j, pixel) in row
+			.iter()
+			.enumerate()
+			.take(canvas.width.saturating_sub(frame_x))
+		{
+			indices[y * canvas.width + frame_x + j] = get_animation_index(*pixel, options);
+		}
+	}
+
+	return indices;
+}
+
+/// Draw all frames in a range onto a common canvas.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file containing the frames.
+/// * `frames` - Indices of the frames in the animation.
+/// * `options` - Animation options.
+///
+/// # Returns
+///
+/// Canvas and the drawn frames.
+fn draw_animation(
+	slp: &SLPFile,
+	frames: Range<usize>,
+	options: AnimationOptions,
+) -> (AnchoredCanvas, Vec<Vec<Option<u8>>>) {
+	let canvas = AnchoredCanvas::from_frame_infos(&slp.frame_infos[frames.clone()]);
+
+	let drawn = frames
+		.map(|i| draw_frame(&slp.frames[i], &slp.frame_infos[i], &canvas, options))
+		.collect();
+
+	return (canvas, drawn);
+}
+
+/// Choose the palette index used for transparent pixels in a GIF.
+///
+/// An index that no visible pixel uses is preferred. If every index is
+/// used, the least used index is freed by drawing its pixels with the
+/// closest other palette color.
+///
+/// # Arguments
+///
+/// * `drawn` - Palette indices of the drawn frames.
+/// * `palette` - Palette used by the frames.
+///
+/// # Returns
+///
+/// Transparent index and the index its visible pixels are drawn with.
+fn get_transparent_index(
+	drawn: &[Vec<Option<u8>>],
+	palette: &Palette,
+) -> (u8, u8) {
+	let mut counts = [0usize; PALETTE_MAX_COLORS];
+	for index in drawn.iter().flatten().flatten() {
+		counts[*index as usize] += 1;
+	}
+
+	let (transparent_index, _) = counts
+		.iter()
+		.enumerate()
+		.rev()
+		.min_by_key(|(_, &count)| count)
+		.unwrap();
+	// the counts have 256 entries
+	#[allow(clippy::cast_possible_truncation)]
+	let transparent_index = transparent_index as u8;
+	if counts[transparent_index as usize] == 0 {
+		return (transparent_index, transparent_index);
+	}
+
+	let color = palette.get_color(transparent_index).unwrap_or([0; 4]);
+	let rgb = [color[0], color[1], color[2]];
+	let num_colors = palette.get_num_colors();
+	let below = palette.find_closest_color_in(rgb, 0..transparent_index as usize);
+	let above = palette.find_closest_color_in(rgb, transparent_index as usize + 1..num_colors);
+	let distance = |index: u8| -> u32 {
+		let other = palette.get_color(index).unwrap_or([0; 4]);
+		return (0..3)
+			.map(|c| u32::from(rgb[c].abs_diff(other[c])).pow(2))
+			.sum();
+	};
+	let replacement = if transparent_index == 0
+		|| (usize::from(transparent_index) + 1 < num_colors && distance(above) < distance(below))
+	{
+		above
+	}
+	else {
+		below
+	};
+
+	return (transparent_index, replacement);
+}
+
+/// Export frames of an SLP file as an animated GIF.
+///
+/// The palette is used as the global color table of the GIF, so the palette
+/// indices of the frame pixels are written unchanged. Transparent pixels use
+/// a palette index that is not drawn in any frame. If all 256 indices are
+/// drawn, the least used color is replaced by its closest match.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file containing the frames.
+/// * `frames` - Indices of the frames in the animation.
+/// * `palette` - Palette used by the SLP file.
+/// * `options` - Animation options.
+/// * `writer` - Output the GIF is written to.
+///
+/// # Errors
+///
+/// Returns an error if the frames do not fit into a GIF or writing the GIF
+/// fails.
+pub fn export_gif<W: Write>(
+	slp: &SLPFile,
+	frames: Range<usize>,
+	palette: &Palette,
+	options: AnimationOptions,
+	writer: W,
+) -> io::Result<()> {
+	let (canvas, drawn) = draw_animation(slp, frames, options);
+
+	let width = u16::try_from(canvas.width).map_err(io::Error::other)?;
+	let height = u16::try_from(canvas.height).map_err(io::Error::other)?;
+
+	let (transparent_index, replacement) = get_transparent_index(&drawn, palette);
+
+	let mut encoder = gif::Encoder::new(writer, width, height, &palette.to_rgb_bytes())
+		.map_err(io::Error::other)?;
+	let repeat = match options.loop_count {
+		0 => gif::Repeat::Infinite,
+		n => gif::Repeat::Finite(n),
+	};
+	encoder.set_repeat(repeat).map_err(io::Error::other)?;
+
+	for indices in drawn {
+		let buffer = indices
+			.iter()
+			.map(|index| match index {
+				Some(index) if *index == transparent_index => replacement,
+				Some(index) => *index,
+				None => transparent_index,
+			})
+			.collect::<Vec<u8>>();
+
+		let frame = gif::Frame {
+			width,
+			height,
+			// GIF delays are stored in units of 10ms
+			delay: options.frame_delay / 10,
+			dispose: gif::DisposalMethod::Background,
+			transparent: Some(transparent_index),
+			buffer: buffer.into(),
+			..gif::Frame::default()
+		};
+		encoder.write_frame(&frame).map_err(io::Error::other)?;
+	}
+
+	return Ok(());
+}
+
+/// Export frames of an SLP file as an animated PNG (APNG).
+///
+/// # Arguments
+///
+/// * `slp` - SLP file containing the frames.
+/// * `frames` - Indices of the frames in the animation.
+/// * `palette` - Palette used by the SLP file.
+/// * `options` - Animation options.
+/// * `writer` - Output the APNG is written to.
+///
+/// # Errors
+///
+/// Returns an error if the frames do not fit into a PNG or writing the PNG
+/// fails.
+pub fn export_apng<W: Write>(
+	slp: &SLPFile,
+	frames: Range<usize>,
+	palette: &Palette,
+	options: AnimationOptions,
+	writer: W,
+) -> io::Result<()> {
+	let (canvas, drawn) = draw_animation(slp, frames, options);
+
+	let width = u32::try_from(canvas.width).map_err(io::Error::other)?;
+	let height = u32::try_from(canvas.height).map_err(io::Error::other)?;
+	let num_frames = u32::try_from(drawn.len()).map_err(io::Error::other)?;
+
+	let mut encoder = png::Encoder::new(writer, width, height);
+	encoder.set_color(png::ColorType::Rgba);
+	encoder.set_depth(png::BitDepth::Eight);
+	encoder
+		.set_animated(num_frames, u32::from(options.loop_count))
+		.map_err(io::Error::other)?;
+	encoder
+		.set_frame_delay(options.frame_delay, 1000)
+		.map_err(io::Error::other)?;
+	encoder
+		.set_dispose_op(png::DisposeOp::Background)
+		.map_err(io::Error::other)?;
+
+	let mut png_writer = encoder.write_header().map_err(io::Error::other)?;
+	for indices in drawn {
+		let mut data = Vec::<u8>::with_capacity(indices.len() * 4);
+		for index in indices {
+			let rgba = index
+				.and_then(|i| palette.get_color(i))
+				.unwrap_or([0, 0, 0, 0]);
+			data.extend_from_slice(&rgba);
+		}
+		png_writer
+			.write_image_data(&data)
+			.map_err(io::Error::other)?;
+	}
+	png_writer.finish().map_err(io::Error::other)?;
+
+	return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::export::animation::export_apng;
+	use crate::export::animation::export_gif;
+	use crate::export::animation::get_transparent_index;
+	use crate::export::animation::AnimationOptions;
+	use crate::palette::palette::Palette;
+	use crate::slp::frame::SLPFrame;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::SLPPixelType;
+	use crate::slp::slp::SLPFile;
+
+	/// Create an SLP file with two 2x1 frames.
+	fn create_slp() -> SLPFile {
+		let transparent = PalettePixel::new(SLPPixelType::Transparent, 0);
+		let frames = (1..=2)
+			.map(|index| {
+				let pixel = PalettePixel::new(SLPPixelType::Palette, index);
+				SLPFrame::new(Vec::new(), Vec::new(), vec![vec![pixel, transparent]])
+			})
+			.collect::<Vec<_>>();
+
+		return SLPFile::from_palette_frames(frames);
+	}
+
+	/// Test the frames, delays and transparency of exported animations.
+	#[test]
+	fn export_animations() {
+		let slp = create_slp();
+		let palette = Palette::new(vec![[0, 0, 0, 255], [255, 0, 0, 255], [0, 255, 0, 255]]);
+		let options = AnimationOptions {
+			frame_delay: 200,
+			..AnimationOptions::default()
+		};
+
+		let mut gif_bytes = Vec::<u8>::new();
+		export_gif(&slp, 0..2, &palette, options, &mut gif_bytes).unwrap();
+		let mut decode_options = gif::DecodeOptions::new();
+		decode_options.set_color_output(gif::ColorOutput::Indexed);
+		let mut decoder = decode_options.read_info(gif_bytes.as_slice()).unwrap();
+		let mut num_frames = 0;
+		while let Some(frame) = decoder.read_next_frame().unwrap() {
+			num_frames += 1;
+			assert_eq!(frame.delay, 20);
+			assert_eq!(frame.transparent, Some(255));
+			assert_eq!(frame.buffer.as_ref(), [num_frames, 255]);
+		}
+		assert_eq!(num_frames, 2);
+
+		let mut png_bytes = Vec::<u8>::new();
+		export_apng(&slp, 0..2, &palette, options, &mut png_bytes).unwrap();
+		let mut reader = png::Decoder::new(png_bytes.as_slice()).read_info().unwrap();
+		assert_eq!(reader.info().animation_control.unwrap().num_frames, 2);
+		let mut buffer = vec![0; reader.output_buffer_size()];
+		let _ = reader.next_frame(&mut buffer).unwrap();
+		let control = reader.info().frame_control.unwrap();
+		assert_eq!((control.delay_num, control.delay_den), (200, 1000));
+		assert_eq!(buffer, [255, 0, 0, 255, 0, 0, 0, 0]);
+	}
+
+	/// Test that a full palette frees its least used color for transparency.
+	#[test]
+	fn transparent_index_full_palette() {
+		let mut colors = (0..=255).map(|i| [i, i, i, 255]).collect::<Vec<[u8; 4]>>();
+		colors[7] = [200, 0, 0, 255];
+		colors[9] = [201, 0, 0, 255];
+		let palette = Palette::new(colors);
+
+		let mut indices = (0..=255).map(Some).collect::<Vec<Option<u8>>>();
+		indices.extend((0..=255).filter(|&i| i != 9).map(Some));
+		assert_eq!(get_transparent_index(&[indices], &palette), (9, 7));
+
+		let free = vec![Some(1), None];
+		assert_eq!(get_transparent_index(&[free], &palette), (255, 255));
+	}
+}
@@ -0,0 +1,384 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::palette::palette::Palette;
+use crate::slp::slp::SLPFile;
+use crate::util::image::RGBAImageConvertible;
+use crate::util::rect_pack::MaxRectsPacker;
+use crate::util::rect_pack::Rect;
+
+/// Options for packing frames into texture atlases.
+#[derive(Debug, Clone, Copy)]
+pub struct AtlasOptions {
+	/// Width of an atlas texture.
+	pub width: u32,
+	/// Height of an atlas texture.
+	pub height: u32,
+	/// Number of empty pixels between frames and around the atlas border.
+	pub padding: u32,
+	/// Remove fully transparent rows and columns around the frames.
+	pub trim: bool,
+}
+
+impl Default for AtlasOptions {
+	fn default() -> Self {
+		Self {
+			width: 4096,
+			height: 4096,
+			padding: 1,
+			trim: false,
+		}
+	}
+}
+
+/// Location of a frame in a texture atlas.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtlasEntry {
+	/// Name of the file the frame belongs to.
+	pub file: String,
+	/// Index of the frame in the file.
+	pub frame: usize,
+	/// Index of the atlas texture containing the frame.
+	pub atlas: usize,
+	/// Area of the frame in the atlas texture.
+	pub rect: Rect,
+	/// X coordinate of the anchor point relative to the frame area.
+	pub anchor_x: i32,
+	/// Y coordinate of the anchor point relative to the frame area.
+	pub anchor_y: i32,
+}
+
+/// Set of atlas textures and the manifest of the frames packed into them.
+#[derive(Debug, Clone)]
+pub struct TextureAtlas {
+	/// Atlas textures.
+	pub images: Vec<image::RgbaImage>,
+	/// Manifest entries, one for every packed frame.
+	pub entries: Vec<AtlasEntry>,
+}
+
+impl TextureAtlas {
+	/// Get the entry for a frame.
+	///
+	/// # Arguments
+	///
+	/// * `file` - Name of the file the frame belongs to.
+	/// * `frame` - Index of the frame in the file.
+	///
+	/// # Returns
+	///
+	/// Manifest entry or `None` if the frame is not in the atlas.
+	pub fn get_entry(
+		&self,
+		file: &str,
+		frame: usize,
+	) -> Option<&AtlasEntry> {
+		return self
+			.entries
+			.iter()
+			.find(|entry| entry.file == file && entry.frame == frame);
+	}
+
+	/// Save the atlas textures and the manifest to a directory.
+	///
+	/// Textures are saved as `<name>_<index>.png` and the manifest as
+	/// `<name>.csv`.
+	///
+	/// # Arguments
+	///
+	/// * `directory` - Output directory.
+	/// * `name` - Base name of the output files.
+	///
+	/// # Errors
+	///
+	/// Returns an error if one of the files cannot be written.
+	pub fn save(
+		&self,
+		directory: &Path,
+		name: &str,
+	) -> image::ImageResult<()> {
+		for (i, image) in self.images.iter().enumerate() {
+			image.save(directory.join(format!("{name}_{i}.png")))?;
+		}
+		fs::write(directory.join(format!("{name}.csv")), self.to_string())?;
+
+		return Ok(());
+	}
+}
+
+impl fmt::Display for TextureAtlas {
+	/// Write the manifest as CSV.
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		writeln!(f, "file,frame,atlas,x,y,width,height,anchor_x,anchor_y")?;
+		for entry in &self.entries {
+			writeln!(
+				f,
+				"{},{},{},{},{},{},{},{},{}",
+				entry.file,
+				entry.frame,
+				entry.atlas,
+				entry.rect.x,
+				entry.rect.y,
+				entry.rect.width,
+				entry.rect.height,
+				entry.anchor_x,
+				entry.anchor_y
+			)?;
+		}
+
+		Ok(())
+	}
+}
+
+/// Frame that is waiting to be packed.
+struct AtlasItem {
+	/// Name of the file the frame belongs to.
+	file: String,
+	/// Index of the frame in the file.
+	frame: usize,
+	/// Frame image, trimmed if requested.
+	image: image::RgbaImage,
+	/// X coordinate of the anchor point relative to the image.
+	anchor_x: i32,
+	/// Y coordinate of the anchor point relative to the image.
+	anchor_y: i32,
+}
+
+/// Get the area of an image that contains non-transparent pixels.
+///
+/// # Arguments
+///
+/// * `image` - Image to check.
+///
+/// # Returns
+///
+/// Area with visible pixels or `None` if the image is fully transparent.
+fn get_visible_area(image: &image::RgbaImage) -> Option<Rect> {
+	let mut min_x = u32::MAX;
+	let mut min_y = u32::MAX;
+	let mut max_x = 0;
+	let mut max_y = 0;
+
+	for (x, y, pixel) in image.enumerate_pixels() {
+		if pixel[3] == 0 {
+			continue;
+		}
+		min_x = min_x.min(x);
+		min_y = min_y.min(y);
+		max_x = max_x.max(x);
+		max_y = max_y.max(y);
+	}
+
+	if min_x > max_x {
+		return None;
+	}
+
+	return Some(Rect::new(
+		min_x,
+		min_y,
+		max_x - min_x + 1,
+		max_y - min_y + 1,
+	));
+}
+
+/// Pack the frames of multiple SLP files into texture atlases.
+///
+/// Frames are sorted by size and placed with the `MaxRects` algorithm. A new
+/// atlas texture is started whenever a frame does not fit into any of the
+/// existing ones. Fully transparent frames are not placed and get an empty
+/// area in the first atlas.
+///
+/// # Arguments
+///
+/// * `files` - Names and contents of the SLP files.
+/// * `palette` - Palette used by the SLP files.
+/// * `options` - Atlas options.
+///
+/// # Returns
+///
+/// Atlas textures and manifest.
+///
+/// # Errors
+///
+/// Returns an error if a frame is larger than an atlas texture.
+///
+/// # Panics
+///
+/// Panics if a frame is too large for its anchor to be moved when trimming.
+pub fn pack_atlas(
+	files: &[(&str, &SLPFile)],
+	palette: &Palette,
+	options: AtlasOptions,
+) -> io::Result<TextureAtlas> {
+	let lookup = palette.get_lookup();
+
+	let mut items = Vec::<AtlasItem>::new();
+	for (file, slp) in files {
+		for (i, (frame, frame_info)) in slp.frames.iter().zip(&slp.frame_infos).enumerate() {
+			let mut image = frame.to_image(&lookup);
+			let mut anchor_x = frame_info.data.anchor_x;
+			let mut anchor_y = frame_info.data.anchor_y;
+
+			if options.trim {
+				let area = get_visible_area(&image).unwrap_or(Rect::new(0, 0, 0, 0));
+				image = image::imageops::crop_imm(&image, area.x, area.y, area.width, area.height)
+					.to_image();
+				anchor_x -= i32::try_from(area.x).unwrap();
+				anchor_y -= i32::try_from(area.y).unwrap();
+			}
+
+			items.push(AtlasItem {
+				file: (*file).to_string(),
+				frame: i,
+				image,
+				anchor_x,
+				anchor_y,
+			});
+		}
+	}
+
+	// place large frames first; the sort is stable so the output is
+	// deterministic
+	let mut order = (0..items.len()).collect::<Vec<usize>>();
+	order.sort_by_key(|&i| {
+		let (width, height) = items[i].image.dimensions();
+		std::cmp::Reverse((width.max(height), width * height))
+	});
+
+	let padding = options.padding;
+	let mut packers = Vec::<MaxRectsPacker>::new();
+	let mut placements = vec![(0, Rect::new(0, 0, 0, 0)); items.len()];
+	for i in order {
+		let (width, height) = items[i].image.dimensions();
+		if width == 0 || height == 0 {
+			continue;
+		}
+
+		let padded_width = width + padding;
+		let padded_height = height + padding;
+
+		let mut placed = packers.iter_mut().enumerate().find_map(|(atlas, packer)| {
+			packer
+				.insert(padded_width, padded_height)
+				.map(|rect| (atlas, rect))
+		});
+
+		if placed.is_none() {
+			let mut packer = MaxRectsPacker::new(
+				options.width.saturating_sub(padding),
+				options.height.saturating_sub(padding),
+			);
+			let rect = packer.insert(padded_width, padded_height).ok_or_else(|| {
+				io::Error::new(
+					io::ErrorKind::InvalidInput,
+					format!(
+						"Frame {} of {} ({}x{}) does not fit into a {}x{} atlas",
+						items[i].frame, items[i].file, width, height, options.width, options.height
+					),
+				)
+			})?;
+			packers.push(packer);
+			placed = Some((packers.len() - 1, rect));
+		}
+
+		let (atlas, rect) = placed.unwrap();
+		placements[i] = (
+			atlas,
+			Rect::new(rect.x + padding, rect.y + padding, width, height),
+		);
+	}
+
+	let mut images = (0..packers.len())
+		.map(|_| image::RgbaImage::new(options.width, options.height))
+		.collect::<Vec<image::RgbaImage>>();
+
+	let mut entries = Vec::<AtlasEntry>::with_capacity(items.len());
+	for (item, (atlas, rect)) in items.into_iter().zip(placements) {
+		if rect.width > 0 && rect.height > 0 {
+			image::imageops::replace(
+				&mut images[atlas],
+				&item.image,
+				i64::from(rect.x),
+				i64::from(rect.y),
+			);
+		}
+
+		entries.push(AtlasEntry {
+			file: item.file,
+			frame: item.frame,
+			atlas,
+			rect,
+			anchor_x: item.anchor_x,
+			anchor_y: item.anchor_y,
+		});
+	}
+
+	return Ok(TextureAtlas { images, entries });
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::export::atlas::pack_atlas;
+	use crate::export::atlas::AtlasOptions;
+	use crate::palette::palette::Palette;
+	use crate::slp::frame::SLPFrame;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::SLPPixelType;
+	use crate::slp::slp::SLPFile;
+	use crate::util::rect_pack::Rect;
+
+	/// Test the manifest of packed frames and frames that do not fit.
+	#[test]
+	fn pack_frames() {
+		let transparent = PalettePixel::new(SLPPixelType::Transparent, 0);
+		let color = PalettePixel::new(SLPPixelType::Palette, 1);
+		let frames = vec![
+			SLPFrame::new(Vec::new(), Vec::new(), vec![vec![color; 4]; 3]),
+			SLPFrame::new(
+				Vec::new(),
+				Vec::new(),
+				vec![vec![transparent; 3], vec![transparent, color, color]],
+			),
+		];
+		let mut slp = SLPFile::from_palette_frames(frames);
+		for (frame_info, (anchor_x, anchor_y)) in slp.frame_infos.iter_mut().zip([(2, 1), (1, 2)]) {
+			frame_info.data.anchor_x = anchor_x;
+			frame_info.data.anchor_y = anchor_y;
+		}
+		let palette = Palette::new(vec![[0, 0, 0, 255], [10, 20, 30, 255]]);
+		let options = AtlasOptions {
+			width: 16,
+			height: 16,
+			padding: 1,
+			trim: true,
+		};
+
+		let atlas = pack_atlas(&[("unit", &slp)], &palette, options).unwrap();
+		assert_eq!(atlas.images.len(), 1);
+		let first = atlas.get_entry("unit", 0).unwrap();
+		assert_eq!(first.rect, Rect::new(1, 1, 4, 3));
+		assert_eq!((first.anchor_x, first.anchor_y), (2, 1));
+		let second = atlas.get_entry("unit", 1).unwrap();
+		assert_eq!((second.rect.width, second.rect.height), (2, 1));
+		assert_eq!((second.anchor_x, second.anchor_y), (0, 1));
+		assert_eq!(
+			atlas.images[0].get_pixel(second.rect.x, second.rect.y).0,
+			[10, 20, 30, 255]
+		);
+		assert!(atlas.to_string().contains("unit,0,0,1,1,4,3,2,1\n"));
+
+		let small = AtlasOptions {
+			width: 3,
+			height: 3,
+			..options
+		};
+		assert!(pack_atlas(&[("unit", &slp)], &palette, small).is_err());
+	}
+}
@@ -0,0 +1,319 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::path::Path;
+
+use crate::palette::palette::Palette;
+use crate::render::compositor::composite_slp_frame;
+use crate::render::compositor::CompositorOptions;
+use crate::render::compositor::RenderLayers;
+use crate::slp::frame::SLPFrame;
+use crate::slp::pixel::PalettePixel;
+use crate::slp::pixel::SLPPixelType;
+use crate::slp::slp::SLPFile;
+use crate::util::canvas::AnchoredCanvas;
+
+/// Layout of the exported mask images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaskLayout {
+	/// One single-channel image per mask.
+	Separate,
+	/// All masks packed into the channels of one RGBA image.
+	///
+	/// The channels are: R = player color, G = shadow, B = player color
+	/// outline (`Special1`), A = black outline (`Special2`).
+	Packed,
+}
+
+/// Base color image and masks of the special pixel types in a frame.
+#[derive(Debug, Clone)]
+pub struct FrameMasks {
+	/// Palette and player colors of the frame.
+	pub base: image::RgbaImage,
+	/// Player color pixels.
+	pub player: image::GrayImage,
+	/// Shadow pixels. The value is the shadow opacity.
+	pub shadow: image::GrayImage,
+	/// Player color outline pixels (`Special1`).
+	pub player_outline: image::GrayImage,
+	/// Black outline pixels (`Special2`).
+	pub black_outline: image::GrayImage,
+}
+
+impl FrameMasks {
+	/// Pack the masks into the channels of one RGBA image.
+	///
+	/// # Returns
+	///
+	/// Image with the masks in the channels described by
+	/// [`MaskLayout::Packed`].
+	pub fn to_packed(&self) -> image::RgbaImage {
+		return image::RgbaImage::from_fn(self.base.width(), self.base.height(), |x, y| {
+			image::Rgba([
+				self.player.get_pixel(x, y)[0],
+				self.shadow.get_pixel(x, y)[0],
+				self.player_outline.get_pixel(x, y)[0],
+				self.black_outline.get_pixel(x, y)[0],
+			])
+		});
+	}
+
+	/// Save the base image and the masks as PNG files.
+	///
+	/// The base image is saved as `<name>.png`. Separate masks are saved as
+	/// `<name>_player.png`, `<name>_shadow.png`, `<name>_outline1.png` and
+	/// `<name>_outline2.png`. Packed masks are saved as `<name>_masks.png`.
+	///
+	/// # Arguments
+	///
+	/// * `directory` - Output directory.
+	/// * `name` - Base name of the output files.
+	/// * `layout` - Layout of the mask images.
+	///
+	/// # Errors
+	///
+	/// Returns an error if one of the files cannot be written.
+	pub fn save(
+		&self,
+		directory: &Path,
+		name: &str,
+		layout: MaskLayout,
+	) -> image::ImageResult<()> {
+		self.base.save(directory.join(format!("{name}.png")))?;
+
+		match layout {
+			MaskLayout::Separate => {
+				self.player
+					.save(directory.join(format!("{name}_player.png")))?;
+				self.shadow
+					.save(directory.join(format!("{name}_shadow.png")))?;
+				self.player_outline
+					.save(directory.join(format!("{name}_outline1.png")))?;
+				self.black_outline
+					.save(directory.join(format!("{name}_outline2.png")))?;
+			}
+			MaskLayout::Packed => {
+				self.to_packed()
+					.save(directory.join(format!("{name}_masks.png")))?;
+			}
+		}
+
+		return Ok(());
+	}
+}
+
+/// Draw the mask values of a frame onto a mask image.
+///
+/// # Arguments
+///
+/// * `mask` - Mask image to draw on.
+/// * `frame` - Frame to draw.
+/// * `position` - Position of the frame's top left corner in the mask.
+/// * `get_value` - Function returning the mask value of a pixel or `None` if
+///   the pixel is not part of the mask.
+fn draw_mask<F>(
+	mask: &mut image::GrayImage,
+	frame: &SLPFrame<PalettePixel>,
+	position: (i32, i32),
+	get_value: F,
+) where
+	F: Fn(PalettePixel) -> Option<u8>,
+{
+	for (i, row) in frame.get_pixels().iter().enumerate() {
+		for (j, pixel) in row.iter().enumerate() {
+			if let Some(value) = get_value(*pixel) {
+				let x = u32::try_from(position.0).unwrap() + u32::try_from(j).unwrap();
+				let y = u32::try_from(position.1).unwrap() + u32::try_from(i).unwrap();
+				mask.put_pixel(x, y, image::Luma([value]));
+			}
+		}
+	}
+}
+
+/// Get the shadow mask value of a pixel.
+///
+/// # Arguments
+///
+/// * `pixel` - Pixel to check.
+///
+/// # Returns
+///
+/// Shadow opacity or `None` if the pixel is not a shadow pixel.
+const fn get_shadow_value(pixel: PalettePixel) -> Option<u8> {
+	match pixel.pixel_type {
+		SLPPixelType::Shadow => Some(u8::MAX),
+		// shadow frames store the intensity as the color value
+		SLPPixelType::ShadowV4 => Some(pixel.index),
+		_ => None,
+	}
+}
+
+/// Extract the base color image and the special pixel masks of a frame.
+///
+/// All images have the same size. If the file has shadow frames, the shadow
+/// frame is aligned with the main frame on the anchor points.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file containing the frame.
+/// * `index` - Index of the frame.
+/// * `palette` - Palette used by the frames.
+/// * `player` - Player number used for player colors in the base image.
+///
+/// # Returns
+///
+/// Base image and masks of the frame or `None` if the index is out of range
+/// or the file has 32-bit frames.
+///
+/// # Panics
+///
+/// Panics if the frames are too large for an image.
+pub fn extract_masks(
+	slp: &SLPFile,
+	index: usize,
+	palette: &Palette,
+	player: u8,
+) -> Option<FrameMasks> {
+	let base_options = CompositorOptions {
+		layers: RenderLayers {
+			shadow: false,
+			main: true,
+			player: true,
+			player_outline: false,
+			black_outline: false,
+		},
+		player,
+		..CompositorOptions::default()
+	};
+	let base = composite_slp_frame(slp, index, palette, base_options)?.image;
+
+	let frame = &slp.frames[index];
+	let frame_info = &slp.frame_infos[index];
+	let shadow = slp
+		.shadow_frames
+		.get(index)
+		.zip(slp.shadow_frame_infos.get(index));
+
+	let canvas = match shadow {
+		Some((_, shadow_info)) => AnchoredCanvas::from_frame_infos([frame_info, shadow_info]),
+		None => AnchoredCanvas::from_frame_infos([frame_info]),
+	};
+	let position = canvas.get_frame_position(frame_info);
+	let (width, height) = base.dimensions();
+
+	let mut player_mask = image::GrayImage::new(width, height);
+	draw_mask(&mut player_mask, frame, position, |pixel| {
+		matches!(
+			pixel.pixel_type,
+			SLPPixelType::Player | SLPPixelType::PlayerV4
+		)
+		.then_some(u8::MAX)
+	});
+
+	let mut shadow_mask = image::GrayImage::new(width, height);
+	if let Some((shadow_frame, shadow_info)) = shadow {
+		draw_mask(
+			&mut shadow_mask,
+			shadow_frame,
+			canvas.get_frame_position(shadow_info),
+			get_shadow_value,
+		);
+	}
+	draw_mask(&mut shadow_mask, frame, position, get_shadow_value);
+
+	let mut player_outline = image::GrayImage::new(width, height);
+	draw_mask(&mut player_outline, frame, position, |pixel| {
+		(pixel.pixel_type == SLPPixelType::Special1).then_some(u8::MAX)
+	});
+
+	let mut black_outline = image::GrayImage::new(width, height);
+	draw_mask(&mut black_outline, frame, position, |pixel| {
+		(pixel.pixel_type == SLPPixelType::Special2).then_some(u8::MAX)
+	});
+
+	return Some(FrameMasks {
+		base,
+		player: player_mask,
+		shadow: shadow_mask,
+		player_outline,
+		black_outline,
+	});
+}
+
+/// Export the base color images and masks of all frames in an SLP file.
+///
+/// Files for frame `i` are named `<name>_<i>`, see [`FrameMasks::save`].
+/// 32-bit frames are not exported.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file.
+/// * `palette` - Palette used by the frames.
+/// * `player` - Player number used for player colors in the base images.
+/// * `directory` - Output directory.
+/// * `name` - Base name of the output files.
+/// * `layout` - Layout of the mask images.
+///
+/// # Errors
+///
+/// Returns an error if one of the files cannot be written.
+pub fn export_masks(
+	slp: &SLPFile,
+	palette: &Palette,
+	player: u8,
+	directory: &Path,
+	name: &str,
+	layout: MaskLayout,
+) -> image::ImageResult<()> {
+	for i in 0..slp.frames.len() {
+		if let Some(masks) = extract_masks(slp, i, palette, player) {
+			masks.save(directory, &format!("{name}_{i}"), layout)?;
+		}
+	}
+
+	return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::export::mask::extract_masks;
+	use crate::palette::palette::Palette;
+	use crate::slp::frame::SLPFrame;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::SLPPixelType;
+	use crate::slp::slp::SLPFile;
+
+	/// Test the player color, shadow and outline masks of a frame.
+	#[test]
+	fn extract_frame_masks() {
+		let frame = SLPFrame::new(
+			Vec::new(),
+			Vec::new(),
+			vec![vec![
+				PalettePixel::new(SLPPixelType::Palette, 1),
+				PalettePixel::new(SLPPixelType::Player, 3),
+				PalettePixel::new(SLPPixelType::Shadow, 0),
+				PalettePixel::new(SLPPixelType::Special1, 0),
+				PalettePixel::new(SLPPixelType::Special2, 0),
+			]],
+		);
+		let slp = SLPFile::from_palette_frames(vec![frame]);
+		let mut colors = vec![[0, 0, 0, 255]; 40];
+		colors[1] = [9, 9, 9, 255];
+		colors[35] = [0, 0, 250, 255];
+		let palette = Palette::new(colors);
+
+		let masks = extract_masks(&slp, 0, &palette, 2).unwrap();
+		let values =
+			|mask: &image::GrayImage| mask.pixels().map(|pixel| pixel[0]).collect::<Vec<_>>();
+		assert_eq!(values(&masks.player), [0, 255, 0, 0, 0]);
+		assert_eq!(values(&masks.shadow), [0, 0, 255, 0, 0]);
+		assert_eq!(values(&masks.player_outline), [0, 0, 0, 255, 0]);
+		assert_eq!(values(&masks.black_outline), [0, 0, 0, 0, 255]);
+
+		// the base image uses the colors of player 2
+		assert_eq!(masks.base.get_pixel(0, 0).0, [9, 9, 9, 255]);
+		assert_eq!(masks.base.get_pixel(1, 0).0, [0, 0, 250, 255]);
+		assert_eq!(masks.base.get_pixel(3, 0).0, [0, 0, 0, 0]);
+		assert_eq!(masks.to_packed().get_pixel(1, 0).0, [255, 0, 0, 0]);
+	}
+}
@@ -0,0 +1,191 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::blendomatic::definitions::BLENDOMATIC_HEADER_SIZE;
+use crate::blendomatic::definitions::BLENDOMATIC_TILE_COUNTS;
+use crate::dds::definitions::DDS_MAGIC;
+use crate::drs::definitions::DRS_COPYRIGHT_SIZE;
+use crate::drs::definitions::DRS_HEADER_SIZE;
+use crate::drs::definitions::DRS_VERSION_SIZE;
+use crate::palette::definitions::JASC_PAL_MAGIC;
+use crate::sld::definitions::SLD_SIGNATURE;
+use crate::slp::definitions::SLP_VERSION_2_0N;
+use crate::slp::definitions::SLP_VERSION_3_0;
+use crate::slp::definitions::SLP_VERSION_4_0X;
+use crate::slp::definitions::SLP_VERSION_4_1X;
+use crate::slp::definitions::SLP_VERSION_4_2P;
+use crate::slp::definitions::SLP_VERSION_SIZE;
+use crate::slp::types::SLPVersion;
+use crate::smp::definitions::SMP_SIGNATURE;
+use crate::smx::definitions::SMX_SIGNATURE;
+
+/// Prefix of the copyright string in DRS archives.
+const DRS_COPYRIGHT_PREFIX: &[u8] = b"Copyright";
+
+/// Format of a graphics file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum GraphicFormat {
+	/// SLP sprite with its version.
+	SLP(SLPVersion),
+	/// SMP layered sprite.
+	SMP,
+	/// SMX layered sprite.
+	SMX,
+	/// SLD block compressed sprite.
+	SLD,
+	/// DRS archive.
+	DRS,
+	/// DDS texture.
+	DDS,
+	/// Palette in JASC-PAL format.
+	Palette,
+	/// Blendomatic terrain blend masks.
+	Blendomatic,
+}
+
+/// Check if the bytes are a DRS archive.
+///
+/// DRS archives have no magic bytes, so the copyright string and a version
+/// of the form `d.dd` are checked.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the file.
+///
+/// # Returns
+///
+/// true if the bytes look like a DRS archive, else false.
+fn is_drs(bytes: &[u8]) -> bool {
+	if bytes.len() < DRS_HEADER_SIZE || !bytes.starts_with(DRS_COPYRIGHT_PREFIX) {
+		return false;
+	}
+
+	let version = &bytes[DRS_COPYRIGHT_SIZE..DRS_COPYRIGHT_SIZE + DRS_VERSION_SIZE];
+	return version[1] == b'.'
+		&& [version[0], version[2], version[3]]
+			.iter()
+			.all(u8::is_ascii_digit);
+}
+
+/// Check if the bytes are a blendomatic file.
+///
+/// Blendomatic files have no magic bytes, so the tile count in the header
+/// and the file size are checked. All blend modes have the same tile size
+/// as the first one.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the file.
+///
+/// # Returns
+///
+/// true if the bytes look like a blendomatic file, else false.
+fn is_blendomatic(bytes: &[u8]) -> bool {
+	if bytes.len() < BLENDOMATIC_HEADER_SIZE + 4 {
+		return false;
+	}
+	let read_u32 = |pos: usize| u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+
+	let num_modes = read_u32(0) as usize;
+	let num_tiles = read_u32(4);
+	if !BLENDOMATIC_TILE_COUNTS.contains(&num_tiles) {
+		return false;
+	}
+
+	let tiles = num_tiles as usize;
+	let pixels = read_u32(BLENDOMATIC_HEADER_SIZE) as usize;
+	// pixel count, flags, bit masks and byte masks
+	let mode_size = pixels
+		.checked_mul(4 + tiles)
+		.and_then(|masks| masks.checked_add(4 + tiles));
+	let size = mode_size
+		.and_then(|mode_size| mode_size.checked_mul(num_modes))
+		.and_then(|modes| modes.checked_add(BLENDOMATIC_HEADER_SIZE));
+
+	return size == Some(bytes.len());
+}
+
+/// Detect the format of a graphics file from its magic bytes.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the file.
+///
+/// # Returns
+///
+/// Format of the file or `None` if the format is unknown.
+pub fn detect_format(bytes: &[u8]) -> Option<GraphicFormat> {
+	let version = bytes
+		.get(0..SLP_VERSION_SIZE)
+		.and_then(|version| SLPVersion::try_from(version).ok());
+	if let Some(version) = version {
+		match version {
+			SLP_VERSION_2_0N | SLP_VERSION_3_0 | SLP_VERSION_4_0X | SLP_VERSION_4_1X
+			| SLP_VERSION_4_2P => return Some(GraphicFormat::SLP(version)),
+			_ if &version == SMP_SIGNATURE => return Some(GraphicFormat::SMP),
+			_ if &version == SMX_SIGNATURE => return Some(GraphicFormat::SMX),
+			_ if &version == SLD_SIGNATURE => return Some(GraphicFormat::SLD),
+			_ if &version == DDS_MAGIC => return Some(GraphicFormat::DDS),
+			_ => {}
+		}
+	}
+
+	if bytes.starts_with(JASC_PAL_MAGIC.as_bytes()) {
+		return Some(GraphicFormat::Palette);
+	}
+	if is_drs(bytes) {
+		return Some(GraphicFormat::DRS);
+	}
+	if is_blendomatic(bytes) {
+		return Some(GraphicFormat::Blendomatic);
+	}
+
+	return None;
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::format::detect::detect_format;
+	use crate::format::detect::GraphicFormat;
+
+	/// Test detecting formats with and without magic bytes.
+	#[test]
+	fn detect_formats() {
+		assert_eq!(
+			detect_format(b"2.0N\x01\x00\x00\x00"),
+			Some(GraphicFormat::SLP(*b"2.0N"))
+		);
+		assert_eq!(
+			detect_format(b"4.2P\x00\x00\x00\x00"),
+			Some(GraphicFormat::SLP(*b"4.2P"))
+		);
+		assert_eq!(detect_format(b"SMP$\x00\x05"), Some(GraphicFormat::SMP));
+		assert_eq!(detect_format(b"SMPX\x02\x00"), Some(GraphicFormat::SMX));
+		assert_eq!(detect_format(b"DDS \x7c"), Some(GraphicFormat::DDS));
+		assert_eq!(
+			detect_format(b"JASC-PAL\r\n0100\r\n0\r\n"),
+			Some(GraphicFormat::Palette)
+		);
+
+		let mut drs = b"Copyright (c) 1997 Ensemble Studios.".to_vec();
+		drs.resize(40, 0);
+		drs.extend_from_slice(b"1.00tribe");
+		drs.resize(64, 0);
+		assert_eq!(detect_format(&drs), Some(GraphicFormat::DRS));
+
+		let mut blendomatic = Vec::<u8>::new();
+		blendomatic.extend_from_slice(&1u32.to_le_bytes());
+		blendomatic.extend_from_slice(&31u32.to_le_bytes());
+		blendomatic.extend_from_slice(&7u32.to_le_bytes());
+		blendomatic.resize(8 + 4 + 31 + 7 * 4 + 31 * 7, 0);
+		assert_eq!(
+			detect_format(&blendomatic),
+			Some(GraphicFormat::Blendomatic)
+		);
+		blendomatic.push(0);
+		assert_eq!(detect_format(&blendomatic), None);
+
+		assert_eq!(detect_format(b"\x89PNG"), None);
+		assert_eq!(detect_format(b""), None);
+	}
+}
@@ -0,0 +1,124 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::io;
+
+use crate::blendomatic::blendomatic::parse_blendomatic;
+use crate::blendomatic::blendomatic::BlendomaticFile;
+use crate::dds::dds::parse_dds;
+use crate::dds::dds::DDSFile;
+use crate::drs::drs::parse_drs;
+use crate::drs::drs::DRSFile;
+use crate::format::detect::detect_format;
+use crate::format::detect::GraphicFormat;
+use crate::palette::palette::parse_palette;
+use crate::palette::palette::Palette;
+use crate::sld::sld::parse_sld;
+use crate::sld::sld::SLDFile;
+use crate::slp::slp::check_slp_header;
+use crate::slp::slp::parse_slp;
+use crate::slp::slp::SLPFile;
+use crate::smp::smp::parse_smp;
+use crate::smp::smp::SMPFile;
+use crate::smx::smx::parse_smx;
+use crate::smx::smx::SMXFile;
+
+/// Parsed graphics file.
+#[derive(Debug)]
+#[allow(clippy::upper_case_acronyms, clippy::large_enum_variant)]
+pub enum Graphic {
+	/// SLP sprite.
+	SLP(SLPFile),
+	/// SMP layered sprite.
+	SMP(SMPFile),
+	/// SMX layered sprite.
+	SMX(SMXFile),
+	/// SLD block compressed sprite.
+	SLD(SLDFile),
+	/// DRS archive.
+	DRS(DRSFile),
+	/// DDS texture.
+	DDS(DDSFile),
+	/// Palette in JASC-PAL format.
+	Palette(Palette),
+	/// Blendomatic terrain blend masks.
+	Blendomatic(BlendomaticFile),
+}
+
+impl Graphic {
+	/// Get the format of the parsed file.
+	///
+	/// # Returns
+	///
+	/// Format of the file. SLP files report the version they were parsed
+	/// as, so compressed 4.2P files report 4.1X.
+	pub const fn get_format(&self) -> GraphicFormat {
+		return match self {
+			Self::SLP(slp) => GraphicFormat::SLP(slp.header.data.version),
+			Self::SMP(_) => GraphicFormat::SMP,
+			Self::SMX(_) => GraphicFormat::SMX,
+			Self::SLD(_) => GraphicFormat::SLD,
+			Self::DRS(_) => GraphicFormat::DRS,
+			Self::DDS(_) => GraphicFormat::DDS,
+			Self::Palette(_) => GraphicFormat::Palette,
+			Self::Blendomatic(_) => GraphicFormat::Blendomatic,
+		};
+	}
+}
+
+/// Parse a graphics file of any supported format.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the file.
+///
+/// # Returns
+///
+/// The parsed file.
+///
+/// # Errors
+///
+/// Returns an error if the format is unknown or the headers of the file are
+/// invalid.
+///
+/// # Panics
+///
+/// Panics if the frame data of an SLP, SMP, SMX or SLD file is truncated.
+pub fn open_graphic(bytes: &[u8]) -> io::Result<Graphic> {
+	let format = detect_format(bytes)
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Unknown file format"))?;
+
+	let graphic = match format {
+		GraphicFormat::SLP(_) => {
+			let _version = check_slp_header(bytes)?;
+			Graphic::SLP(parse_slp(bytes))
+		}
+		GraphicFormat::SMP => Graphic::SMP(parse_smp(bytes)?),
+		GraphicFormat::SMX => Graphic::SMX(parse_smx(bytes)?),
+		GraphicFormat::SLD => Graphic::SLD(parse_sld(bytes)?),
+		GraphicFormat::DRS => Graphic::DRS(parse_drs(bytes)?),
+		GraphicFormat::DDS => Graphic::DDS(parse_dds(bytes)?),
+		GraphicFormat::Palette => Graphic::Palette(parse_palette(bytes)?),
+		GraphicFormat::Blendomatic => Graphic::Blendomatic(parse_blendomatic(bytes)?),
+	};
+
+	return Ok(graphic);
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::format::graphic::open_graphic;
+
+	/// Test that unknown and malformed files return an error.
+	#[test]
+	fn open_invalid_graphics() {
+		for bytes in [
+			&b"unknown format"[..],
+			b"2.0N\x01\x00",
+			b"4.2P\x00",
+			b"SMP$\x00\x00",
+			b"JASC-PAL\r\n0100\r\n",
+		] {
+			assert!(open_graphic(bytes).is_err(), "{bytes:?}");
+		}
+	}
+}
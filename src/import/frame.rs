@@ -0,0 +1,501 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::path::Path;
+
+use crate::palette::palette::Palette;
+use crate::palette::quantize::QuantizeOptions;
+use crate::palette::quantize::QuantizeReport;
+use crate::palette::quantize::Quantizer;
+use crate::slp::definitions::SLP_PLAYER_COLOR_BLOCK_SIZE;
+use crate::slp::frame::SLPFrame;
+use crate::slp::frame_info::SLPFrameInfo;
+use crate::slp::frame_info::SLPFrameType;
+use crate::slp::pixel::PalettePixel;
+use crate::slp::pixel::SLPPixelType;
+use crate::slp::types::SLPVersion;
+
+/// Key identifying pixels with a reserved meaning in an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelKey {
+	/// Pixels with exactly this RGBA color.
+	Color([u8; 4]),
+	/// Pixels with this alpha value.
+	Alpha(u8),
+}
+
+impl PixelKey {
+	/// Check if a color matches the key.
+	///
+	/// # Arguments
+	///
+	/// * `color` - RGBA color.
+	///
+	/// # Returns
+	///
+	/// true if the color matches, else false.
+	pub fn matches(
+		self,
+		color: [u8; 4],
+	) -> bool {
+		return match self {
+			Self::Color(key) => key == color,
+			Self::Alpha(alpha) => alpha == color[3],
+		};
+	}
+}
+
+/// Options for importing images as frames.
+///
+/// The default keys are the colors [`PalettePixel`] exports special pixels
+/// as, so exported frames import without changes.
+#[derive(Debug, Clone, Copy)]
+pub struct ImportOptions {
+	/// Key of player color pixels. The green channel stores the index in the
+	/// player color block.
+	pub player_key: Option<PixelKey>,
+	/// Key of shadow pixels.
+	pub shadow_key: Option<PixelKey>,
+	/// Key of player color outline pixels (`Special1`).
+	pub player_outline_key: Option<PixelKey>,
+	/// Key of black outline pixels (`Special2`).
+	pub black_outline_key: Option<PixelKey>,
+	/// Pixels matching no key with an alpha value below the threshold are
+	/// transparent.
+	pub alpha_threshold: u8,
+	/// Player number whose colors mark player color pixels in the image if
+	/// a player mask is used.
+	pub player: u8,
+}
+
+impl Default for ImportOptions {
+	fn default() -> Self {
+		Self {
+			player_key: Some(PixelKey::Alpha(254)),
+			shadow_key: Some(PixelKey::Color([0, 0, 0, 100])),
+			player_outline_key: Some(PixelKey::Color([0, 0, 0, 252])),
+			black_outline_key: Some(PixelKey::Color([0, 0, 0, 250])),
+			alpha_threshold: 128,
+			player: 1,
+		}
+	}
+}
+
+/// Mask images marking special pixels in an imported image.
+///
+/// Pixels with a non-zero mask value are player color or shadow pixels.
+/// Masks override the keys of [`ImportOptions`].
+#[derive(Debug, Clone, Default)]
+pub struct ImportMasks {
+	/// Player color pixels. Their index is the closest color in the player
+	/// color block of [`ImportOptions::player`].
+	pub player: Option<image::GrayImage>,
+	/// Shadow pixels.
+	pub shadow: Option<image::GrayImage>,
+}
+
+/// Frame imported from an image.
+#[derive(Debug, Clone)]
+pub struct ImportedFrame {
+	/// Frame with palette indices.
+	pub frame: SLPFrame<PalettePixel>,
+	/// X coordinate of the anchor point.
+	pub anchor_x: i32,
+	/// Y coordinate of the anchor point.
+	pub anchor_y: i32,
+}
+
+impl ImportedFrame {
+	/// Create the frame info of the frame.
+	///
+	/// The offsets are left at 0 because writers compute them.
+	///
+	/// # Arguments
+	///
+	/// * `frame_type` - Frame type.
+	/// * `slp_version` - SLP version.
+	///
+	/// # Returns
+	///
+	/// Frame info with the size and anchor of the frame.
+	///
+	/// # Panics
+	///
+	/// Panics if the frame is too large.
+	pub fn get_frame_info(
+		&self,
+		frame_type: SLPFrameType,
+		slp_version: SLPVersion,
+	) -> SLPFrameInfo {
+		return SLPFrameInfo::new(
+			0,
+			0,
+			0,
+			0,
+			i32::try_from(self.frame.get_width()).unwrap(),
+			i32::try_from(self.frame.get_height()).unwrap(),
+			self.anchor_x,
+			self.anchor_y,
+			frame_type,
+			slp_version,
+		);
+	}
+}
+
+/// Check if a mask marks a pixel.
+///
+/// # Arguments
+///
+/// * `mask` - Mask image.
+/// * `x` - X coordinate of the pixel.
+/// * `y` - Y coordinate of the pixel.
+///
+/// # Returns
+///
+/// true if the mask exists and its value is not 0, else false.
+fn is_masked(
+	mask: Option<&image::GrayImage>,
+	x: u32,
+	y: u32,
+) -> bool {
+	return mask.is_some_and(|mask| mask.get_pixel(x, y)[0] != 0);
+}
+
+/// Get the special pixel an image color is converted to.
+///
+/// # Arguments
+///
+/// * `image` - Imported image.
+/// * `x` - X coordinate of the pixel.
+/// * `y` - Y coordinate of the pixel.
+/// * `palette` - Palette used by the frame.
+/// * `masks` - Masks of special pixels.
+/// * `options` - Import options.
+///
+/// # Returns
+///
+/// Special pixel or `None` if the pixel is a palette color.
+fn get_special_pixel(
+	image: &image::RgbaImage,
+	x: u32,
+	y: u32,
+	palette: &Palette,
+	masks: &ImportMasks,
+	options: &ImportOptions,
+) -> Option<PalettePixel> {
+	let color = image.get_pixel(x, y).0;
+	let matches = |key: Option<PixelKey>| key.is_some_and(|key| key.matches(color));
+
+	if is_masked(masks.player.as_ref(), x, y) {
+		let block = options.player as usize * SLP_PLAYER_COLOR_BLOCK_SIZE as usize;
+		let block_range = block..block + SLP_PLAYER_COLOR_BLOCK_SIZE as usize;
+		let index = palette.find_closest_color_in([color[0], color[1], color[2]], block_range);
+		// the color block is checked to be in the palette
+		let offset = index - u8::try_from(block).unwrap();
+		return Some(PalettePixel::new(SLPPixelType::Player, offset));
+	}
+	if is_masked(masks.shadow.as_ref(), x, y) {
+		return Some(PalettePixel::new(SLPPixelType::Shadow, 0));
+	}
+	if matches(options.player_key) {
+		return Some(PalettePixel::new(SLPPixelType::Player, color[1]));
+	}
+	if matches(options.shadow_key) {
+		return Some(PalettePixel::new(SLPPixelType::Shadow, 0));
+	}
+	if matches(options.player_outline_key) {
+		return Some(PalettePixel::new(SLPPixelType::Special1, 0));
+	}
+	if matches(options.black_outline_key) {
+		return Some(PalettePixel::new(SLPPixelType::Special2, 0));
+	}
+	if color[3] < options.alpha_threshold {
+		return Some(PalettePixel::new(SLPPixelType::Transparent, 0));
+	}
+
+	return None;
+}
+
+/// Import an image as a frame with palette indices.
+///
+/// Colors are mapped to the perceptually closest palette color without
+/// dithering. Pixels matching one of the keys or marked by a mask become
+/// special pixels.
+///
+/// # Arguments
+///
+/// * `image` - Image to import.
+/// * `palette` - Palette used by the frame.
+/// * `masks` - Masks of special pixels.
+/// * `options` - Import options.
+///
+/// # Returns
+///
+/// Frame with the pixels of the image.
+///
+/// # Panics
+///
+/// Panics if a mask does not have the size of the image or a player mask is
+/// given and the color block of [`ImportOptions::player`] is not in the
+/// palette.
+pub fn import_frame(
+	image: &image::RgbaImage,
+	palette: &Palette,
+	masks: &ImportMasks,
+	options: &ImportOptions,
+) -> SLPFrame<PalettePixel> {
+	let mut quantizer = Quantizer::new(palette, &QuantizeOptions::default());
+
+	return import_frame_quantized(image, palette, &mut quantizer, masks, options).0;
+}
+
+/// Import an image as a frame with a quantizer for the palette colors.
+///
+/// Pixels matching one of the keys or marked by a mask become special
+/// pixels. All other pixels are quantized, so reserved palette ranges and
+/// dithering are configured by the quantizer.
+///
+/// # Arguments
+///
+/// * `image` - Image to import.
+/// * `palette` - Palette used by the frame.
+/// * `quantizer` - Quantizer for the palette.
+/// * `masks` - Masks of special pixels.
+/// * `options` - Import options.
+///
+/// # Returns
+///
+/// Frame with the pixels of the image and the quantization error of its
+/// palette pixels.
+///
+/// # Panics
+///
+/// Panics if a mask does not have the size of the image or a player mask is
+/// given and the color block of [`ImportOptions::player`] is not in the
+/// palette.
+pub fn import_frame_quantized(
+	image: &image::RgbaImage,
+	palette: &Palette,
+	quantizer: &mut Quantizer,
+	masks: &ImportMasks,
+	options: &ImportOptions,
+) -> (SLPFrame<PalettePixel>, QuantizeReport) {
+	for mask in [&masks.player, &masks.shadow].into_iter().flatten() {
+		assert!(
+			mask.dimensions() == image.dimensions(),
+			"Mask size {:?} does not match image size {:?}",
+			mask.dimensions(),
+			image.dimensions()
+		);
+	}
+	if masks.player.is_some() {
+		let block_end =
+			(usize::from(options.player) + 1) * usize::from(SLP_PLAYER_COLOR_BLOCK_SIZE);
+		assert!(
+			block_end <= palette.get_num_colors(),
+			"Color block of player {} is not in the palette with {} colors",
+			options.player,
+			palette.get_num_colors()
+		);
+	}
+
+	let special = |x: u32, y: u32| get_special_pixel(image, x, y, palette, masks, options);
+	let (indices, report) = quantizer.quantize(image, |x, y, _| special(x, y).is_none());
+
+	let rows = indices
+		.into_iter()
+		.zip(0..)
+		.map(|(row, y)| {
+			row.into_iter()
+				.zip(0..)
+				.map(|(index, x)| {
+					index.map_or_else(
+						|| special(x, y).unwrap(),
+						|index| PalettePixel::new(SLPPixelType::Palette, index),
+					)
+				})
+				.collect()
+		})
+		.collect();
+
+	return (SLPFrame::new(Vec::new(), Vec::new(), rows), report);
+}
+
+/// Parse the content of an anchor file.
+///
+/// The file contains the X and Y coordinates of the anchor point, separated
+/// by a comma or whitespace.
+///
+/// # Arguments
+///
+/// * `content` - Content of the anchor file.
+///
+/// # Returns
+///
+/// Anchor point or `None` if the content is invalid.
+pub fn parse_anchor(content: &str) -> Option<(i32, i32)> {
+	let mut values = content
+		.split(|c: char| c == ',' || c.is_whitespace())
+		.filter(|value| !value.is_empty())
+		.map(str::parse::<i32>);
+
+	let anchor_x = values.next()?.ok()?;
+	let anchor_y = values.next()?.ok()?;
+	if values.next().is_some() {
+		return None;
+	}
+
+	return Some((anchor_x, anchor_y));
+}
+
+/// Load an image file as a frame.
+///
+/// Files next to `<name>.png` are used if they exist: the masks
+/// `<name>_player.png` and `<name>_shadow.png` and the anchor file
+/// `<name>_anchor.txt`. Without an anchor file, the anchor is the center of
+/// the image.
+///
+/// # Arguments
+///
+/// * `path` - Path to the image file.
+/// * `palette` - Palette used by the frame.
+/// * `options` - Import options.
+///
+/// # Returns
+///
+/// Imported frame.
+///
+/// # Errors
+///
+/// Returns an error if one of the files cannot be read or the anchor file
+/// is invalid.
+///
+/// # Panics
+///
+/// Panics if a mask does not have the size of the image or a player mask
+/// exists and the color block of [`ImportOptions::player`] is not in the
+/// palette.
+pub fn load_frame(
+	path: &Path,
+	palette: &Palette,
+	options: &ImportOptions,
+) -> image::ImageResult<ImportedFrame> {
+	let image = image::open(path)?.into_rgba8();
+	let stem = path
+		.file_stem()
+		.map(|stem| stem.to_string_lossy().into_owned())
+		.unwrap_or_default();
+	let sidecar = |suffix: &str| path.with_file_name(format!("{stem}{suffix}"));
+
+	let load_mask = |suffix: &str| -> image::ImageResult<Option<image::GrayImage>> {
+		let mask_path = sidecar(suffix);
+		if !mask_path.exists() {
+			return Ok(None);
+		}
+		return Ok(Some(image::open(mask_path)?.into_luma8()));
+	};
+	let masks = ImportMasks {
+		player: load_mask("_player.png")?,
+		shadow: load_mask("_shadow.png")?,
+	};
+
+	let anchor_path = sidecar("_anchor.txt");
+	let (anchor_x, anchor_y) = if anchor_path.exists() {
+		let content = std::fs::read_to_string(&anchor_path)?;
+		parse_anchor(&content).ok_or_else(|| {
+			std::io::Error::new(
+				std::io::ErrorKind::InvalidData,
+				format!("Invalid anchor file {}", anchor_path.display()),
+			)
+		})?
+	}
+	else {
+		(
+			i32::try_from(image.width() / 2).unwrap_or(i32::MAX),
+			i32::try_from(image.height() / 2).unwrap_or(i32::MAX),
+		)
+	};
+
+	return Ok(ImportedFrame {
+		frame: import_frame(&image, palette, &masks, options),
+		anchor_x,
+		anchor_y,
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::import::frame::import_frame;
+	use crate::import::frame::parse_anchor;
+	use crate::import::frame::ImportMasks;
+	use crate::import::frame::ImportOptions;
+	use crate::palette::palette::Palette;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::SLPPixelType;
+
+	/// Test importing key colors and mask pixels.
+	#[test]
+	fn import_key_colors() {
+		let mut colors = vec![[0, 0, 0, 255], [250, 0, 0, 255], [0, 0, 250, 255]];
+		colors.resize(16, [10, 10, 10, 255]);
+		colors.resize(32, [0, 200, 0, 255]);
+		colors[20] = [0, 0, 255, 255];
+		let palette = Palette::new(colors);
+
+		let image = image::RgbaImage::from_raw(
+			7,
+			1,
+			[
+				[240, 10, 10, 255], // close to red
+				[0, 5, 0, 254],     // player key
+				[0, 0, 0, 100],     // shadow key
+				[0, 0, 0, 252],     // outline 1 key
+				[0, 0, 0, 250],     // outline 2 key
+				[0, 0, 250, 20],    // below the alpha threshold
+				[0, 0, 240, 255],   // masked player pixel
+			]
+			.concat(),
+		)
+		.unwrap();
+		let mut player_mask = image::GrayImage::new(7, 1);
+		player_mask.put_pixel(6, 0, image::Luma([255]));
+		let masks = ImportMasks {
+			player: Some(player_mask),
+			shadow: None,
+		};
+
+		let frame = import_frame(&image, &palette, &masks, &ImportOptions::default());
+		assert_eq!(
+			frame.get_pixels()[0],
+			[
+				PalettePixel::new(SLPPixelType::Palette, 1),
+				PalettePixel::new(SLPPixelType::Player, 5),
+				PalettePixel::new(SLPPixelType::Shadow, 0),
+				PalettePixel::new(SLPPixelType::Special1, 0),
+				PalettePixel::new(SLPPixelType::Special2, 0),
+				PalettePixel::new(SLPPixelType::Transparent, 0),
+				PalettePixel::new(SLPPixelType::Player, 4),
+			]
+		);
+
+		assert_eq!(parse_anchor("12, -3\n"), Some((12, -3)));
+		assert_eq!(parse_anchor("12"), None);
+	}
+
+	/// Test that player masks require the player's color block.
+	#[test]
+	#[should_panic(expected = "Color block of player 16")]
+	fn import_player_outside_palette() {
+		let palette = Palette::new(vec![[0, 0, 0, 255]; 256]);
+		let image = image::RgbaImage::new(1, 1);
+		let masks = ImportMasks {
+			player: Some(image::GrayImage::new(1, 1)),
+			shadow: None,
+		};
+		let options = ImportOptions {
+			player: 16,
+			..ImportOptions::default()
+		};
+
+		let _ = import_frame(&image, &palette, &masks, &options);
+	}
+}
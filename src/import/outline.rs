@@ -0,0 +1,265 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::slp::frame::SLPFrame;
+use crate::slp::pixel::PalettePixel;
+use crate::slp::pixel::RGBAPixel;
+use crate::slp::pixel::SLPPixelType;
+use crate::slp::slp::SLPFile;
+
+/// Options for generating outline pixels.
+///
+/// The defaults follow the game's unit frames: a player color outline one
+/// pixel wide around the silhouette, where only pixels sharing an edge
+/// with the silhouette are outlined.
+#[derive(Debug, Clone, Copy)]
+pub struct OutlineOptions {
+	/// Pixel type of the outline, `Special1` (player color) or `Special2`
+	/// (black).
+	pub pixel_type: SLPPixelType,
+	/// Width of the outline in pixels.
+	pub thickness: u32,
+	/// Also outline pixels that only touch the silhouette diagonally.
+	pub diagonal: bool,
+}
+
+impl Default for OutlineOptions {
+	fn default() -> Self {
+		Self {
+			pixel_type: SLPPixelType::Special1,
+			thickness: 1,
+			diagonal: false,
+		}
+	}
+}
+
+/// Frame with generated outline pixels.
+#[derive(Debug, Clone)]
+pub struct OutlinedFrame {
+	/// Frame with outline pixels.
+	pub frame: SLPFrame<PalettePixel>,
+	/// Number of columns added to the left of the frame.
+	pub offset_x: u32,
+	/// Number of rows added to the top of the frame.
+	pub offset_y: u32,
+}
+
+/// Pixel that can belong to the opaque silhouette of a frame.
+pub trait SilhouettePixel {
+	/// Check if the pixel belongs to the opaque silhouette of a frame.
+	///
+	/// # Returns
+	///
+	/// true if the pixel is a color or player color pixel, else false.
+	fn is_silhouette(&self) -> bool;
+}
+
+impl SilhouettePixel for PalettePixel {
+	fn is_silhouette(&self) -> bool {
+		return matches!(
+			self.pixel_type,
+			SLPPixelType::Palette | SLPPixelType::Player | SLPPixelType::PlayerV4
+		);
+	}
+}
+
+impl SilhouettePixel for RGBAPixel {
+	fn is_silhouette(&self) -> bool {
+		return matches!(
+			self.pixel_type,
+			SLPPixelType::Palette | SLPPixelType::Player | SLPPixelType::PlayerV4
+		);
+	}
+}
+
+/// Generate outline pixels around the silhouette of a frame.
+///
+/// Transparent pixels within the outline thickness of the silhouette
+/// become outline pixels. Shadow and existing outline pixels are kept. The
+/// frame grows where the outline does not fit, so the anchor of the frame
+/// must be moved by the returned offsets.
+///
+/// # Arguments
+///
+/// * `frame` - Frame with palette indices.
+/// * `options` - Outline options.
+///
+/// # Returns
+///
+/// Frame with outline pixels.
+///
+/// # Panics
+///
+/// Panics if the pixel type is not `Special1` or `Special2`.
+pub fn outline_frame(
+	frame: &SLPFrame<PalettePixel>,
+	options: &OutlineOptions,
+) -> OutlinedFrame {
+	assert!(
+		matches!(
+			options.pixel_type,
+			SLPPixelType::Special1 | SLPPixelType::Special2
+		),
+		"Outline pixels must be Special1 or Special2, got {:?}",
+		options.pixel_type
+	);
+
+	let pad = options.thickness as usize;
+	let width = frame.get_width() + 2 * pad;
+	let height = frame.get_height() + 2 * pad;
+
+	// pixels of the padded frame covered by the silhouette or the outline
+	let mut reached = vec![vec![false; width]; height];
+	for (y, row) in frame.get_pixels().iter().enumerate() {
+		for (x, pixel) in row.iter().enumerate() {
+			reached[y + pad][x + pad] = pixel.is_silhouette();
+		}
+	}
+	let mut outline = vec![vec![false; width]; height];
+	for _ in 0..options.thickness {
+		let previous = reached.clone();
+		for y in 0..height {
+			for x in 0..width {
+				if previous[y][x] {
+					continue;
+				}
+				let is_near = |dx: isize, dy: isize| {
+					let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy))
+					else {
+						return false;
+					};
+					return previous
+						.get(ny)
+						.and_then(|row| row.get(nx))
+						.copied()
+						.unwrap_or(false);
+				};
+				let mut near = is_near(-1, 0) || is_near(1, 0) || is_near(0, -1) || is_near(0, 1);
+				if options.diagonal {
+					near |= is_near(-1, -1) || is_near(1, -1) || is_near(-1, 1) || is_near(1, 1);
+				}
+				if near {
+					reached[y][x] = true;
+					outline[y][x] = true;
+				}
+			}
+		}
+	}
+
+	// crop the padding that contains no outline pixels
+	let has_outline_row = |y: usize| outline[y].iter().any(|&value| value);
+	let has_outline_col = |x: usize| outline.iter().any(|row| row[x]);
+	let top = (0..pad).find(|&y| has_outline_row(y)).unwrap_or(pad);
+	let bottom = (0..pad)
+		.find(|&y| has_outline_row(height - 1 - y))
+		.unwrap_or(pad);
+	let left = (0..pad).find(|&x| has_outline_col(x)).unwrap_or(pad);
+	let right = (0..pad)
+		.find(|&x| has_outline_col(width - 1 - x))
+		.unwrap_or(pad);
+
+	let transparent = PalettePixel::new(SLPPixelType::Transparent, 0);
+	let outline_pixel = PalettePixel::new(options.pixel_type, 0);
+	let rows = (top..height - bottom)
+		.map(|y| {
+			(left..width - right)
+				.map(|x| {
+					let pixel = y
+						.checked_sub(pad)
+						.zip(x.checked_sub(pad))
+						.and_then(|(fy, fx)| frame.get_pixels().get(fy)?.get(fx))
+						.copied()
+						.unwrap_or(transparent);
+					if outline[y][x] && pixel.pixel_type == SLPPixelType::Transparent {
+						return outline_pixel;
+					}
+					return pixel;
+				})
+				.collect()
+		})
+		.collect();
+
+	return OutlinedFrame {
+		frame: SLPFrame::new(Vec::new(), Vec::new(), rows),
+		// the padding is at most the thickness
+		offset_x: u32::try_from(pad - left).unwrap(),
+		offset_y: u32::try_from(pad - top).unwrap(),
+	};
+}
+
+/// Generate outline pixels for all palette frames of an SLP file.
+///
+/// Frame sizes and anchors are updated for frames that grow. 32-bit frames
+/// and shadow frames are not changed.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file.
+/// * `options` - Outline options.
+///
+/// # Panics
+///
+/// Panics if the pixel type is not `Special1` or `Special2` or a frame
+/// becomes too large.
+pub fn outline_slp(
+	slp: &mut SLPFile,
+	options: &OutlineOptions,
+) {
+	for (frame, info) in slp.frames.iter_mut().zip(&mut slp.frame_infos) {
+		let outlined = outline_frame(frame, options);
+		*frame = outlined.frame;
+
+		info.data.width = i32::try_from(frame.get_width()).unwrap();
+		info.data.height = i32::try_from(frame.get_height()).unwrap();
+		info.data.anchor_x += i32::try_from(outlined.offset_x).unwrap();
+		info.data.anchor_y += i32::try_from(outlined.offset_y).unwrap();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::import::outline::outline_frame;
+	use crate::import::outline::OutlineOptions;
+	use crate::slp::frame::SLPFrame;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::SLPPixelType;
+
+	/// Test outlining a single pixel with and without diagonal neighbours.
+	#[test]
+	fn outline_pixel() {
+		let frame = SLPFrame::new(
+			Vec::new(),
+			Vec::new(),
+			vec![vec![PalettePixel::new(SLPPixelType::Palette, 7)]],
+		);
+		let as_types = |frame: &SLPFrame<PalettePixel>| {
+			frame
+				.get_pixels()
+				.iter()
+				.map(|row| row.iter().map(|pixel| pixel.pixel_type).collect::<Vec<_>>())
+				.collect::<Vec<_>>()
+		};
+		let t = SLPPixelType::Transparent;
+		let o = SLPPixelType::Special1;
+		let p = SLPPixelType::Palette;
+
+		let outlined = outline_frame(&frame, &OutlineOptions::default());
+		assert_eq!((outlined.offset_x, outlined.offset_y), (1, 1));
+		assert_eq!(
+			as_types(&outlined.frame),
+			vec![vec![t, o, t], vec![o, p, o], vec![t, o, t]]
+		);
+
+		let outlined = outline_frame(
+			&frame,
+			&OutlineOptions {
+				diagonal: true,
+				..OutlineOptions::default()
+			},
+		);
+		assert_eq!(
+			as_types(&outlined.frame),
+			vec![vec![o, o, o], vec![o, p, o], vec![o, o, o]]
+		);
+		assert_eq!(outlined.frame.get_pixels()[1][1].index, 7);
+	}
+}
@@ -0,0 +1,438 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::import::outline::SilhouettePixel;
+use crate::slp::definitions::SLP_FRAME_PROPERTY_32BIT;
+use crate::slp::frame::SLPFrame;
+use crate::slp::frame_info::SLPFrameInfo;
+use crate::slp::frame_info::SLPFrameType;
+use crate::slp::pixel::PalettePixel;
+use crate::slp::pixel::SLPPixelType;
+use crate::slp::slp::SLPFile;
+
+/// Options for synthesizing shadows.
+///
+/// The silhouette is projected onto the ground at the anchor row. A pixel
+/// at height `h` above the anchor is moved `h * skew` pixels to the right
+/// and drawn at height `h * squash`. The defaults approximate the game's
+/// sun, which casts shadows to the right.
+#[derive(Debug, Clone, Copy)]
+pub struct ShadowOptions {
+	/// Horizontal shift per pixel of height. Negative values cast shadows
+	/// to the left.
+	pub skew: f32,
+	/// Vertical scale of the shadow. Must be greater than 0.
+	pub squash: f32,
+	/// Horizontal offset of the shadow in pixels.
+	pub offset_x: i32,
+	/// Vertical offset of the shadow in pixels.
+	pub offset_y: i32,
+	/// Intensity stored in the pixels of SLP 4.x shadow frames.
+	pub intensity: u8,
+}
+
+impl Default for ShadowOptions {
+	fn default() -> Self {
+		Self {
+			skew: 1.0,
+			squash: 0.5,
+			offset_x: 0,
+			offset_y: 0,
+			intensity: 128,
+		}
+	}
+}
+
+/// Frame with a synthesized shadow.
+#[derive(Debug, Clone)]
+pub struct ShadowFrame {
+	/// Frame with shadow pixels.
+	pub frame: SLPFrame<PalettePixel>,
+	/// X coordinate of the anchor point.
+	pub anchor_x: i32,
+	/// Y coordinate of the anchor point.
+	pub anchor_y: i32,
+}
+
+/// Shadow cast by a silhouette.
+struct ShadowMask {
+	/// Rows of shadow pixels.
+	mask: Vec<Vec<bool>>,
+	/// X coordinate of the left column in frame coordinates.
+	left: i32,
+	/// Y coordinate of the top row in frame coordinates.
+	top: i32,
+}
+
+impl ShadowMask {
+	/// Check if a position in frame coordinates is in the shadow.
+	///
+	/// # Arguments
+	///
+	/// * `x` - X coordinate.
+	/// * `y` - Y coordinate.
+	///
+	/// # Returns
+	///
+	/// true if the position is in the shadow, else false.
+	fn contains(
+		&self,
+		x: i32,
+		y: i32,
+	) -> bool {
+		let (Ok(x), Ok(y)) = (
+			usize::try_from(x - self.left),
+			usize::try_from(y - self.top),
+		)
+		else {
+			return false;
+		};
+
+		return self
+			.mask
+			.get(y)
+			.and_then(|row| row.get(x))
+			.copied()
+			.unwrap_or(false);
+	}
+
+	/// Get the width of the shadow.
+	///
+	/// # Returns
+	///
+	/// Number of columns.
+	fn get_width(&self) -> i32 {
+		return i32::try_from(self.mask.first().map_or(0, Vec::len)).unwrap();
+	}
+
+	/// Get the height of the shadow.
+	///
+	/// # Returns
+	///
+	/// Number of rows.
+	fn get_height(&self) -> i32 {
+		return i32::try_from(self.mask.len()).unwrap();
+	}
+}
+
+/// Project the silhouette of a frame onto the ground.
+///
+/// # Arguments
+///
+/// * `frame` - Frame casting the shadow.
+/// * `anchor_y` - Y coordinate of the ground row.
+/// * `options` - Shadow options.
+///
+/// # Returns
+///
+/// Shadow of the silhouette.
+// coordinates are far below the precision limits of f32
+#[allow(
+	clippy::cast_possible_truncation,
+	clippy::cast_possible_wrap,
+	clippy::cast_precision_loss,
+	clippy::cast_sign_loss,
+	clippy::suboptimal_flops
+)]
+fn cast_shadow<T: SilhouettePixel>(
+	frame: &SLPFrame<T>,
+	anchor_y: i32,
+	options: &ShadowOptions,
+) -> ShadowMask {
+	assert!(
+		options.squash > 0.0,
+		"Shadow squash must be greater than 0, got {}",
+		options.squash
+	);
+
+	let pixels = frame.get_pixels();
+	let is_silhouette = |x: i32, y: i32| {
+		let (Ok(x), Ok(y)) = (usize::try_from(x), usize::try_from(y))
+		else {
+			return false;
+		};
+		return pixels
+			.get(y)
+			.and_then(|row| row.get(x))
+			.is_some_and(SilhouettePixel::is_silhouette);
+	};
+
+	let positions = pixels.iter().enumerate().flat_map(|(y, row)| {
+		row.iter()
+			.enumerate()
+			.filter(|(_, pixel)| pixel.is_silhouette())
+			.map(move |(x, _)| (x as i32, y as i32))
+	});
+	let Some((min_x, min_y, max_x, max_y)) = positions.fold(None, |bounds, (x, y)| {
+		let (min_x, min_y, max_x, max_y) = bounds.unwrap_or((x, y, x, y));
+		return Some((min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y)));
+	})
+	else {
+		return ShadowMask {
+			mask: Vec::new(),
+			left: 0,
+			top: 0,
+		};
+	};
+
+	let anchor = anchor_y as f32;
+	let project = |x: f32, y: f32| {
+		let height = anchor - y;
+		return (
+			x + height * options.skew + options.offset_x as f32,
+			anchor - height * options.squash + options.offset_y as f32,
+		);
+	};
+	let corners = [
+		project(min_x as f32, min_y as f32),
+		project((max_x + 1) as f32, min_y as f32),
+		project(min_x as f32, (max_y + 1) as f32),
+		project((max_x + 1) as f32, (max_y + 1) as f32),
+	];
+	let left = corners.iter().map(|c| c.0).fold(f32::MAX, f32::min).floor() as i32;
+	let right = corners.iter().map(|c| c.0).fold(f32::MIN, f32::max).ceil() as i32;
+	let top = corners.iter().map(|c| c.1).fold(f32::MAX, f32::min).floor() as i32;
+	let bottom = corners.iter().map(|c| c.1).fold(f32::MIN, f32::max).ceil() as i32;
+
+	// sample the silhouette at the inverse projection of every pixel center
+	let mask = (top..bottom)
+		.map(|y| {
+			let height = (anchor + options.offset_y as f32 - (y as f32 + 0.5)) / options.squash;
+			let source_y = (anchor - height).floor() as i32;
+			(left..right)
+				.map(|x| {
+					let source_x =
+						(x as f32 + 0.5 - options.offset_x as f32 - height * options.skew).floor();
+					is_silhouette(source_x as i32, source_y)
+				})
+				.collect()
+		})
+		.collect();
+
+	return ShadowMask { mask, left, top };
+}
+
+/// Synthesize a separate shadow frame for a frame.
+///
+/// The shadow frame stores the intensity from the options in `ShadowV4`
+/// pixels, as SLP 4.x shadow frames do. It only covers the shadow and has
+/// its own anchor.
+///
+/// # Arguments
+///
+/// * `frame` - Frame casting the shadow.
+/// * `anchor_x` - X coordinate of the anchor point of the frame.
+/// * `anchor_y` - Y coordinate of the anchor point of the frame.
+/// * `options` - Shadow options.
+///
+/// # Returns
+///
+/// Shadow frame. Frames without opaque pixels cast an empty shadow.
+///
+/// # Panics
+///
+/// Panics if the squash is not greater than 0.
+pub fn generate_shadow<T: SilhouettePixel>(
+	frame: &SLPFrame<T>,
+	anchor_x: i32,
+	anchor_y: i32,
+	options: &ShadowOptions,
+) -> ShadowFrame {
+	let shadow = cast_shadow(frame, anchor_y, options);
+	let pixel = |in_shadow: bool| {
+		if in_shadow {
+			return PalettePixel::new(SLPPixelType::ShadowV4, options.intensity);
+		}
+		return PalettePixel::new(SLPPixelType::Transparent, 0);
+	};
+	let rows = shadow
+		.mask
+		.iter()
+		.map(|row| row.iter().map(|&in_shadow| pixel(in_shadow)).collect())
+		.collect();
+
+	return ShadowFrame {
+		frame: SLPFrame::new(Vec::new(), Vec::new(), rows),
+		anchor_x: anchor_x - shadow.left,
+		anchor_y: anchor_y - shadow.top,
+	};
+}
+
+/// Add a synthesized shadow to a frame.
+///
+/// Transparent pixels of the frame in the shadow become `Shadow` pixels, as
+/// frames before SLP 4.x store them. The frame grows where the shadow does
+/// not fit.
+///
+/// # Arguments
+///
+/// * `frame` - Frame casting the shadow.
+/// * `anchor_x` - X coordinate of the anchor point of the frame.
+/// * `anchor_y` - Y coordinate of the anchor point of the frame.
+/// * `options` - Shadow options.
+///
+/// # Returns
+///
+/// Frame with shadow pixels and its new anchor.
+///
+/// # Panics
+///
+/// Panics if the squash is not greater than 0.
+pub fn add_shadow(
+	frame: &SLPFrame<PalettePixel>,
+	anchor_x: i32,
+	anchor_y: i32,
+	options: &ShadowOptions,
+) -> ShadowFrame {
+	let shadow = cast_shadow(frame, anchor_y, options);
+	let width = i32::try_from(frame.get_width()).unwrap();
+	let height = i32::try_from(frame.get_height()).unwrap();
+
+	let (left, top, right, bottom) = if shadow.mask.is_empty() {
+		(0, 0, width, height)
+	}
+	else {
+		(
+			shadow.left.min(0),
+			shadow.top.min(0),
+			(shadow.left + shadow.get_width()).max(width),
+			(shadow.top + shadow.get_height()).max(height),
+		)
+	};
+
+	let transparent = PalettePixel::new(SLPPixelType::Transparent, 0);
+	let rows = (top..bottom)
+		.map(|y| {
+			(left..right)
+				.map(|x| {
+					let pixel = usize::try_from(y)
+						.ok()
+						.zip(usize::try_from(x).ok())
+						.and_then(|(fy, fx)| frame.get_pixels().get(fy)?.get(fx))
+						.copied()
+						.unwrap_or(transparent);
+					if pixel.pixel_type == SLPPixelType::Transparent && shadow.contains(x, y) {
+						return PalettePixel::new(SLPPixelType::Shadow, 0);
+					}
+					return pixel;
+				})
+				.collect()
+		})
+		.collect();
+
+	return ShadowFrame {
+		frame: SLPFrame::new(Vec::new(), Vec::new(), rows),
+		anchor_x: anchor_x - left,
+		anchor_y: anchor_y - top,
+	};
+}
+
+/// Synthesize shadows for all frames of an SLP file.
+///
+/// SLP 4.x files get a shadow frame for every main frame, replacing
+/// existing shadow frames. Older files get shadow pixels in their palette
+/// frames, whose sizes and anchors are updated.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file.
+/// * `options` - Shadow options.
+///
+/// # Panics
+///
+/// Panics if the squash is not greater than 0 or a frame becomes too large.
+pub fn shadow_slp(
+	slp: &mut SLPFile,
+	options: &ShadowOptions,
+) {
+	if slp.header.data_v4.is_none() {
+		for (frame, info) in slp.frames.iter_mut().zip(&mut slp.frame_infos) {
+			let shadowed = add_shadow(frame, info.data.anchor_x, info.data.anchor_y, options);
+			*frame = shadowed.frame;
+
+			info.data.width = i32::try_from(frame.get_width()).unwrap();
+			info.data.height = i32::try_from(frame.get_height()).unwrap();
+			info.data.anchor_x = shadowed.anchor_x;
+			info.data.anchor_y = shadowed.anchor_y;
+		}
+		return;
+	}
+
+	let shadows = if slp.rgba_frames.is_empty() {
+		slp.frames
+			.iter()
+			.zip(&slp.frame_infos)
+			.map(|(frame, info)| {
+				generate_shadow(frame, info.data.anchor_x, info.data.anchor_y, options)
+			})
+			.collect::<Vec<ShadowFrame>>()
+	}
+	else {
+		slp.rgba_frames
+			.iter()
+			.zip(&slp.frame_infos)
+			.map(|(frame, info)| {
+				generate_shadow(frame, info.data.anchor_x, info.data.anchor_y, options)
+			})
+			.collect::<Vec<ShadowFrame>>()
+	};
+
+	slp.shadow_frame_infos = shadows
+		.iter()
+		.zip(&slp.frame_infos)
+		.map(|(shadow, info)| {
+			SLPFrameInfo::new(
+				0,
+				0,
+				info.data.palette_offset,
+				info.data.properties & !SLP_FRAME_PROPERTY_32BIT,
+				i32::try_from(shadow.frame.get_width()).unwrap(),
+				i32::try_from(shadow.frame.get_height()).unwrap(),
+				shadow.anchor_x,
+				shadow.anchor_y,
+				SLPFrameType::Shadow,
+				info.slp_version,
+			)
+		})
+		.collect();
+	slp.shadow_frames = shadows.into_iter().map(|shadow| shadow.frame).collect();
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::import::shadow::add_shadow;
+	use crate::import::shadow::generate_shadow;
+	use crate::import::shadow::ShadowOptions;
+	use crate::slp::frame::SLPFrame;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::SLPPixelType;
+
+	/// Test projecting a vertical bar onto the ground.
+	#[test]
+	fn cast_bar_shadow() {
+		let bar = PalettePixel::new(SLPPixelType::Palette, 3);
+		let transparent = PalettePixel::new(SLPPixelType::Transparent, 0);
+		let frame = SLPFrame::new(Vec::new(), Vec::new(), vec![vec![bar, transparent]; 4]);
+		let options = ShadowOptions::default();
+
+		// the bar is 4 pixels high, so the shadow is 2 rows high and moves
+		// 4 pixels to the right
+		let shadow = generate_shadow(&frame, 0, 4, &options);
+		assert_eq!(shadow.frame.get_height(), 2);
+		assert_eq!(shadow.frame.get_width(), 5);
+		assert_eq!((shadow.anchor_x, shadow.anchor_y), (0, 2));
+		let pixels = shadow.frame.get_pixels();
+		assert_eq!(pixels[1][1], PalettePixel::new(SLPPixelType::ShadowV4, 128));
+		assert_eq!(pixels[1][0].pixel_type, SLPPixelType::Transparent);
+		assert_eq!(pixels[0][3].pixel_type, SLPPixelType::ShadowV4);
+		assert_eq!(pixels[0][0].pixel_type, SLPPixelType::Transparent);
+
+		let shadowed = add_shadow(&frame, 0, 4, &options);
+		assert_eq!(shadowed.frame.get_width(), 5);
+		assert_eq!(shadowed.frame.get_height(), 4);
+		assert_eq!((shadowed.anchor_x, shadowed.anchor_y), (0, 4));
+		let pixels = shadowed.frame.get_pixels();
+		assert_eq!(pixels[3][0], bar);
+		assert_eq!(pixels[3][1].pixel_type, SLPPixelType::Shadow);
+		assert_eq!(pixels[0][1], transparent);
+	}
+}
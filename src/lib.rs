@@ -48,18 +48,47 @@
     clippy::semicolon_if_nothing_returned,
     clippy::trivially_copy_pass_by_ref
 )]
+// getters and module layout are intentional
+#![allow(clippy::must_use_candidate, clippy::module_inception)]
 
-mod slp;
-mod util;
+pub mod blendomatic;
+pub mod dds;
+pub mod drs;
+pub mod export;
+pub mod format;
+pub mod import;
+pub mod palette;
+pub mod render;
+pub mod sld;
+pub mod slp;
+pub mod smp;
+pub mod smx;
+pub mod util;
 
-use std::path::PathBuf;
+use std::io;
+use std::path::Path;
 
-use crate::slp::slp::parse_slp;
-
-pub fn parse_file(path: PathBuf) {
-	let content = std::fs::read(path).expect("Failed to read file");
-	let slp = parse_slp(content);
+use crate::format::graphic::open_graphic;
+use crate::format::graphic::Graphic;
+use crate::import::outline::outline_slp;
+use crate::import::outline::OutlineOptions;
+use crate::slp::definitions::SLP_VERSION_4_2P;
+use crate::slp::optimize::optimize_slp_file;
+use crate::slp::optimize::OptimizeOptions;
+use crate::slp::slp::check_slp_header;
+use crate::slp::slp::parse_slp_with_options;
+use crate::slp::slp::SLPFile;
+use crate::slp::slp::SLPParseOptions;
+use crate::slp::writer::encode_slp;
+use crate::slp::writer_v4::encode_slp4;
+use crate::slp::writer_v4::SLP4WriterOptions;
 
+/// Print the contents of an SLP file.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file.
+fn print_slp(slp: SLPFile) {
 	println!("{}", slp.header);
 	println!("---------------------");
 	for frame_info in slp.frame_infos {
@@ -70,4 +99,201 @@ pub fn parse_file(path: PathBuf) {
 		println!("{frame_data}");
 		println!("---------------------");
 	}
+	for frame_data in slp.rgba_frames {
+		println!("{frame_data}");
+		println!("---------------------");
+	}
+	for frame_info in slp.shadow_frame_infos {
+		println!("{frame_info}");
+		println!("---------------------");
+	}
+	for frame_data in slp.shadow_frames {
+		println!("{frame_data}");
+		println!("---------------------");
+	}
+}
+
+/// Print the contents of a graphics file.
+///
+/// # Arguments
+///
+/// * `graphic` - Parsed graphics file.
+fn print_graphic(graphic: Graphic) {
+	match graphic {
+		Graphic::SLP(slp) => print_slp(slp),
+		Graphic::SMP(smp) => {
+			println!("{}", smp.header);
+			println!("---------------------");
+			for frame in smp.frames {
+				for layer in frame.layers {
+					println!("{}", layer.info);
+				}
+				println!("---------------------");
+			}
+		}
+		Graphic::SMX(smx) => {
+			println!("{}", smx.header);
+			println!("---------------------");
+			for frame in smx.frames {
+				println!("{}", frame.header);
+				for layer in frame.layers {
+					println!("{}", layer.info);
+				}
+				println!("---------------------");
+			}
+		}
+		Graphic::SLD(sld) => {
+			println!("{}", sld.header);
+			println!("---------------------");
+			for frame in sld.frames {
+				println!("{}", frame.header);
+				for layer in frame.layers {
+					println!("{}", layer.header);
+				}
+				println!("---------------------");
+			}
+		}
+		Graphic::DRS(drs) => {
+			println!("{}", drs.header);
+			println!("---------------------");
+			for table in &drs.tables {
+				println!("{}", table.info);
+				for entry in &table.entries {
+					println!("{entry}");
+				}
+				println!("---------------------");
+			}
+		}
+		Graphic::DDS(dds) => println!("{}", dds.header),
+		Graphic::Palette(palette) => {
+			for (index, color) in palette.get_colors().iter().enumerate() {
+				println!("{index}: {color:?}");
+			}
+		}
+		Graphic::Blendomatic(blendomatic) => {
+			println!("num_tiles: {}", blendomatic.num_tiles);
+			for (index, mode) in blendomatic.blend_modes.iter().enumerate() {
+				println!("blend mode {index}: {} pixels per tile", mode.pixel_count);
+			}
+		}
+	}
+}
+
+/// Parse a graphics file and print its contents.
+///
+/// # Arguments
+///
+/// * `path` - Path to the file.
+///
+/// # Panics
+///
+/// Panics if the file cannot be read.
+pub fn parse_file(path: &Path) {
+	let content = std::fs::read(path).expect("Failed to read file");
+	match open_graphic(&content) {
+		Ok(graphic) => print_graphic(graphic),
+		Err(error) => eprintln!("Failed to parse {}: {error}", path.display()),
+	}
+}
+
+/// Encode an SLP file with the layout of its header.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file.
+/// * `compress` - Compress SLP 4.x files to 4.2P.
+///
+/// # Returns
+///
+/// Bytes of the SLP file.
+fn encode_slp_file(
+	slp: &SLPFile,
+	compress: bool,
+) -> Vec<u8> {
+	return slp.header.data_v4.as_ref().map_or_else(
+		|| encode_slp(slp),
+		|data_v4| {
+			let options = SLP4WriterOptions {
+				compress,
+				..SLP4WriterOptions::from_header(data_v4)
+			};
+			encode_slp4(slp, options)
+		},
+	);
+}
+
+/// Generate outline pixels for the frames of an SLP file and save the result.
+///
+/// Compressed 4.2P files are written compressed again.
+///
+/// # Arguments
+///
+/// * `path` - Path to the SLP file.
+/// * `output` - Path of the written SLP file.
+/// * `options` - Outline options.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or written, is not an SLP
+/// file or has 32-bit frames.
+pub fn outline_file(
+	path: &Path,
+	output: &Path,
+	options: &OutlineOptions,
+) -> io::Result<()> {
+	let content = std::fs::read(path)?;
+	let compressed = check_slp_header(&content)? == SLP_VERSION_4_2P;
+	let mut slp = parse_slp_with_options(
+		&content,
+		SLPParseOptions {
+			retain_frame_data: true,
+		},
+	);
+	if !slp.rgba_frames.is_empty() {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"Outlines cannot be generated for 32-bit frames",
+		));
+	}
+
+	outline_slp(&mut slp, options);
+	std::fs::write(output, encode_slp_file(&slp, compressed))?;
+	println!(
+		"Wrote {} outlined frames to {}",
+		slp.frames.len(),
+		output.display()
+	);
+
+	return Ok(());
+}
+
+/// Optimize the encoding of an SLP file and save the result.
+///
+/// # Arguments
+///
+/// * `path` - Path to the SLP file.
+/// * `output` - Path of the written SLP file.
+/// * `options` - Optimization options.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or written or is not an SLP
+/// file.
+pub fn optimize_file(
+	path: &Path,
+	output: &Path,
+	options: &OptimizeOptions,
+) -> io::Result<()> {
+	let content = std::fs::read(path)?;
+	let (optimized, report) = optimize_slp_file(content, options)?;
+
+	std::fs::write(output, optimized)?;
+	println!(
+		"Saved {} bytes ({} -> {} bytes)",
+		report.get_bytes_saved(),
+		report.original_size,
+		report.optimized_size
+	);
+
+	return Ok(());
 }
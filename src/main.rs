@@ -1,7 +1,11 @@
 // Copyright 2023-2023 the slutils-rs authors.
 
 use clap::Parser;
+use slutils::import::outline::OutlineOptions;
+use slutils::optimize_file;
+use slutils::outline_file;
 use slutils::parse_file;
+use slutils::slp::optimize::OptimizeOptions;
 
 /// Command line arguments
 #[derive(Parser)]
@@ -9,10 +13,37 @@ use slutils::parse_file;
 struct Cli {
 	/// The path to the file to read
 	path: std::path::PathBuf,
+	/// Generate outline pixels around the silhouette of the SLP frames
+	#[arg(long, requires = "output")]
+	outline: bool,
+	/// Re-encode the SLP file with the smallest commands and shared data
+	#[arg(long, requires = "output", conflicts_with = "outline")]
+	optimize: bool,
+	/// Crop the transparent borders of the frames when optimizing
+	#[arg(long, requires = "optimize")]
+	crop: bool,
+	/// The path of the written file
+	#[arg(short, long)]
+	output: Option<std::path::PathBuf>,
 }
 
 fn main() {
 	let args = Cli::parse();
 
-	parse_file(args.path);
+	match args.output {
+		Some(output) if args.outline => {
+			if let Err(error) = outline_file(&args.path, &output, &OutlineOptions::default()) {
+				eprintln!("Failed to outline {}: {error}", args.path.display());
+			}
+		}
+		Some(output) if args.optimize => {
+			let options = OptimizeOptions {
+				crop_frames: args.crop,
+			};
+			if let Err(error) = optimize_file(&args.path, &output, &options) {
+				eprintln!("Failed to optimize {}: {error}", args.path.display());
+			}
+		}
+		_ => parse_file(&args.path),
+	}
 }
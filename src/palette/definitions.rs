@@ -0,0 +1,17 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+/// Magic string at the start of a JASC palette file.
+pub const JASC_PAL_MAGIC: &str = "JASC-PAL";
+
+/// Maximum number of colors in a palette.
+pub const PALETTE_MAX_COLORS: usize = 256;
+
+/// Number of bits per channel used for cells of the color lookup table.
+pub const QUANTIZE_LOOKUP_BITS: u32 = 6;
+
+/// Range of the offsets added to colors by ordered dithering.
+pub const QUANTIZE_ORDERED_SPREAD: f32 = 32.0;
+
+/// 4x4 Bayer matrix used by ordered dithering.
+pub const QUANTIZE_BAYER_MATRIX: [[u8; 4]; 4] =
+	[[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
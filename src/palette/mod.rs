@@ -0,0 +1,5 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+pub mod definitions;
+pub mod palette;
+pub mod quantize;
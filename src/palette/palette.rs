@@ -0,0 +1,260 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::collections::HashMap;
+use std::io;
+use std::ops::Range;
+
+use crate::palette::definitions::JASC_PAL_MAGIC;
+use crate::palette::definitions::PALETTE_MAX_COLORS;
+
+/// Color palette used by palette-indexed frames.
+#[derive(Debug, Clone)]
+pub struct Palette {
+	/// RGBA colors, indexed by palette index.
+	colors: Vec<[u8; 4]>,
+}
+
+impl Palette {
+	/// Create a new palette.
+	///
+	/// # Arguments
+	///
+	/// * `colors` - RGBA colors, indexed by palette index.
+	///
+	/// # Returns
+	///
+	/// New palette.
+	///
+	/// # Panics
+	///
+	/// Panics if there are more than 256 colors.
+	pub fn new(colors: Vec<[u8; 4]>) -> Self {
+		assert!(
+			colors.len() <= PALETTE_MAX_COLORS,
+			"Palette can have at most {} colors, got {}",
+			PALETTE_MAX_COLORS,
+			colors.len()
+		);
+
+		Self { colors }
+	}
+
+	/// Get the number of colors in the palette.
+	///
+	/// # Returns
+	///
+	/// Number of colors.
+	pub const fn get_num_colors(&self) -> usize {
+		return self.colors.len();
+	}
+
+	/// Get the color for a palette index.
+	///
+	/// # Arguments
+	///
+	/// * `index` - Palette index.
+	///
+	/// # Returns
+	///
+	/// RGBA color or `None` if the index is not in the palette.
+	pub fn get_color(
+		&self,
+		index: u8,
+	) -> Option<[u8; 4]> {
+		return self.colors.get(index as usize).copied();
+	}
+
+	/// Get all colors in the palette.
+	///
+	/// # Returns
+	///
+	/// RGBA colors, indexed by palette index.
+	pub fn get_colors(&self) -> &[[u8; 4]] {
+		return &self.colors;
+	}
+
+	/// Find the palette index with the color closest to an RGB color.
+	///
+	/// # Arguments
+	///
+	/// * `color` - RGB color.
+	///
+	/// # Returns
+	///
+	/// Palette index of the closest color.
+	pub fn find_closest_color(
+		&self,
+		color: [u8; 3],
+	) -> u8 {
+		return self.find_closest_color_in(color, 0..self.colors.len());
+	}
+
+	/// Find the index with the color closest to an RGB color in a range of
+	/// palette indices.
+	///
+	/// # Arguments
+	///
+	/// * `color` - RGB color.
+	/// * `range` - Palette indices to search.
+	///
+	/// # Returns
+	///
+	/// Palette index of the closest color or the start of the range if no
+	/// index of the range is in the palette.
+	///
+	/// # Panics
+	///
+	/// Panics if the range contains indices above 255.
+	pub fn find_closest_color_in(
+		&self,
+		color: [u8; 3],
+		range: Range<usize>,
+	) -> u8 {
+		let distance = |other: &[u8; 4]| -> u32 {
+			return (0..3)
+				.map(|c| {
+					let diff = u32::from(color[c].abs_diff(other[c]));
+					diff * diff
+				})
+				.sum();
+		};
+
+		let start = range.start;
+		let index = self
+			.colors
+			.iter()
+			.enumerate()
+			.take(range.end)
+			.skip(start)
+			.min_by_key(|(_, other)| distance(other))
+			.map_or(start, |(index, _)| index);
+
+		return u8::try_from(index).unwrap();
+	}
+
+	/// Get the palette as a lookup table for pixel conversion.
+	///
+	/// # Returns
+	///
+	/// Map from palette index to RGBA color.
+	pub fn get_lookup(&self) -> HashMap<usize, [u8; 4]> {
+		return self.colors.iter().copied().enumerate().collect();
+	}
+
+	/// Get the palette as a flat list of RGB bytes.
+	///
+	/// The list is padded with black to 256 colors.
+	///
+	/// # Returns
+	///
+	/// RGB bytes of all palette colors.
+	pub fn to_rgb_bytes(&self) -> Vec<u8> {
+		let mut bytes = Vec::<u8>::with_capacity(PALETTE_MAX_COLORS * 3);
+		for color in &self.colors {
+			bytes.extend_from_slice(&color[0..3]);
+		}
+		bytes.resize(PALETTE_MAX_COLORS * 3, 0);
+
+		return bytes;
+	}
+}
+
+/// Parse a palette file in JASC-PAL format.
+///
+/// The file consists of the magic string, a version line, the number of
+/// colors and one line per color with 3 (RGB) or 4 (RGBA) values.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the palette file.
+///
+/// # Returns
+///
+/// The parsed palette.
+///
+/// # Errors
+///
+/// Returns an error if the file is not a valid JASC-PAL palette.
+pub fn parse_palette(bytes: &[u8]) -> io::Result<Palette> {
+	let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+	let content = std::str::from_utf8(bytes)
+		.map_err(|_| invalid("Palette file is not valid text".to_string()))?;
+	let mut lines = content.lines().map(str::trim).filter(|l| !l.is_empty());
+
+	let magic = lines.next().unwrap_or_default();
+	if magic != JASC_PAL_MAGIC {
+		return Err(invalid(format!(
+			"Expected palette magic {JASC_PAL_MAGIC}, found {magic}"
+		)));
+	}
+
+	// version line, always 0100
+	let _version = lines
+		.next()
+		.ok_or_else(|| invalid("Palette file is missing the version".to_string()))?;
+
+	let num_colors: usize = lines
+		.next()
+		.ok_or_else(|| invalid("Palette file is missing the color count".to_string()))?
+		.parse()
+		.map_err(|_| invalid("Palette color count is not a number".to_string()))?;
+
+	let mut colors = Vec::<[u8; 4]>::with_capacity(num_colors.min(PALETTE_MAX_COLORS));
+	for line in lines.take(num_colors) {
+		let values = line
+			.split_whitespace()
+			.map(str::parse::<u8>)
+			.collect::<Result<Vec<u8>, _>>()
+			.map_err(|_| invalid(format!("Palette color value is not a byte: {line}")))?;
+
+		match values.as_slice() {
+			[r, g, b] => colors.push([*r, *g, *b, 255]),
+			[r, g, b, a] => colors.push([*r, *g, *b, *a]),
+			_ => return Err(invalid(format!("Invalid palette color entry: {line}"))),
+		}
+	}
+
+	if colors.len() != num_colors {
+		return Err(invalid(format!(
+			"Expected {} palette colors, found {}",
+			num_colors,
+			colors.len()
+		)));
+	}
+
+	return Ok(Palette::new(colors));
+}
+
+#[cfg(test)]
+mod tests {
+	/// Test parsing a JASC-PAL palette with RGB and RGBA entries.
+	#[test]
+	fn parse_jasc_palette() {
+		let content = "JASC-PAL\r\n0100\r\n3\r\n0 0 0\r\n255 128 64\r\n1 2 3 4\r\n";
+		let palette = crate::palette::palette::parse_palette(content.as_bytes()).unwrap();
+
+		assert_eq!(palette.get_num_colors(), 3);
+		assert_eq!(palette.get_color(0), Some([0, 0, 0, 255]));
+		assert_eq!(palette.get_color(1), Some([255, 128, 64, 255]));
+		assert_eq!(palette.get_color(2), Some([1, 2, 3, 4]));
+		assert_eq!(palette.get_color(3), None);
+
+		let rgb = palette.to_rgb_bytes();
+		assert_eq!(rgb.len(), 768);
+		assert_eq!(&rgb[3..6], &[255, 128, 64]);
+	}
+
+	/// Test that truncated and malformed palettes return an error.
+	#[test]
+	fn parse_invalid_palette() {
+		for content in [
+			"JASC-PAL\r\n",
+			"JASC-PAL\r\n0100\r\n3\r\n0 0 0\r\n",
+			"JASC-PAL\r\n0100\r\n1\r\n0 0 256\r\n",
+			"JASC-PAL\r\n0100\r\n1\r\n0 0\r\n",
+		] {
+			assert!(crate::palette::palette::parse_palette(content.as_bytes()).is_err());
+		}
+	}
+}
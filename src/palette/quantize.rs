@@ -0,0 +1,436 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::palette::definitions::QUANTIZE_BAYER_MATRIX;
+use crate::palette::definitions::QUANTIZE_LOOKUP_BITS;
+use crate::palette::definitions::QUANTIZE_ORDERED_SPREAD;
+use crate::palette::palette::Palette;
+
+/// Dithering applied when quantizing images.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dithering {
+	/// Every pixel is mapped to its closest color.
+	#[default]
+	None,
+	/// The quantization error is diffused to the neighbouring pixels.
+	FloydSteinberg,
+	/// Colors are offset by a 4x4 Bayer matrix before matching.
+	Ordered,
+}
+
+/// Options for quantizing images to a palette.
+#[derive(Debug, Clone, Default)]
+pub struct QuantizeOptions {
+	/// Dithering applied to the image.
+	pub dithering: Dithering,
+	/// Palette indices that are never chosen, e.g. player colors.
+	pub excluded: Vec<Range<usize>>,
+}
+
+/// Quantization error of an image.
+///
+/// Errors are distances in the Oklab color space, where 1.0 is the distance
+/// between black and white.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct QuantizeReport {
+	/// Number of quantized pixels.
+	pub num_pixels: usize,
+	/// Mean error of the quantized pixels.
+	pub mean_error: f32,
+	/// Largest error of a quantized pixel.
+	pub max_error: f32,
+}
+
+/// Convert an sRGB channel to linear light.
+///
+/// # Arguments
+///
+/// * `value` - sRGB channel value.
+///
+/// # Returns
+///
+/// Linear channel value between 0.0 and 1.0.
+fn to_linear(value: f32) -> f32 {
+	let value = value / 255.0;
+	if value <= 0.040_45 {
+		return value / 12.92;
+	}
+
+	return ((value + 0.055) / 1.055).powf(2.4);
+}
+
+/// Convert an sRGB color to the perceptual Oklab color space.
+///
+/// # Arguments
+///
+/// * `color` - sRGB color with channels between 0.0 and 255.0.
+///
+/// # Returns
+///
+/// Oklab color (L, a, b).
+#[allow(clippy::many_single_char_names, clippy::suboptimal_flops)]
+pub fn to_oklab(color: [f32; 3]) -> [f32; 3] {
+	let [r, g, b] = color.map(to_linear);
+
+	let l = 0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b;
+	let m = 0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b;
+	let s = 0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b;
+	let [l, m, s] = [l.cbrt(), m.cbrt(), s.cbrt()];
+
+	return [
+		0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s,
+		1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s,
+		0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s,
+	];
+}
+
+/// Get the distance between two Oklab colors.
+///
+/// # Arguments
+///
+/// * `a` - First color.
+/// * `b` - Second color.
+///
+/// # Returns
+///
+/// Euclidean distance of the colors.
+fn distance(
+	a: [f32; 3],
+	b: [f32; 3],
+) -> f32 {
+	return (0..3).map(|c| (a[c] - b[c]).powi(2)).sum::<f32>().sqrt();
+}
+
+/// Quantizer mapping true colors to the indices of a palette.
+///
+/// Colors are compared in the Oklab color space. Matches are cached in a 3D
+/// lookup table over RGB cells, so a quantizer should be reused for all
+/// frames that use the same palette.
+#[derive(Debug, Clone)]
+pub struct Quantizer {
+	/// Palette colors as sRGB and Oklab colors, `None` for excluded indices.
+	colors: Vec<Option<([f32; 3], [f32; 3])>>,
+	/// First palette index of each exact sRGB color.
+	exact: HashMap<[u8; 3], u8>,
+	/// Closest palette index per RGB cell, filled on first use.
+	lookup: Vec<Option<u8>>,
+	/// Dithering applied to images.
+	dithering: Dithering,
+}
+
+impl Quantizer {
+	/// Create a quantizer for a palette.
+	///
+	/// # Arguments
+	///
+	/// * `palette` - Palette to quantize to.
+	/// * `options` - Quantization options.
+	///
+	/// # Returns
+	///
+	/// New quantizer.
+	///
+	/// # Panics
+	///
+	/// Panics if all palette indices are excluded.
+	pub fn new(
+		palette: &Palette,
+		options: &QuantizeOptions,
+	) -> Self {
+		let colors = palette
+			.get_colors()
+			.iter()
+			.enumerate()
+			.map(|(index, color)| {
+				if options.excluded.iter().any(|range| range.contains(&index)) {
+					return None;
+				}
+				let rgb = [color[0], color[1], color[2]].map(f32::from);
+				return Some((rgb, to_oklab(rgb)));
+			})
+			.collect::<Vec<_>>();
+		assert!(
+			colors.iter().any(Option::is_some),
+			"All palette colors are excluded from quantization"
+		);
+
+		// iterate backwards so duplicate colors keep their first index
+		let exact = palette
+			.get_colors()
+			.iter()
+			.enumerate()
+			.rev()
+			.filter(|(index, _)| colors[*index].is_some())
+			.map(|(index, color)| {
+				// palettes have at most 256 colors
+				#[allow(clippy::cast_possible_truncation)]
+				return ([color[0], color[1], color[2]], index as u8);
+			})
+			.collect::<HashMap<_, _>>();
+
+		return Self {
+			colors,
+			exact,
+			lookup: vec![None; 1 << (3 * QUANTIZE_LOOKUP_BITS)],
+			dithering: options.dithering,
+		};
+	}
+
+	/// Find the palette index closest to a color without the lookup table.
+	///
+	/// # Arguments
+	///
+	/// * `lab` - Oklab color.
+	///
+	/// # Returns
+	///
+	/// Closest palette index.
+	fn find_closest(
+		&self,
+		lab: [f32; 3],
+	) -> u8 {
+		let index = self
+			.colors
+			.iter()
+			.enumerate()
+			.filter_map(|(index, color)| color.map(|(_, other)| (index, distance(lab, other))))
+			.min_by(|a, b| a.1.total_cmp(&b.1))
+			.map_or(0, |(index, _)| index);
+
+		// palettes have at most 256 colors
+		#[allow(clippy::cast_possible_truncation)]
+		return index as u8;
+	}
+
+	/// Get the palette index closest to an RGB color.
+	///
+	/// Colors of the palette map to their own index. Other colors are
+	/// matched against the center of the lookup table cell containing the
+	/// color, which is cached.
+	///
+	/// # Arguments
+	///
+	/// * `color` - RGB color.
+	///
+	/// # Returns
+	///
+	/// Closest palette index.
+	pub fn get_index(
+		&mut self,
+		color: [u8; 3],
+	) -> u8 {
+		if let Some(&index) = self.exact.get(&color) {
+			return index;
+		}
+
+		let shift = 8 - QUANTIZE_LOOKUP_BITS;
+		let cell = color.map(|c| usize::from(c >> shift));
+		let key =
+			(cell[0] << (2 * QUANTIZE_LOOKUP_BITS)) | (cell[1] << QUANTIZE_LOOKUP_BITS) | cell[2];
+
+		if let Some(index) = self.lookup[key] {
+			return index;
+		}
+
+		let center = color.map(|c| f32::from(((c >> shift) << shift) | (1 << (shift - 1))));
+		let index = self.find_closest(to_oklab(center));
+		self.lookup[key] = Some(index);
+
+		return index;
+	}
+
+	/// Get the sRGB color of a palette index.
+	///
+	/// # Arguments
+	///
+	/// * `index` - Palette index chosen by the quantizer.
+	///
+	/// # Returns
+	///
+	/// sRGB and Oklab color of the index.
+	fn get_color(
+		&self,
+		index: u8,
+	) -> ([f32; 3], [f32; 3]) {
+		return self.colors[index as usize].unwrap();
+	}
+
+	/// Quantize the pixels of an image.
+	///
+	/// Only selected pixels are quantized. Unselected pixels neither receive
+	/// nor spread dithering errors.
+	///
+	/// # Arguments
+	///
+	/// * `image` - Image to quantize.
+	/// * `select` - Returns true for pixels that should be quantized, given
+	///   their coordinates and color.
+	///
+	/// # Returns
+	///
+	/// Rows of palette indices (`None` for unselected pixels) and the error
+	/// of the quantized pixels.
+	pub fn quantize<F>(
+		&mut self,
+		image: &image::RgbaImage,
+		select: F,
+	) -> (Vec<Vec<Option<u8>>>, QuantizeReport)
+	where
+		F: Fn(u32, u32, [u8; 4]) -> bool,
+	{
+		let width = image.width() as usize;
+		let mut errors = vec![[0.0f32; 3]; width + 2];
+		let mut next_errors = vec![[0.0f32; 3]; width + 2];
+		let mut report = QuantizeReport::default();
+		let mut total_error = 0.0f64;
+
+		let mut rows = Vec::<Vec<Option<u8>>>::with_capacity(image.height() as usize);
+		for y in 0..image.height() {
+			let mut row = Vec::<Option<u8>>::with_capacity(width);
+			for x in 0..image.width() {
+				let color = image.get_pixel(x, y).0;
+				if !select(x, y, color) {
+					row.push(None);
+					continue;
+				}
+
+				let source = [color[0], color[1], color[2]].map(f32::from);
+				let target = match self.dithering {
+					Dithering::None => source,
+					Dithering::FloydSteinberg => {
+						let error = errors[x as usize + 1];
+						[0, 1, 2].map(|c| source[c] + error[c])
+					}
+					Dithering::Ordered => {
+						let threshold = QUANTIZE_BAYER_MATRIX[y as usize % 4][x as usize % 4];
+						let offset = (f32::from(threshold) + 0.5) / 16.0 - 0.5;
+						source.map(|c| c + offset * QUANTIZE_ORDERED_SPREAD)
+					}
+				};
+
+				// the target is clamped to the channel range
+				#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+				let index = self.get_index(target.map(|c| c.clamp(0.0, 255.0).round() as u8));
+				let (rgb, lab) = self.get_color(index);
+
+				if self.dithering == Dithering::FloydSteinberg {
+					let error = [0, 1, 2].map(|c| target[c] - rgb[c]);
+					let x = x as usize + 1;
+					for c in 0..3 {
+						errors[x + 1][c] += error[c] * 7.0 / 16.0;
+						next_errors[x - 1][c] += error[c] * 3.0 / 16.0;
+						next_errors[x][c] += error[c] * 5.0 / 16.0;
+						next_errors[x + 1][c] += error[c] / 16.0;
+					}
+				}
+
+				let error = distance(to_oklab(source), lab);
+				total_error += f64::from(error);
+				report.max_error = report.max_error.max(error);
+				report.num_pixels += 1;
+				row.push(Some(index));
+			}
+
+			std::mem::swap(&mut errors, &mut next_errors);
+			next_errors.fill([0.0; 3]);
+			rows.push(row);
+		}
+
+		if report.num_pixels > 0 {
+			// the mean only needs single precision
+			#[allow(clippy::cast_possible_truncation, clippy::cast_precision_loss)]
+			{
+				report.mean_error = (total_error / report.num_pixels as f64) as f32;
+			}
+		}
+
+		return (rows, report);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::palette::palette::Palette;
+	use crate::palette::quantize::Dithering;
+	use crate::palette::quantize::QuantizeOptions;
+	use crate::palette::quantize::Quantizer;
+
+	/// Test matching, excluded ranges and dithering of a dark gray.
+	#[test]
+	#[allow(clippy::single_range_in_vec_init)]
+	fn quantize_gray() {
+		let palette = Palette::new(vec![
+			[0, 0, 0, 255],
+			[128, 128, 128, 255],
+			[255, 255, 255, 255],
+			[120, 120, 120, 255],
+		]);
+		let mut quantizer = Quantizer::new(
+			&palette,
+			&QuantizeOptions {
+				dithering: Dithering::None,
+				excluded: vec![3..4],
+			},
+		);
+		assert_eq!(quantizer.get_index([10, 10, 10]), 0);
+		assert_eq!(quantizer.get_index([121, 121, 121]), 1);
+		assert_eq!(quantizer.get_index([250, 250, 250]), 2);
+
+		let image = image::RgbaImage::from_pixel(16, 16, image::Rgba([46, 46, 46, 255]));
+		let (rows, report) = quantizer.quantize(&image, |x, _, _| x > 0);
+		assert_eq!(rows[0][0], None);
+		assert_eq!(report.num_pixels, 16 * 15);
+		assert!(report.mean_error > 0.0);
+		assert!((report.mean_error - report.max_error).abs() < 1e-6);
+
+		for dithering in [Dithering::FloydSteinberg, Dithering::Ordered] {
+			let mut quantizer = Quantizer::new(
+				&palette,
+				&QuantizeOptions {
+					dithering,
+					excluded: vec![3..4],
+				},
+			);
+			let (rows, _) = quantizer.quantize(&image, |_, _, _| true);
+			let mut counts = [0; 3];
+			for index in rows.into_iter().flatten().flatten() {
+				counts[index as usize] += 1;
+			}
+			assert!(counts[0] > 64, "{dithering:?}: {counts:?}");
+			assert!(counts[1] > 64, "{dithering:?}: {counts:?}");
+		}
+	}
+
+	/// Test that palette colors sharing a lookup cell map back to their index.
+	#[test]
+	fn quantize_exact_colors() {
+		let palette = Palette::new(vec![
+			[100, 100, 100, 255],
+			[101, 101, 101, 255],
+			[100, 100, 100, 255],
+			[0, 0, 0, 255],
+		]);
+		let mut quantizer = Quantizer::new(&palette, &QuantizeOptions::default());
+		assert_eq!(quantizer.get_index([101, 101, 101]), 1);
+		assert_eq!(quantizer.get_index([100, 100, 100]), 0);
+
+		let image = image::RgbaImage::from_fn(2, 1, |x, _| {
+			let color = palette.get_colors()[x as usize];
+			return image::Rgba(color);
+		});
+		for dithering in [Dithering::None, Dithering::FloydSteinberg] {
+			let mut quantizer = Quantizer::new(
+				&palette,
+				&QuantizeOptions {
+					dithering,
+					excluded: Vec::new(),
+				},
+			);
+			let (rows, report) = quantizer.quantize(&image, |_, _, _| true);
+			assert_eq!(rows, vec![vec![Some(0), Some(1)]], "{dithering:?}");
+			assert!(report.max_error.abs() < 1e-6);
+		}
+	}
+}
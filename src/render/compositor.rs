@@ -0,0 +1,447 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::palette::palette::Palette;
+use crate::slp::definitions::SLP_PLAYER_COLOR_BLOCK_SIZE;
+use crate::slp::frame::SLPFrame;
+use crate::slp::frame_info::SLPFrameInfo;
+use crate::slp::pixel::PalettePixel;
+use crate::slp::pixel::SLPPixelType;
+use crate::slp::slp::SLPFile;
+use crate::util::canvas::AnchoredCanvas;
+use crate::util::pixel::blend_over;
+
+/// Switches for the layers drawn by the compositor.
+///
+/// Layers are drawn in the order of the fields: shadow first, then the main
+/// graphics and player colors, then the outlines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct RenderLayers {
+	/// Shadow pixels and shadow frames.
+	pub shadow: bool,
+	/// Palette colored pixels.
+	pub main: bool,
+	/// Player colored pixels.
+	pub player: bool,
+	/// Player color outline pixels (`Special1`).
+	pub player_outline: bool,
+	/// Black outline pixels (`Special2`).
+	pub black_outline: bool,
+}
+
+impl RenderLayers {
+	/// All layers enabled.
+	pub const ALL: Self = Self {
+		shadow: true,
+		main: true,
+		player: true,
+		player_outline: true,
+		black_outline: true,
+	};
+
+	/// Layers that are visible for an unobstructed object in the game.
+	pub const VISIBLE: Self = Self {
+		shadow: true,
+		main: true,
+		player: true,
+		player_outline: false,
+		black_outline: false,
+	};
+
+	/// Layers that are visible when the object is hidden behind another
+	/// object in the game.
+	pub const OCCLUDED: Self = Self {
+		shadow: false,
+		main: false,
+		player: false,
+		player_outline: true,
+		black_outline: true,
+	};
+}
+
+impl Default for RenderLayers {
+	fn default() -> Self {
+		Self::VISIBLE
+	}
+}
+
+/// Options for compositing frame layers.
+#[derive(Debug, Clone, Copy)]
+pub struct CompositorOptions {
+	/// Layers that are drawn.
+	pub layers: RenderLayers,
+	/// Player number used for player colors.
+	pub player: u8,
+	/// Opacity of shadow pixels in main frames. Shadow frames store their
+	/// own opacity per pixel.
+	pub shadow_alpha: u8,
+	/// Draw black outline pixels (`Special2`) in the player's outline color,
+	/// as the game does for objects hidden behind other objects.
+	pub occluded: bool,
+}
+
+impl Default for CompositorOptions {
+	fn default() -> Self {
+		Self {
+			layers: RenderLayers::VISIBLE,
+			player: 1,
+			shadow_alpha: 128,
+			occluded: false,
+		}
+	}
+}
+
+/// Frame rendered to an RGBA image.
+#[derive(Debug, Clone)]
+pub struct RenderedFrame {
+	/// Rendered image.
+	pub image: image::RgbaImage,
+	/// X coordinate of the anchor point in the image.
+	pub anchor_x: i32,
+	/// Y coordinate of the anchor point in the image.
+	pub anchor_y: i32,
+}
+
+/// Get the palette index of the player color outline.
+///
+/// The outline uses the first color in the player's color block.
+///
+/// # Arguments
+///
+/// * `player` - Player number.
+///
+/// # Returns
+///
+/// Palette index of the outline color.
+pub const fn get_outline_index(player: u8) -> u8 {
+	return player.wrapping_mul(SLP_PLAYER_COLOR_BLOCK_SIZE);
+}
+
+/// Get the color of a shadow pixel.
+///
+/// # Arguments
+///
+/// * `pixel` - Pixel to draw.
+/// * `options` - Compositor options.
+///
+/// # Returns
+///
+/// Color of the pixel or `None` if it is not on the shadow layer.
+const fn get_shadow_color(
+	pixel: PalettePixel,
+	options: CompositorOptions,
+) -> Option<[u8; 4]> {
+	match pixel.pixel_type {
+		SLPPixelType::Shadow => Some([0, 0, 0, options.shadow_alpha]),
+		// shadow frames store the intensity as the color value
+		SLPPixelType::ShadowV4 => Some([0, 0, 0, pixel.index]),
+		_ => None,
+	}
+}
+
+/// Get the color of a main graphics or player color pixel.
+///
+/// # Arguments
+///
+/// * `pixel` - Pixel to draw.
+/// * `palette` - Palette used by the frame.
+/// * `options` - Compositor options.
+///
+/// # Returns
+///
+/// Color of the pixel or `None` if it is not on an enabled layer.
+fn get_main_color(
+	pixel: PalettePixel,
+	palette: &Palette,
+	options: CompositorOptions,
+) -> Option<[u8; 4]> {
+	match pixel.pixel_type {
+		SLPPixelType::Palette if options.layers.main => palette.get_color(pixel.index),
+		SLPPixelType::Player | SLPPixelType::PlayerV4 if options.layers.player => {
+			palette.get_color(pixel.get_player_index(options.player))
+		}
+		_ => None,
+	}
+}
+
+/// Get the color of an outline pixel.
+///
+/// # Arguments
+///
+/// * `pixel` - Pixel to draw.
+/// * `palette` - Palette used by the frame.
+/// * `options` - Compositor options.
+///
+/// # Returns
+///
+/// Color of the pixel or `None` if it is not on an enabled layer.
+fn get_outline_color(
+	pixel: PalettePixel,
+	palette: &Palette,
+	options: CompositorOptions,
+) -> Option<[u8; 4]> {
+	match pixel.pixel_type {
+		SLPPixelType::Special1 if options.layers.player_outline => {
+			palette.get_color(get_outline_index(options.player))
+		}
+		SLPPixelType::Special2 if options.layers.black_outline => {
+			if options.occluded {
+				return palette.get_color(get_outline_index(options.player));
+			}
+			Some([0, 0, 0, 255])
+		}
+		_ => None,
+	}
+}
+
+/// Draw the pixels of a frame onto an image.
+///
+/// # Arguments
+///
+/// * `image` - Image to draw on.
+/// * `frame` - Frame to draw.
+/// * `position` - Position of the frame's top left corner in the image.
+/// * `get_color` - Function returning the color of a pixel or `None` if the
+///   pixel is not drawn.
+fn draw_layer<F>(
+	image: &mut image::RgbaImage,
+	frame: &SLPFrame<PalettePixel>,
+	position: (i32, i32),
+	get_color: F,
+) where
+	F: Fn(PalettePixel) -> Option<[u8; 4]>,
+{
+	for (i, row) in frame.get_pixels().iter().enumerate() {
+		for (j, pixel) in row.iter().enumerate() {
+			let Some(color) = get_color(*pixel)
+			else {
+				continue;
+			};
+
+			let x = u32::try_from(position.0).unwrap() + u32::try_from(j).unwrap();
+			let y = u32::try_from(position.1).unwrap() + u32::try_from(i).unwrap();
+			let dst = image.get_pixel_mut(x, y);
+			dst.0 = blend_over(dst.0, color);
+		}
+	}
+}
+
+/// Composite the layers of a frame into a single RGBA image.
+///
+/// The layers are drawn in the order the game draws them: shadows are
+/// blended onto the background first, then the main graphics and player
+/// colors are drawn on top, followed by the outlines. If a shadow frame is
+/// given, it is aligned with the main frame on the anchor points.
+///
+/// # Arguments
+///
+/// * `frame` - Main frame.
+/// * `frame_info` - Frame info of the main frame.
+/// * `shadow` - Shadow frame and its frame info (SLP 4.x only).
+/// * `palette` - Palette used by the frames.
+/// * `options` - Compositor options.
+///
+/// # Returns
+///
+/// Rendered frame.
+///
+/// # Panics
+///
+/// Panics if the frames are too large for an image.
+pub fn composite_frame(
+	frame: &SLPFrame<PalettePixel>,
+	frame_info: &SLPFrameInfo,
+	shadow: Option<(&SLPFrame<PalettePixel>, &SLPFrameInfo)>,
+	palette: &Palette,
+	options: CompositorOptions,
+) -> RenderedFrame {
+	let canvas = match shadow {
+		Some((_, shadow_info)) => AnchoredCanvas::from_frame_infos([frame_info, shadow_info]),
+		None => AnchoredCanvas::from_frame_infos([frame_info]),
+	};
+	let mut image = image::RgbaImage::new(
+		u32::try_from(canvas.width).unwrap(),
+		u32::try_from(canvas.height).unwrap(),
+	);
+	let position = canvas.get_frame_position(frame_info);
+
+	if options.layers.shadow {
+		if let Some((shadow_frame, shadow_info)) = shadow {
+			draw_layer(
+				&mut image,
+				shadow_frame,
+				canvas.get_frame_position(shadow_info),
+				|pixel| get_shadow_color(pixel, options),
+			);
+		}
+		draw_layer(&mut image, frame, position, |pixel| {
+			get_shadow_color(pixel, options)
+		});
+	}
+
+	draw_layer(&mut image, frame, position, |pixel| {
+		get_main_color(pixel, palette, options)
+	});
+	draw_layer(&mut image, frame, position, |pixel| {
+		get_outline_color(pixel, palette, options)
+	});
+
+	return RenderedFrame {
+		image,
+		anchor_x: canvas.anchor_x,
+		anchor_y: canvas.anchor_y,
+	};
+}
+
+/// Composite the layers of a frame in an SLP file.
+///
+/// The matching shadow frame is included if the file has shadow frames.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file containing the frame.
+/// * `index` - Index of the frame.
+/// * `palette` - Palette used by the frames.
+/// * `options` - Compositor options.
+///
+/// # Returns
+///
+/// Rendered frame or `None` if the index is out of range or the file has
+/// 32-bit frames.
+///
+/// # Panics
+///
+/// Panics if the frames are too large for an image.
+pub fn composite_slp_frame(
+	slp: &SLPFile,
+	index: usize,
+	palette: &Palette,
+	options: CompositorOptions,
+) -> Option<RenderedFrame> {
+	let frame = slp.frames.get(index)?;
+	let frame_info = slp.frame_infos.get(index)?;
+	let shadow = slp
+		.shadow_frames
+		.get(index)
+		.zip(slp.shadow_frame_infos.get(index));
+
+	return Some(composite_frame(frame, frame_info, shadow, palette, options));
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::palette::palette::Palette;
+	use crate::render::compositor::composite_frame;
+	use crate::render::compositor::composite_slp_frame;
+	use crate::render::compositor::CompositorOptions;
+	use crate::render::compositor::RenderLayers;
+	use crate::slp::frame::SLPFrame;
+	use crate::slp::frame_info::SLPFrameInfo;
+	use crate::slp::frame_info::SLPFrameType;
+	use crate::slp::header::SLPHeader;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::RGBAPixel;
+	use crate::slp::pixel::SLPPixelType;
+	use crate::slp::slp::SLPFile;
+
+	/// Test that the body, player color and outline layers are drawn over
+	/// the shadow.
+	#[test]
+	fn composite_layer_order() {
+		let mut colors = vec![[0, 0, 0, 255]; 19];
+		colors[1] = [255, 0, 0, 128];
+		colors[16] = [0, 0, 200, 255];
+		colors[18] = [0, 100, 0, 255];
+		let palette = Palette::new(colors);
+
+		let frame = SLPFrame::new(
+			Vec::new(),
+			Vec::new(),
+			vec![vec![
+				PalettePixel::new(SLPPixelType::Palette, 1),
+				PalettePixel::new(SLPPixelType::Transparent, 0),
+				PalettePixel::new(SLPPixelType::Player, 2),
+				PalettePixel::new(SLPPixelType::Special1, 0),
+			]],
+		);
+		let shadow = SLPFrame::new(
+			Vec::new(),
+			Vec::new(),
+			vec![vec![PalettePixel::new(SLPPixelType::ShadowV4, 100); 4]],
+		);
+		let frame_info = SLPFrameInfo::new(0, 0, 0, 0, 4, 1, 0, 0, SLPFrameType::Main, *b"4.1X");
+		let shadow_info = SLPFrameInfo {
+			frame_type: SLPFrameType::Shadow,
+			..frame_info
+		};
+
+		let options = CompositorOptions {
+			layers: RenderLayers::ALL,
+			..CompositorOptions::default()
+		};
+		let rendered = composite_frame(
+			&frame,
+			&frame_info,
+			Some((&shadow, &shadow_info)),
+			&palette,
+			options,
+		);
+		let pixels = rendered
+			.image
+			.pixels()
+			.map(|pixel| pixel.0)
+			.collect::<Vec<_>>();
+		assert_eq!(
+			pixels,
+			[
+				// translucent body blended over the shadow
+				[184, 0, 0, 177],
+				[0, 0, 0, 100],
+				// player color of player 1
+				[0, 100, 0, 255],
+				// player color outline over the shadow
+				[0, 0, 200, 255],
+			]
+		);
+
+		// outlines are hidden for visible objects
+		let rendered = composite_frame(
+			&frame,
+			&frame_info,
+			Some((&shadow, &shadow_info)),
+			&palette,
+			CompositorOptions::default(),
+		);
+		assert_eq!(rendered.image.get_pixel(3, 0).0, [0, 0, 0, 100]);
+	}
+
+	/// Test that 32-bit frames and missing frames are not rendered.
+	#[test]
+	fn composite_missing_frames() {
+		let palette = Palette::new(vec![[0, 0, 0, 255]]);
+		let pixel = RGBAPixel::new(SLPPixelType::Palette, 1, 2, 3, 255);
+		let slp = SLPFile {
+			header: SLPHeader::new(*b"3.0\0", 1, [0; 24]),
+			frame_infos: vec![SLPFrameInfo::new(
+				0,
+				0,
+				0,
+				0x07,
+				1,
+				1,
+				0,
+				0,
+				SLPFrameType::Main,
+				*b"3.0\0",
+			)],
+			frames: Vec::new(),
+			rgba_frames: vec![SLPFrame::new(Vec::new(), Vec::new(), vec![vec![pixel]])],
+			shadow_frame_infos: Vec::new(),
+			shadow_frames: Vec::new(),
+		};
+
+		let options = CompositorOptions::default();
+		assert!(composite_slp_frame(&slp, 0, &palette, options).is_none());
+		assert!(composite_slp_frame(&slp, 1, &palette, options).is_none());
+	}
+}
@@ -0,0 +1,182 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::palette::definitions::PALETTE_MAX_COLORS;
+use crate::palette::palette::Palette;
+use crate::render::compositor::composite_slp_frame;
+use crate::render::compositor::CompositorOptions;
+use crate::render::compositor::RenderedFrame;
+use crate::slp::slp::SLPFile;
+
+/// Brightness factor the game uses for explored but not visible objects.
+pub const FOG_DARKENING_FACTOR: f32 = 0.5;
+
+/// Mapping from palette indices to the indices of their darkened colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DarkeningTable {
+	/// Darkened palette index, indexed by palette index.
+	mapping: [u8; PALETTE_MAX_COLORS],
+}
+
+impl DarkeningTable {
+	/// Create a darkening table from an explicit mapping.
+	///
+	/// # Arguments
+	///
+	/// * `mapping` - Darkened palette index, indexed by palette index.
+	///
+	/// # Returns
+	///
+	/// New darkening table.
+	pub const fn new(mapping: [u8; PALETTE_MAX_COLORS]) -> Self {
+		Self { mapping }
+	}
+
+	/// Create a darkening table by scaling the palette colors.
+	///
+	/// Every color is multiplied by the factor and mapped to the palette
+	/// color closest to the result.
+	///
+	/// # Arguments
+	///
+	/// * `palette` - Palette to darken.
+	/// * `factor` - Brightness factor between 0.0 (black) and 1.0 (unchanged).
+	///
+	/// # Returns
+	///
+	/// New darkening table.
+	#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+	pub fn from_factor(
+		palette: &Palette,
+		factor: f32,
+	) -> Self {
+		let factor = factor.clamp(0.0, 1.0);
+		let mut mapping = [0; PALETTE_MAX_COLORS];
+
+		for (i, color) in palette.get_colors().iter().enumerate() {
+			let target = [
+				(f32::from(color[0]) * factor).round() as u8,
+				(f32::from(color[1]) * factor).round() as u8,
+				(f32::from(color[2]) * factor).round() as u8,
+			];
+			mapping[i] = palette.find_closest_color(target);
+		}
+
+		// indices outside the palette are kept as they are
+		for (i, index) in mapping
+			.iter_mut()
+			.enumerate()
+			.skip(palette.get_num_colors())
+		{
+			// the mapping has at most 256 entries
+			*index = i as u8;
+		}
+
+		return Self { mapping };
+	}
+
+	/// Get the darkened palette index for a palette index.
+	///
+	/// # Arguments
+	///
+	/// * `index` - Palette index.
+	///
+	/// # Returns
+	///
+	/// Darkened palette index.
+	pub const fn get_index(
+		&self,
+		index: u8,
+	) -> u8 {
+		return self.mapping[index as usize];
+	}
+
+	/// Create a copy of a palette where every index has its darkened color.
+	///
+	/// The original palette is not changed.
+	///
+	/// # Arguments
+	///
+	/// * `palette` - Palette to darken.
+	///
+	/// # Returns
+	///
+	/// Darkened palette.
+	pub fn apply(
+		&self,
+		palette: &Palette,
+	) -> Palette {
+		let colors = palette.get_colors();
+		let darkened = colors
+			.iter()
+			.enumerate()
+			.map(|(i, color)| {
+				let index = self.mapping[i] as usize;
+				colors.get(index).copied().unwrap_or(*color)
+			})
+			.collect::<Vec<[u8; 4]>>();
+
+		return Palette::new(darkened);
+	}
+}
+
+/// Render a frame as it is drawn under the fog of war.
+///
+/// The frame is composited with a palette darkened by the table, so all
+/// palette, player and outline colors are darkened. Shadow opacity is not
+/// changed.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file containing the frame.
+/// * `index` - Index of the frame.
+/// * `palette` - Palette used by the frames.
+/// * `table` - Darkening table for the palette.
+/// * `options` - Compositor options.
+///
+/// # Returns
+///
+/// Darkened rendered frame or `None` if the index is out of range or the
+/// file has 32-bit frames.
+///
+/// # Panics
+///
+/// Panics if the frames are too large for an image.
+pub fn render_fogged(
+	slp: &SLPFile,
+	index: usize,
+	palette: &Palette,
+	table: &DarkeningTable,
+	options: CompositorOptions,
+) -> Option<RenderedFrame> {
+	let darkened = table.apply(palette);
+
+	return composite_slp_frame(slp, index, &darkened, options);
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::palette::palette::Palette;
+	use crate::render::fog::DarkeningTable;
+
+	/// Test that darkening maps colors to the closest darker palette color.
+	#[test]
+	fn darken_palette() {
+		let palette = Palette::new(vec![
+			[0, 0, 0, 255],
+			[100, 100, 100, 255],
+			[200, 200, 200, 255],
+			[200, 0, 0, 255],
+		]);
+		let table = DarkeningTable::from_factor(&palette, 0.5);
+
+		assert_eq!(table.get_index(0), 0);
+		assert_eq!(table.get_index(1), 0);
+		assert_eq!(table.get_index(2), 1);
+		assert_eq!(table.get_index(3), 0);
+		assert_eq!(table.get_index(10), 10);
+
+		let darkened = table.apply(&palette);
+		assert_eq!(darkened.get_color(2), Some([100, 100, 100, 255]));
+		assert_eq!(palette.get_color(2), Some([200, 200, 200, 255]));
+	}
+}
@@ -0,0 +1,6 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+pub mod compositor;
+pub mod fog;
+pub mod occlusion;
+pub mod terrain;
@@ -0,0 +1,147 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::palette::palette::Palette;
+use crate::render::compositor::composite_slp_frame;
+use crate::render::compositor::CompositorOptions;
+use crate::render::compositor::RenderLayers;
+use crate::render::compositor::RenderedFrame;
+use crate::slp::slp::SLPFile;
+
+/// Render a frame as it is drawn when it is hidden behind another object.
+///
+/// Only the outline pixels are drawn. Both `Special1` and `Special2` pixels
+/// use the player's outline color.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file containing the frame.
+/// * `index` - Index of the frame.
+/// * `palette` - Palette used by the frames.
+/// * `player` - Player number used for the outline color.
+///
+/// # Returns
+///
+/// Rendered outline of the frame or `None` if the index is out of range or
+/// the file has 32-bit frames.
+///
+/// # Panics
+///
+/// Panics if the frames are too large for an image.
+pub fn render_occluded(
+	slp: &SLPFile,
+	index: usize,
+	palette: &Palette,
+	player: u8,
+) -> Option<RenderedFrame> {
+	let options = CompositorOptions {
+		layers: RenderLayers::OCCLUDED,
+		player,
+		occluded: true,
+		..CompositorOptions::default()
+	};
+
+	return composite_slp_frame(slp, index, palette, options);
+}
+
+/// Render a frame that is partially hidden behind another object.
+///
+/// Where the occluder covers the frame, only the outline is drawn. All other
+/// pixels are rendered with the given compositor options. The occluder
+/// itself is not drawn.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file containing the frame.
+/// * `index` - Index of the frame.
+/// * `palette` - Palette used by the frames.
+/// * `options` - Compositor options for the visible part of the frame.
+/// * `occluder` - Mask of the occluding object with the same size as the
+///   rendered frame. Pixels with a non-zero value are covered.
+///
+/// # Returns
+///
+/// Rendered frame or `None` if the index is out of range or the file has
+/// 32-bit frames.
+///
+/// # Panics
+///
+/// Panics if the occluder mask does not have the size of the rendered frame.
+pub fn render_behind(
+	slp: &SLPFile,
+	index: usize,
+	palette: &Palette,
+	options: CompositorOptions,
+	occluder: &image::GrayImage,
+) -> Option<RenderedFrame> {
+	let mut visible = composite_slp_frame(slp, index, palette, options)?;
+	let outline = render_occluded(slp, index, palette, options.player)?;
+
+	assert!(
+		occluder.dimensions() == visible.image.dimensions(),
+		"Occluder size {:?} does not match frame size {:?}",
+		occluder.dimensions(),
+		visible.image.dimensions()
+	);
+
+	for (x, y, pixel) in visible.image.enumerate_pixels_mut() {
+		if occluder.get_pixel(x, y)[0] != 0 {
+			*pixel = *outline.image.get_pixel(x, y);
+		}
+	}
+
+	return Some(visible);
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::palette::palette::Palette;
+	use crate::render::compositor::CompositorOptions;
+	use crate::render::occlusion::render_behind;
+	use crate::render::occlusion::render_occluded;
+	use crate::slp::frame::SLPFrame;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::SLPPixelType;
+	use crate::slp::slp::SLPFile;
+
+	/// Test that occluded outline pixels are drawn in the player color.
+	#[test]
+	fn occluded_outline_color() {
+		let frame = SLPFrame::new(
+			Vec::new(),
+			Vec::new(),
+			vec![vec![
+				PalettePixel::new(SLPPixelType::Palette, 1),
+				PalettePixel::new(SLPPixelType::Special1, 0),
+				PalettePixel::new(SLPPixelType::Special2, 0),
+			]],
+		);
+		let slp = SLPFile::from_palette_frames(vec![frame]);
+		let mut colors = vec![[0, 0, 0, 255]; 48];
+		colors[1] = [9, 9, 9, 255];
+		colors[32] = [200, 0, 0, 255];
+		let palette = Palette::new(colors);
+
+		let rendered = render_occluded(&slp, 0, &palette, 2).unwrap();
+		let pixels = rendered
+			.image
+			.pixels()
+			.map(|pixel| pixel.0)
+			.collect::<Vec<_>>();
+		assert_eq!(pixels, [[0, 0, 0, 0], [200, 0, 0, 255], [200, 0, 0, 255]]);
+
+		// only the covered part of the frame shows the outline
+		let mut occluder = image::GrayImage::new(3, 1);
+		occluder.put_pixel(2, 0, image::Luma([255]));
+		let options = CompositorOptions {
+			player: 2,
+			..CompositorOptions::default()
+		};
+		let rendered = render_behind(&slp, 0, &palette, options, &occluder).unwrap();
+		let pixels = rendered
+			.image
+			.pixels()
+			.map(|pixel| pixel.0)
+			.collect::<Vec<_>>();
+		assert_eq!(pixels, [[9, 9, 9, 255], [0, 0, 0, 0], [200, 0, 0, 255]]);
+	}
+}
@@ -0,0 +1,154 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::palette::palette::Palette;
+use crate::render::compositor::composite_slp_frame;
+use crate::render::compositor::CompositorOptions;
+use crate::slp::slp::SLPFile;
+
+/// De-project an isometric diamond tile into a flat square texture.
+///
+/// The top corner of the diamond becomes the top left corner of the square.
+/// The upper right edge of the diamond, from the top to the right corner,
+/// runs along the x axis of the square and the upper left edge, from the top
+/// to the left corner, along the y axis. Each texture pixel samples the
+/// nearest pixel of the diamond.
+///
+/// # Arguments
+///
+/// * `tile` - Image of the diamond tile. The diamond touches all four image
+///   borders.
+/// * `tile_size` - Width and height of the flat texture.
+///
+/// # Returns
+///
+/// Flat texture of the tile.
+pub fn deproject_tile(
+	tile: &image::RgbaImage,
+	tile_size: u32,
+) -> image::RgbaImage {
+	let (width, height) = tile.dimensions();
+	let half_width = f64::from(width) / 2.0;
+	let half_height = f64::from(height) / 2.0;
+	let size = f64::from(tile_size);
+
+	return image::RgbaImage::from_fn(tile_size, tile_size, |x, y| {
+		let u = (f64::from(x) + 0.5) / size;
+		let v = (f64::from(y) + 0.5) / size;
+		let iso_x = half_width * (1.0 + u - v);
+		let iso_y = half_height * (u + v);
+
+		// sample coordinates are non-negative and clamped to the image
+		#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+		let (sample_x, sample_y) = (
+			(iso_x as u32).min(width - 1),
+			(iso_y as u32).min(height - 1),
+		);
+
+		return *tile.get_pixel(sample_x, sample_y);
+	});
+}
+
+/// Convert a terrain frame into a flat square texture.
+///
+/// # Arguments
+///
+/// * `slp` - Terrain SLP file.
+/// * `index` - Index of the frame.
+/// * `palette` - Palette used by the frames.
+/// * `tile_size` - Width and height of the flat texture.
+///
+/// # Returns
+///
+/// Flat texture of the frame or `None` if the index is out of range or the
+/// file has 32-bit frames.
+///
+/// # Panics
+///
+/// Panics if the frame is too large for an image.
+pub fn flatten_terrain_frame(
+	slp: &SLPFile,
+	index: usize,
+	palette: &Palette,
+	tile_size: u32,
+) -> Option<image::RgbaImage> {
+	let rendered = composite_slp_frame(slp, index, palette, CompositorOptions::default())?;
+
+	return Some(deproject_tile(&rendered.image, tile_size));
+}
+
+/// Convert all frames of a terrain into one seamless flat texture.
+///
+/// Terrain frames form a square grid of tiles that repeat across the map.
+/// Frames are stored row by row, so frame `y * n + x` covers map tile
+/// `(x mod n, y mod n)` for a grid of `n` by `n` frames. The flattened
+/// frames are placed at the same grid positions, so the texture tiles
+/// seamlessly.
+///
+/// # Arguments
+///
+/// * `slp` - Terrain SLP file.
+/// * `palette` - Palette used by the frames.
+/// * `tile_size` - Width and height of a flattened frame.
+///
+/// # Returns
+///
+/// Flat texture of the terrain or `None` if the file has 32-bit frames.
+///
+/// # Panics
+///
+/// Panics if the number of frames is not a square number.
+pub fn flatten_terrain(
+	slp: &SLPFile,
+	palette: &Palette,
+	tile_size: u32,
+) -> Option<image::RgbaImage> {
+	if !slp.rgba_frames.is_empty() {
+		return None;
+	}
+
+	let num_frames = slp.frames.len();
+	let grid_size = num_frames.isqrt();
+	assert!(
+		grid_size * grid_size == num_frames,
+		"Terrain frames do not form a square grid: {num_frames} frames"
+	);
+
+	let grid_pixels = u32::try_from(grid_size).unwrap() * tile_size;
+	let mut texture = image::RgbaImage::new(grid_pixels, grid_pixels);
+	for index in 0..num_frames {
+		let tile = flatten_terrain_frame(slp, index, palette, tile_size)?;
+		let x = u32::try_from(index % grid_size).unwrap() * tile_size;
+		let y = u32::try_from(index / grid_size).unwrap() * tile_size;
+		image::imageops::replace(&mut texture, &tile, i64::from(x), i64::from(y));
+	}
+
+	return Some(texture);
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::render::terrain::deproject_tile;
+
+	/// Test that the diamond halves map to the triangles of the square.
+	#[test]
+	fn deproject_diamond() {
+		let red = image::Rgba([255, 0, 0, 255]);
+		let blue = image::Rgba([0, 0, 255, 255]);
+		let tile = image::RgbaImage::from_fn(9, 5, |x, _| {
+			if x < 4 {
+				red
+			}
+			else {
+				blue
+			}
+		});
+
+		let flat = deproject_tile(&tile, 8);
+		assert_eq!(flat.dimensions(), (8, 8));
+		// right half of the diamond lies above the diagonal
+		assert_eq!(*flat.get_pixel(6, 1), blue);
+		assert_eq!(*flat.get_pixel(1, 6), red);
+		assert_eq!(*flat.get_pixel(7, 7), blue);
+		assert_eq!(*flat.get_pixel(0, 7), red);
+	}
+}
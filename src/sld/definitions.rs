@@ -0,0 +1,44 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+/// Signature at the start of SLD files.
+pub const SLD_SIGNATURE: &[u8; 4] = b"SLDX";
+
+/// Size of the SLD header.
+pub const SLD_HEADER_SIZE: usize = 16;
+
+/// Size of a frame header.
+pub const SLD_FRAME_HEADER_SIZE: usize = 12;
+
+/// Size of the layer content length field.
+pub const SLD_LAYER_LENGTH_SIZE: usize = 4;
+
+/// Size of a layer header with bounds.
+pub const SLD_LAYER_HEADER_SIZE: usize = 10;
+
+/// Size of a layer header without bounds.
+pub const SLD_LAYER_HEADER_NO_BOUNDS_SIZE: usize = 2;
+
+/// Size of a block command.
+pub const SLD_BLOCK_CMD_SIZE: usize = 2;
+
+/// Layers are padded to a multiple of this size.
+pub const SLD_LAYER_ALIGNMENT: usize = 4;
+
+/// Frame type flag for frames with a main graphics layer.
+pub const SLD_LAYER_MAIN: u8 = 0x01;
+
+/// Frame type flag for frames with a shadow layer.
+pub const SLD_LAYER_SHADOW: u8 = 0x02;
+
+/// Frame type flag for frames with an unknown (tile mask) layer.
+pub const SLD_LAYER_UNKNOWN: u8 = 0x04;
+
+/// Frame type flag for frames with a damage mask layer.
+pub const SLD_LAYER_DAMAGE: u8 = 0x08;
+
+/// Frame type flag for frames with a player color layer.
+pub const SLD_LAYER_PLAYER_COLOR: u8 = 0x10;
+
+/// Layer flag for layers that draw skipped blocks with the blocks of the
+/// previous frame.
+pub const SLD_LAYER_REUSE_PREVIOUS: u8 = 0x80;
@@ -0,0 +1,135 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::fmt;
+
+use crate::sld::definitions::SLD_FRAME_HEADER_SIZE;
+use crate::sld::definitions::SLD_HEADER_SIZE;
+use crate::sld::layer::SLDLayerType;
+use crate::slp::unpack::UnpackFixedSize;
+
+/// Header in an SLD file.
+#[derive(Debug, Clone, Copy)]
+pub struct SLDHeader {
+	/// File signature, always `SLDX`.
+	pub signature: [u8; 4],
+	/// Format version.
+	pub version: u16,
+	/// Number of frames.
+	pub num_frames: u16,
+	/// Unknown value, always 0.
+	pub unknown1: u16,
+	/// Unknown value, always 0x10.
+	pub unknown2: u16,
+	/// Unknown value, always 0xFF000000.
+	pub unknown3: u32,
+}
+
+impl UnpackFixedSize for SLDHeader {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + SLD_HEADER_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		return Self {
+			signature: bytes[0..4].try_into().unwrap(),
+			version: u16::from_le_bytes([bytes[4], bytes[5]]),
+			num_frames: u16::from_le_bytes([bytes[6], bytes[7]]),
+			unknown1: u16::from_le_bytes([bytes[8], bytes[9]]),
+			unknown2: u16::from_le_bytes([bytes[10], bytes[11]]),
+			unknown3: u32::from_le_bytes(bytes[12..16].try_into().unwrap()),
+		};
+	}
+}
+
+impl fmt::Display for SLDHeader {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"version: {}\nnum_frames: {}",
+			self.version, self.num_frames
+		)
+	}
+}
+
+/// Header of a frame in an SLD file.
+#[derive(Debug, Clone, Copy)]
+pub struct SLDFrameHeader {
+	/// Width of the frame canvas.
+	pub canvas_width: u16,
+	/// Height of the frame canvas.
+	pub canvas_height: u16,
+	/// X coordinate of the anchor point on the canvas.
+	pub anchor_x: u16,
+	/// Y coordinate of the anchor point on the canvas.
+	pub anchor_y: u16,
+	/// Flags of the layers present in the frame.
+	pub frame_type: u8,
+	/// Unknown value.
+	pub unknown: u8,
+	/// Index of the frame.
+	pub frame_index: u16,
+}
+
+impl SLDFrameHeader {
+	/// Check if the frame has a layer.
+	///
+	/// # Arguments
+	///
+	/// * `layer_type` - Type of the layer.
+	///
+	/// # Returns
+	///
+	/// true if the layer is present, else false.
+	pub const fn has_layer(
+		&self,
+		layer_type: SLDLayerType,
+	) -> bool {
+		return self.frame_type & layer_type.get_flag() != 0;
+	}
+}
+
+impl UnpackFixedSize for SLDFrameHeader {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + SLD_FRAME_HEADER_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		return Self {
+			canvas_width: u16::from_le_bytes([bytes[0], bytes[1]]),
+			canvas_height: u16::from_le_bytes([bytes[2], bytes[3]]),
+			anchor_x: u16::from_le_bytes([bytes[4], bytes[5]]),
+			anchor_y: u16::from_le_bytes([bytes[6], bytes[7]]),
+			frame_type: bytes[8],
+			unknown: bytes[9],
+			frame_index: u16::from_le_bytes([bytes[10], bytes[11]]),
+		};
+	}
+}
+
+impl fmt::Display for SLDFrameHeader {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"canvas_width: {}\ncanvas_height: {}\nanchor_x: {}\nanchor_y: {}\nframe_type: \
+			 {:#x}\nframe_index: {}",
+			self.canvas_width,
+			self.canvas_height,
+			self.anchor_x,
+			self.anchor_y,
+			self.frame_type,
+			self.frame_index
+		)
+	}
+}
@@ -0,0 +1,156 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::fmt;
+
+use crate::sld::definitions::SLD_LAYER_DAMAGE;
+use crate::sld::definitions::SLD_LAYER_MAIN;
+use crate::sld::definitions::SLD_LAYER_PLAYER_COLOR;
+use crate::sld::definitions::SLD_LAYER_REUSE_PREVIOUS;
+use crate::sld::definitions::SLD_LAYER_SHADOW;
+use crate::sld::definitions::SLD_LAYER_UNKNOWN;
+use crate::util::bcn::BC_BLOCK_DIM;
+
+/// Type of a layer in an SLD frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SLDLayerType {
+	/// Main graphics, BC1 compressed.
+	Main,
+	/// Shadow intensities, BC4 compressed.
+	Shadow,
+	/// Unknown layer (tile mask), BC4 compressed.
+	Unknown,
+	/// Damage mask, BC1 compressed.
+	Damage,
+	/// Player color mask, BC4 compressed.
+	PlayerColor,
+}
+
+impl SLDLayerType {
+	/// All layer types in the order they are stored in a frame.
+	pub const ALL: [Self; 5] = [
+		Self::Main,
+		Self::Shadow,
+		Self::Unknown,
+		Self::Damage,
+		Self::PlayerColor,
+	];
+
+	/// Get the frame type flag of the layer.
+	///
+	/// # Returns
+	///
+	/// Frame type flag.
+	pub const fn get_flag(self) -> u8 {
+		match self {
+			Self::Main => SLD_LAYER_MAIN,
+			Self::Shadow => SLD_LAYER_SHADOW,
+			Self::Unknown => SLD_LAYER_UNKNOWN,
+			Self::Damage => SLD_LAYER_DAMAGE,
+			Self::PlayerColor => SLD_LAYER_PLAYER_COLOR,
+		}
+	}
+
+	/// Check if the layer stores its own bounds.
+	///
+	/// Damage and player color layers use the bounds of the main layer.
+	///
+	/// # Returns
+	///
+	/// true if the layer header contains bounds, else false.
+	pub const fn has_bounds(self) -> bool {
+		return matches!(self, Self::Main | Self::Shadow | Self::Unknown);
+	}
+
+	/// Check if the layer uses BC1 compression.
+	///
+	/// # Returns
+	///
+	/// true for BC1 (RGBA) layers, false for BC4 (single channel) layers.
+	pub const fn is_bc1(self) -> bool {
+		return matches!(self, Self::Main | Self::Damage);
+	}
+}
+
+/// Header of a layer in an SLD frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SLDLayerHeader {
+	/// Left edge of the layer area on the canvas.
+	pub offset_x1: u16,
+	/// Top edge of the layer area on the canvas.
+	pub offset_y1: u16,
+	/// Right edge of the layer area on the canvas.
+	pub offset_x2: u16,
+	/// Bottom edge of the layer area on the canvas.
+	pub offset_y2: u16,
+	/// Layer flags.
+	pub flags: u8,
+	/// Unknown value.
+	pub unknown: u8,
+}
+
+impl SLDLayerHeader {
+	/// Check if skipped blocks are drawn with the blocks of the previous
+	/// frame.
+	///
+	/// # Returns
+	///
+	/// true if blocks are reused, else false.
+	pub const fn reuses_previous(&self) -> bool {
+		return self.flags & SLD_LAYER_REUSE_PREVIOUS != 0;
+	}
+
+	/// Get the number of blocks in a row of the layer area.
+	///
+	/// # Returns
+	///
+	/// Number of blocks.
+	pub const fn get_blocks_width(&self) -> usize {
+		return (self.offset_x2.saturating_sub(self.offset_x1) as usize).div_ceil(BC_BLOCK_DIM);
+	}
+
+	/// Get the number of block rows in the layer area.
+	///
+	/// # Returns
+	///
+	/// Number of block rows.
+	pub const fn get_blocks_height(&self) -> usize {
+		return (self.offset_y2.saturating_sub(self.offset_y1) as usize).div_ceil(BC_BLOCK_DIM);
+	}
+}
+
+impl fmt::Display for SLDLayerHeader {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"offset_x1: {}\noffset_y1: {}\noffset_x2: {}\noffset_y2: {}\nflags: {:#x}",
+			self.offset_x1, self.offset_y1, self.offset_x2, self.offset_y2, self.flags
+		)
+	}
+}
+
+/// Decoded image of an SLD layer.
+#[derive(Debug, Clone)]
+pub enum SLDLayerImage {
+	/// Image of a BC1 layer.
+	Rgba(image::RgbaImage),
+	/// Image of a BC4 layer.
+	Gray(image::GrayImage),
+}
+
+/// Layer of an SLD frame.
+#[derive(Debug, Clone)]
+pub struct SLDLayer {
+	/// Type of the layer.
+	pub layer_type: SLDLayerType,
+	/// Layer header. Layers without own bounds get the bounds of the main
+	/// layer.
+	pub header: SLDLayerHeader,
+	/// Compressed blocks of the layer area in row-major order, including
+	/// blocks reused from the previous frame. Skipped blocks are `None`.
+	pub blocks: Vec<Option<[u8; 8]>>,
+	/// Decoded layer with the size of the frame canvas.
+	pub image: SLDLayerImage,
+}
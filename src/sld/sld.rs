@@ -0,0 +1,449 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::io;
+
+use crate::sld::definitions::SLD_BLOCK_CMD_SIZE;
+use crate::sld::definitions::SLD_FRAME_HEADER_SIZE;
+use crate::sld::definitions::SLD_HEADER_SIZE;
+use crate::sld::definitions::SLD_LAYER_ALIGNMENT;
+use crate::sld::definitions::SLD_LAYER_HEADER_NO_BOUNDS_SIZE;
+use crate::sld::definitions::SLD_LAYER_HEADER_SIZE;
+use crate::sld::definitions::SLD_LAYER_LENGTH_SIZE;
+use crate::sld::definitions::SLD_SIGNATURE;
+use crate::sld::header::SLDFrameHeader;
+use crate::sld::header::SLDHeader;
+use crate::sld::layer::SLDLayer;
+use crate::sld::layer::SLDLayerHeader;
+use crate::sld::layer::SLDLayerImage;
+use crate::sld::layer::SLDLayerType;
+use crate::slp::unpack::UnpackFixedSize;
+use crate::util::bcn::decode_bc1_block;
+use crate::util::bcn::decode_bc4_block;
+use crate::util::bcn::BC1_BLOCK_SIZE;
+use crate::util::bcn::BC_BLOCK_DIM;
+
+/// Frame in an SLD file, consisting of one or more layers.
+#[derive(Debug, Clone)]
+pub struct SLDFrame {
+	/// Frame header with the canvas size, anchor and layer flags.
+	pub header: SLDFrameHeader,
+	/// Layers of the frame in storage order.
+	pub layers: Vec<SLDLayer>,
+}
+
+impl SLDFrame {
+	/// Get a layer of the frame.
+	///
+	/// # Arguments
+	///
+	/// * `layer_type` - Type of the layer.
+	///
+	/// # Returns
+	///
+	/// Layer or `None` if the frame has no layer of the type.
+	pub fn get_layer(
+		&self,
+		layer_type: SLDLayerType,
+	) -> Option<&SLDLayer> {
+		return self
+			.layers
+			.iter()
+			.find(|layer| layer.layer_type == layer_type);
+	}
+}
+
+/// SLD file.
+#[derive(Debug, Clone)]
+pub struct SLDFile {
+	/// SLD header.
+	pub header: SLDHeader,
+	/// Layered frames.
+	pub frames: Vec<SLDFrame>,
+}
+
+/// Get the block of the previous frame's layer at a canvas position.
+///
+/// # Arguments
+///
+/// * `previous` - Layer of the previous frame.
+/// * `x` - X coordinate of the block on the canvas.
+/// * `y` - Y coordinate of the block on the canvas.
+///
+/// # Returns
+///
+/// Block or `None` if the previous layer has no block at the position.
+fn get_previous_block(
+	previous: &SLDLayer,
+	x: usize,
+	y: usize,
+) -> Option<[u8; 8]> {
+	let x1 = previous.header.offset_x1 as usize;
+	let y1 = previous.header.offset_y1 as usize;
+	if x < x1
+		|| y < y1
+		|| !(x - x1).is_multiple_of(BC_BLOCK_DIM)
+		|| !(y - y1).is_multiple_of(BC_BLOCK_DIM)
+	{
+		return None;
+	}
+
+	let bx = (x - x1) / BC_BLOCK_DIM;
+	let by = (y - y1) / BC_BLOCK_DIM;
+	if bx >= previous.header.get_blocks_width() {
+		return None;
+	}
+
+	return previous
+		.blocks
+		.get(by * previous.header.get_blocks_width() + bx)
+		.copied()
+		.flatten();
+}
+
+/// Decode the blocks of a layer to a canvas sized image.
+///
+/// # Arguments
+///
+/// * `layer_type` - Type of the layer.
+/// * `header` - Layer header.
+/// * `blocks` - Compressed blocks of the layer area.
+/// * `frame_header` - Header of the frame.
+///
+/// # Returns
+///
+/// Decoded image.
+fn decode_image(
+	layer_type: SLDLayerType,
+	header: &SLDLayerHeader,
+	blocks: &[Option<[u8; 8]>],
+	frame_header: &SLDFrameHeader,
+) -> SLDLayerImage {
+	let width = u32::from(frame_header.canvas_width);
+	let height = u32::from(frame_header.canvas_height);
+	let blocks_width = header.get_blocks_width().max(1);
+
+	let positions = blocks.iter().enumerate().filter_map(|(i, block)| {
+		let x = header.offset_x1 as usize + (i % blocks_width) * BC_BLOCK_DIM;
+		let y = header.offset_y1 as usize + (i / blocks_width) * BC_BLOCK_DIM;
+		block.map(|block| (x, y, block))
+	});
+	let pixels = |x: usize, y: usize| {
+		(0..BC_BLOCK_DIM * BC_BLOCK_DIM).filter_map(move |i| {
+			let px = u32::try_from(x + i % BC_BLOCK_DIM).ok()?;
+			let py = u32::try_from(y + i / BC_BLOCK_DIM).ok()?;
+			(px < width && py < height).then_some((i, px, py))
+		})
+	};
+
+	if layer_type.is_bc1() {
+		let mut image = image::RgbaImage::new(width, height);
+		for (x, y, block) in positions {
+			let colors = decode_bc1_block(&block);
+			for (i, px, py) in pixels(x, y) {
+				image.put_pixel(px, py, image::Rgba(colors[i]));
+			}
+		}
+		return SLDLayerImage::Rgba(image);
+	}
+
+	let mut image = image::GrayImage::new(width, height);
+	for (x, y, block) in positions {
+		let values = decode_bc4_block(&block);
+		for (i, px, py) in pixels(x, y) {
+			image.put_pixel(px, py, image::Luma([values[i]]));
+		}
+	}
+	return SLDLayerImage::Gray(image);
+}
+
+/// Decode a layer of an SLD frame.
+///
+/// The layer starts with its content length, followed by the layer header
+/// and the block commands. Every command is a pair of the number of skipped
+/// blocks and the number of drawn blocks. The drawn blocks follow the
+/// commands.
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer to read from.
+/// * `offset` - Offset of the layer.
+/// * `layer_type` - Type of the layer.
+/// * `frame_header` - Header of the frame.
+/// * `main_header` - Header of the main layer of the frame, used by layers
+///   without own bounds.
+/// * `previous` - Layer of the same type in the previous frame.
+///
+/// # Returns
+///
+/// Decoded layer and the offset of the next layer.
+///
+/// # Panics
+///
+/// Panics if a layer without own bounds appears without a main layer.
+pub fn decode_layer(
+	buffer: &[u8],
+	offset: usize,
+	layer_type: SLDLayerType,
+	frame_header: &SLDFrameHeader,
+	main_header: Option<&SLDLayerHeader>,
+	previous: Option<&SLDLayer>,
+) -> (SLDLayer, usize) {
+	let content_length = u32::from_le_bytes(
+		buffer[offset..offset + SLD_LAYER_LENGTH_SIZE]
+			.try_into()
+			.unwrap(),
+	) as usize;
+	let mut pos = offset + SLD_LAYER_LENGTH_SIZE;
+
+	let read_u16 = |pos: usize| u16::from_le_bytes([buffer[pos], buffer[pos + 1]]);
+	let header = if layer_type.has_bounds() {
+		let header = SLDLayerHeader {
+			offset_x1: read_u16(pos),
+			offset_y1: read_u16(pos + 2),
+			offset_x2: read_u16(pos + 4),
+			offset_y2: read_u16(pos + 6),
+			flags: buffer[pos + 8],
+			unknown: buffer[pos + 9],
+		};
+		pos += SLD_LAYER_HEADER_SIZE;
+		header
+	}
+	else {
+		let main_header =
+			main_header.unwrap_or_else(|| panic!("SLD {layer_type:?} layer requires a main layer"));
+		let header = SLDLayerHeader {
+			flags: buffer[pos],
+			unknown: buffer[pos + 1],
+			..*main_header
+		};
+		pos += SLD_LAYER_HEADER_NO_BOUNDS_SIZE;
+		header
+	};
+
+	let num_cmds = read_u16(pos) as usize;
+	pos += 2;
+	let cmds = &buffer[pos..pos + num_cmds * SLD_BLOCK_CMD_SIZE];
+	pos += num_cmds * SLD_BLOCK_CMD_SIZE;
+
+	let blocks_width = header.get_blocks_width();
+	let num_blocks = blocks_width * header.get_blocks_height();
+	let reuse = previous.filter(|_| header.reuses_previous());
+
+	let mut blocks = Vec::<Option<[u8; 8]>>::with_capacity(num_blocks);
+	for cmd in cmds.chunks_exact(SLD_BLOCK_CMD_SIZE) {
+		for _ in 0..cmd[0] {
+			let block = reuse.and_then(|previous| {
+				let i = blocks.len();
+				let x = header.offset_x1 as usize + (i % blocks_width) * BC_BLOCK_DIM;
+				let y = header.offset_y1 as usize + (i / blocks_width) * BC_BLOCK_DIM;
+				get_previous_block(previous, x, y)
+			});
+			blocks.push(block);
+		}
+		for _ in 0..cmd[1] {
+			blocks.push(Some(buffer[pos..pos + BC1_BLOCK_SIZE].try_into().unwrap()));
+			pos += BC1_BLOCK_SIZE;
+		}
+	}
+	blocks.resize(num_blocks, None);
+
+	let image = decode_image(layer_type, &header, &blocks, frame_header);
+	let layer = SLDLayer {
+		layer_type,
+		header,
+		blocks,
+		image,
+	};
+
+	let next = offset + content_length.next_multiple_of(SLD_LAYER_ALIGNMENT);
+	return (layer, next);
+}
+
+/// Decode a frame with all of its layers.
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer to read from.
+/// * `offset` - Offset of the frame header.
+/// * `previous` - Previous frame in the file.
+///
+/// # Returns
+///
+/// Decoded frame and the offset of the next frame.
+pub fn decode_frame(
+	buffer: &[u8],
+	offset: usize,
+	previous: Option<&SLDFrame>,
+) -> (SLDFrame, usize) {
+	let header = SLDFrameHeader::from_buffer(buffer, offset);
+	let mut pos = offset + SLD_FRAME_HEADER_SIZE;
+
+	let mut layers = Vec::<SLDLayer>::new();
+	for layer_type in SLDLayerType::ALL {
+		if !header.has_layer(layer_type) {
+			continue;
+		}
+
+		let main_header = layers
+			.iter()
+			.find(|layer| layer.layer_type == SLDLayerType::Main)
+			.map(|layer| &layer.header);
+		let previous_layer = previous.and_then(|frame| frame.get_layer(layer_type));
+
+		let (layer, next) = decode_layer(
+			buffer,
+			pos,
+			layer_type,
+			&header,
+			main_header,
+			previous_layer,
+		);
+		layers.push(layer);
+		pos = next;
+	}
+
+	return (SLDFrame { header, layers }, pos);
+}
+
+/// Parse an SLD file.
+///
+/// Frames are stored one after another directly behind the header.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the SLD file.
+///
+/// # Returns
+///
+/// The parsed SLD file.
+///
+/// # Errors
+///
+/// Returns an error if the file does not start with the SLD signature.
+///
+/// # Panics
+///
+/// Panics if the frame data is truncated.
+pub fn parse_sld(bytes: &[u8]) -> io::Result<SLDFile> {
+	if bytes.len() < SLD_HEADER_SIZE || &bytes[0..4] != SLD_SIGNATURE {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"File is not an SLD file",
+		));
+	}
+	let header = SLDHeader::from_buffer(bytes, 0);
+
+	let mut frames = Vec::<SLDFrame>::with_capacity(header.num_frames as usize);
+	let mut offset = SLD_HEADER_SIZE;
+	for _ in 0..header.num_frames {
+		let (frame, next) = decode_frame(bytes, offset, frames.last());
+		frames.push(frame);
+		offset = next;
+	}
+
+	return Ok(SLDFile { header, frames });
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::sld::layer::SLDLayerImage;
+	use crate::sld::layer::SLDLayerType;
+	use crate::sld::sld::parse_sld;
+
+	/// Build a frame header.
+	fn frame_header(frame_type: u8) -> Vec<u8> {
+		let mut bytes = Vec::<u8>::new();
+		for value in [8u16, 4, 4, 3] {
+			bytes.extend_from_slice(&value.to_le_bytes());
+		}
+		bytes.extend_from_slice(&[frame_type, 0, 0, 0]);
+		return bytes;
+	}
+
+	/// Build a layer from its header, commands and blocks.
+	fn layer(
+		header: &[u8],
+		cmds: &[[u8; 2]],
+		blocks: &[[u8; 8]],
+	) -> Vec<u8> {
+		let mut content = header.to_vec();
+		content.extend_from_slice(&u16::try_from(cmds.len()).unwrap().to_le_bytes());
+		content.extend(cmds.iter().flatten());
+		content.extend(blocks.iter().flatten());
+
+		let length = u32::try_from(content.len() + 4).unwrap();
+		let mut bytes = length.to_le_bytes().to_vec();
+		bytes.extend(content);
+		bytes.resize(bytes.len().next_multiple_of(4), 0);
+		return bytes;
+	}
+
+	/// Test decoding layers and reusing blocks from the previous frame.
+	#[test]
+	fn decode_sld_layers() {
+		let red = [0x00, 0xF8, 0x00, 0x00, 0, 0, 0, 0];
+		let blue = [0x1F, 0x00, 0x00, 0x00, 0, 0, 0, 0];
+		let shadow = [200, 0, 0, 0, 0, 0, 0, 0];
+		let bounds = [0, 0, 0, 0, 8, 0, 4, 0];
+
+		let mut bytes = b"SLDX".to_vec();
+		bytes.extend_from_slice(&[4, 0, 2, 0, 0, 0, 0x10, 0, 0, 0, 0, 0xFF]);
+
+		// frame 0: main layer with 2 blocks, player color layer with 1 block
+		bytes.extend(frame_header(0x11));
+		bytes.extend(layer(
+			&[bounds.as_slice(), &[0, 0]].concat(),
+			&[[0, 2]],
+			&[red, blue],
+		));
+		bytes.extend(layer(&[0, 0], &[[1, 1]], &[shadow]));
+
+		// frame 1: main layer reuses the first block, shadow layer
+		bytes.extend(frame_header(0x03));
+		bytes.extend(layer(
+			&[bounds.as_slice(), &[0x80, 0]].concat(),
+			&[[1, 1]],
+			&[red],
+		));
+		bytes.extend(layer(&[4, 0, 0, 0, 8, 0, 4, 0, 0, 0], &[[0, 1]], &[shadow]));
+
+		let sld = parse_sld(&bytes).unwrap();
+		assert_eq!(sld.frames.len(), 2);
+		assert_eq!(
+			(sld.frames[0].header.anchor_x, sld.frames[0].header.anchor_y),
+			(4, 3)
+		);
+		assert!(sld.frames[0].header.has_layer(SLDLayerType::PlayerColor));
+		assert!(!sld.frames[0].header.has_layer(SLDLayerType::Shadow));
+
+		let main = sld.frames[1].get_layer(SLDLayerType::Main).unwrap();
+		assert_eq!(main.blocks, [Some(red), Some(red)]);
+		let SLDLayerImage::Rgba(image) = &main.image
+		else {
+			panic!("main layer should be an RGBA image");
+		};
+		assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+		assert_eq!(image.get_pixel(7, 3).0, [255, 0, 0, 255]);
+
+		let player = sld.frames[0].get_layer(SLDLayerType::PlayerColor).unwrap();
+		assert_eq!(player.header.offset_x2, 8);
+		let SLDLayerImage::Gray(image) = &player.image
+		else {
+			panic!("player color layer should be a gray image");
+		};
+		assert_eq!(
+			(image.get_pixel(0, 0).0, image.get_pixel(4, 0).0),
+			([0], [200])
+		);
+
+		let shadow_layer = sld.frames[1].get_layer(SLDLayerType::Shadow).unwrap();
+		let SLDLayerImage::Gray(image) = &shadow_layer.image
+		else {
+			panic!("shadow layer should be a gray image");
+		};
+		assert_eq!(
+			(image.get_pixel(3, 0).0, image.get_pixel(4, 0).0),
+			([0], [200])
+		);
+	}
+}
@@ -0,0 +1,565 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::sld::definitions::SLD_LAYER_ALIGNMENT;
+use crate::sld::definitions::SLD_LAYER_LENGTH_SIZE;
+use crate::sld::definitions::SLD_LAYER_REUSE_PREVIOUS;
+use crate::sld::definitions::SLD_SIGNATURE;
+use crate::sld::layer::SLDLayerHeader;
+use crate::sld::layer::SLDLayerType;
+use crate::util::bcn::encode_bc1_block;
+use crate::util::bcn::encode_bc4_block;
+use crate::util::bcn::BC_BLOCK_DIM;
+use crate::util::bcn::BC_BLOCK_PIXELS;
+
+/// Format version written to SLD files.
+pub const SLD_WRITER_VERSION: u16 = 4;
+
+/// Layer images of a frame that is encoded into an SLD file.
+///
+/// All images of a frame must have the same size, which becomes the canvas
+/// size of the frame. Damage and player color layers use the area of the
+/// main layer, so they require a main layer.
+#[derive(Debug, Clone, Default)]
+pub struct SLDFrameImages {
+	/// Main graphics.
+	pub main: Option<image::RgbaImage>,
+	/// Shadow intensities.
+	pub shadow: Option<image::GrayImage>,
+	/// Damage mask.
+	pub damage: Option<image::RgbaImage>,
+	/// Player color mask.
+	pub player_color: Option<image::GrayImage>,
+	/// X coordinate of the anchor point on the canvas.
+	pub anchor_x: u16,
+	/// Y coordinate of the anchor point on the canvas.
+	pub anchor_y: u16,
+}
+
+/// Options for encoding SLD files.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SLDWriterOptions {
+	/// Skip blocks that are identical to the block at the same position in
+	/// the previous frame.
+	pub reuse_previous: bool,
+}
+
+/// Blocks of an encoded layer as the decoder sees them.
+struct EncodedLayer {
+	/// Layer header.
+	header: SLDLayerHeader,
+	/// Blocks of the layer area, `None` for transparent blocks.
+	blocks: Vec<Option<[u8; 8]>>,
+}
+
+impl EncodedLayer {
+	/// Get the block at a canvas position.
+	///
+	/// # Arguments
+	///
+	/// * `x` - X coordinate of the block on the canvas.
+	/// * `y` - Y coordinate of the block on the canvas.
+	///
+	/// # Returns
+	///
+	/// Block or `None` if there is no block at the position.
+	fn get_block(
+		&self,
+		x: usize,
+		y: usize,
+	) -> Option<[u8; 8]> {
+		let x1 = self.header.offset_x1 as usize;
+		let y1 = self.header.offset_y1 as usize;
+		if x < x1
+			|| y < y1 || !(x - x1).is_multiple_of(BC_BLOCK_DIM)
+			|| !(y - y1).is_multiple_of(BC_BLOCK_DIM)
+		{
+			return None;
+		}
+
+		let bx = (x - x1) / BC_BLOCK_DIM;
+		let by = (y - y1) / BC_BLOCK_DIM;
+		if bx >= self.header.get_blocks_width() {
+			return None;
+		}
+
+		return self
+			.blocks
+			.get(by * self.header.get_blocks_width() + bx)
+			.copied()
+			.flatten();
+	}
+}
+
+/// Get the block aligned area containing all visible pixels of an image.
+///
+/// # Arguments
+///
+/// * `width` - Width of the image.
+/// * `height` - Height of the image.
+/// * `is_visible` - Function checking if the pixel at a position is visible.
+///
+/// # Returns
+///
+/// Layer header with the area, empty if no pixel is visible.
+fn get_bounds<F>(
+	width: u32,
+	height: u32,
+	is_visible: F,
+) -> SLDLayerHeader
+where
+	F: Fn(u32, u32) -> bool,
+{
+	let mut min = (u32::MAX, u32::MAX);
+	let mut max = (0, 0);
+	for y in 0..height {
+		for x in 0..width {
+			if is_visible(x, y) {
+				min = (min.0.min(x), min.1.min(y));
+				max = (max.0.max(x), max.1.max(y));
+			}
+		}
+	}
+
+	let mut header = SLDLayerHeader {
+		offset_x1: 0,
+		offset_y1: 0,
+		offset_x2: 0,
+		offset_y2: 0,
+		flags: 0,
+		unknown: 0,
+	};
+	if min.0 > max.0 {
+		return header;
+	}
+
+	let dim = u32::try_from(BC_BLOCK_DIM).unwrap();
+	let x1 = min.0 - min.0 % dim;
+	let y1 = min.1 - min.1 % dim;
+	header.offset_x1 = u16::try_from(x1).unwrap();
+	header.offset_y1 = u16::try_from(y1).unwrap();
+	header.offset_x2 = u16::try_from(x1 + (max.0 + 1 - x1).next_multiple_of(dim)).unwrap();
+	header.offset_y2 = u16::try_from(y1 + (max.1 + 1 - y1).next_multiple_of(dim)).unwrap();
+
+	return header;
+}
+
+/// Encode the blocks of a layer area.
+///
+/// # Arguments
+///
+/// * `header` - Layer header with the area.
+/// * `encode_block` - Function encoding the block at a canvas position or
+///   returning `None` if the block is fully transparent.
+///
+/// # Returns
+///
+/// Blocks of the layer area in row-major order.
+fn encode_blocks<F>(
+	header: &SLDLayerHeader,
+	encode_block: F,
+) -> Vec<Option<[u8; 8]>>
+where
+	F: Fn(u32, u32) -> Option<[u8; 8]>,
+{
+	let mut blocks = Vec::<Option<[u8; 8]>>::new();
+	for by in 0..header.get_blocks_height() {
+		for bx in 0..header.get_blocks_width() {
+			let x = u32::from(header.offset_x1) + u32::try_from(bx * BC_BLOCK_DIM).unwrap();
+			let y = u32::from(header.offset_y1) + u32::try_from(by * BC_BLOCK_DIM).unwrap();
+			blocks.push(encode_block(x, y));
+		}
+	}
+
+	return blocks;
+}
+
+/// Gather the pixels of a block, padding with transparent pixels outside the
+/// image.
+///
+/// # Arguments
+///
+/// * `x` - X coordinate of the block.
+/// * `y` - Y coordinate of the block.
+/// * `width` - Width of the image.
+/// * `height` - Height of the image.
+/// * `get_pixel` - Function returning the pixel at a position.
+/// * `transparent` - Value of transparent pixels.
+///
+/// # Returns
+///
+/// Pixels of the block in row-major order.
+fn gather_block<T, F>(
+	x: u32,
+	y: u32,
+	width: u32,
+	height: u32,
+	get_pixel: F,
+	transparent: T,
+) -> [T; BC_BLOCK_PIXELS]
+where
+	T: Copy,
+	F: Fn(u32, u32) -> T,
+{
+	let dim = u32::try_from(BC_BLOCK_DIM).unwrap();
+	return std::array::from_fn(|i| {
+		let i = u32::try_from(i).unwrap();
+		let (px, py) = (x + i % dim, y + i / dim);
+		if px < width && py < height {
+			get_pixel(px, py)
+		}
+		else {
+			transparent
+		}
+	});
+}
+
+/// Encode the block commands and blocks of a layer.
+///
+/// Transparent blocks are skipped. If a previous layer is given, blocks
+/// equal to the previous frame's blocks are skipped as well when this draws
+/// fewer blocks.
+///
+/// # Arguments
+///
+/// * `header` - Layer header. The reuse flag is set if blocks are reused.
+/// * `blocks` - Blocks of the layer area.
+/// * `previous` - Layer of the same type in the previous frame.
+/// * `transparent` - Encoded fully transparent block.
+///
+/// # Returns
+///
+/// Encoded layer content after the layer header and the blocks as the
+/// decoder sees them.
+fn encode_cmds(
+	header: &mut SLDLayerHeader,
+	blocks: &[Option<[u8; 8]>],
+	previous: Option<&EncodedLayer>,
+	transparent: [u8; 8],
+) -> (Vec<u8>, Vec<Option<[u8; 8]>>) {
+	let blocks_width = header.get_blocks_width().max(1);
+	let plain = blocks.to_vec();
+
+	// with reuse, a block is skipped if the previous frame has the same
+	// block and drawn otherwise, even if it is transparent
+	let reused = previous.map(|previous| {
+		blocks
+			.iter()
+			.enumerate()
+			.map(|(i, block)| {
+				let x = header.offset_x1 as usize + (i % blocks_width) * BC_BLOCK_DIM;
+				let y = header.offset_y1 as usize + (i / blocks_width) * BC_BLOCK_DIM;
+				if previous.get_block(x, y) == *block {
+					None
+				}
+				else {
+					Some(block.unwrap_or(transparent))
+				}
+			})
+			.collect::<Vec<_>>()
+	});
+
+	let count = |list: &[Option<[u8; 8]>]| list.iter().filter(|block| block.is_some()).count();
+	let (draws, seen) = match reused {
+		Some(reused) if count(&reused) < count(&plain) => {
+			header.flags |= SLD_LAYER_REUSE_PREVIOUS;
+			let seen = reused
+				.iter()
+				.zip(blocks)
+				.map(|(draw, block)| draw.or(*block))
+				.collect();
+			(reused, seen)
+		}
+		_ => {
+			header.flags &= !SLD_LAYER_REUSE_PREVIOUS;
+			(plain, blocks.to_vec())
+		}
+	};
+
+	// group the blocks into runs of skipped and drawn blocks
+	let mut cmds = Vec::<[u8; 2]>::new();
+	let mut data = Vec::<u8>::new();
+	let mut i = 0;
+	while i < draws.len() {
+		let mut skip = 0u8;
+		while i < draws.len() && draws[i].is_none() && skip < u8::MAX {
+			skip += 1;
+			i += 1;
+		}
+		let mut draw = 0u8;
+		while i < draws.len() && draw < u8::MAX {
+			let Some(block) = draws[i]
+			else {
+				break;
+			};
+			data.extend_from_slice(&block);
+			draw += 1;
+			i += 1;
+		}
+		cmds.push([skip, draw]);
+	}
+
+	let mut content = u16::try_from(cmds.len())
+		.expect("SLD layer has too many block commands")
+		.to_le_bytes()
+		.to_vec();
+	content.extend(cmds.iter().flatten());
+	content.extend(data);
+
+	return (content, seen);
+}
+
+/// Encode a layer including its length field and padding.
+///
+/// # Arguments
+///
+/// * `layer_type` - Type of the layer.
+/// * `header` - Layer header.
+/// * `cmds` - Block commands and blocks.
+///
+/// # Returns
+///
+/// Bytes of the layer.
+fn encode_layer(
+	layer_type: SLDLayerType,
+	header: &SLDLayerHeader,
+	cmds: &[u8],
+) -> Vec<u8> {
+	let mut content = Vec::<u8>::new();
+	if layer_type.has_bounds() {
+		for value in [
+			header.offset_x1,
+			header.offset_y1,
+			header.offset_x2,
+			header.offset_y2,
+		] {
+			content.extend_from_slice(&value.to_le_bytes());
+		}
+	}
+	content.extend_from_slice(&[header.flags, header.unknown]);
+	content.extend_from_slice(cmds);
+
+	let length = u32::try_from(SLD_LAYER_LENGTH_SIZE + content.len()).unwrap();
+	let mut bytes = length.to_le_bytes().to_vec();
+	bytes.extend(content);
+	bytes.resize(bytes.len().next_multiple_of(SLD_LAYER_ALIGNMENT), 0);
+
+	return bytes;
+}
+
+/// Encode frames into an SLD file.
+///
+/// # Arguments
+///
+/// * `frames` - Layer images and anchors of the frames.
+/// * `options` - Writer options.
+///
+/// # Returns
+///
+/// Bytes of the SLD file.
+///
+/// # Panics
+///
+/// Panics if a frame has no images, the images of a frame differ in size,
+/// a damage or player color layer has no main layer, or the frames do not
+/// fit into the format limits.
+#[allow(clippy::too_many_lines)]
+pub fn encode_sld(
+	frames: &[SLDFrameImages],
+	options: SLDWriterOptions,
+) -> Vec<u8> {
+	let mut bytes = SLD_SIGNATURE.to_vec();
+	bytes.extend_from_slice(&SLD_WRITER_VERSION.to_le_bytes());
+	bytes.extend_from_slice(
+		&u16::try_from(frames.len())
+			.expect("SLD files can have at most 65535 frames")
+			.to_le_bytes(),
+	);
+	bytes.extend_from_slice(&0u16.to_le_bytes());
+	bytes.extend_from_slice(&0x10u16.to_le_bytes());
+	bytes.extend_from_slice(&0xFF00_0000u32.to_le_bytes());
+
+	let mut previous = Vec::<(SLDLayerType, EncodedLayer)>::new();
+	for (index, frame) in frames.iter().enumerate() {
+		let sizes = [
+			frame.main.as_ref().map(image::RgbaImage::dimensions),
+			frame.shadow.as_ref().map(image::GrayImage::dimensions),
+			frame.damage.as_ref().map(image::RgbaImage::dimensions),
+			frame
+				.player_color
+				.as_ref()
+				.map(image::GrayImage::dimensions),
+		];
+		let (width, height) = sizes
+			.iter()
+			.flatten()
+			.copied()
+			.next()
+			.unwrap_or_else(|| panic!("SLD frame {index} has no layer images"));
+		assert!(
+			sizes.iter().flatten().all(|&size| size == (width, height)),
+			"Layer images of SLD frame {index} differ in size"
+		);
+		assert!(
+			frame.main.is_some() || (frame.damage.is_none() && frame.player_color.is_none()),
+			"SLD frame {index} has damage or player color layers without a main layer"
+		);
+
+		let mut layers = Vec::<(SLDLayerType, SLDLayerHeader, Vec<Option<[u8; 8]>>)>::new();
+		let mut main_header = None;
+		if let Some(main) = &frame.main {
+			let header = get_bounds(width, height, |x, y| main.get_pixel(x, y)[3] >= 128);
+			let blocks = encode_blocks(&header, |x, y| {
+				let pixels =
+					gather_block(x, y, width, height, |x, y| main.get_pixel(x, y).0, [0; 4]);
+				pixels
+					.iter()
+					.any(|pixel| pixel[3] >= 128)
+					.then(|| encode_bc1_block(&pixels))
+			});
+			main_header = Some(header);
+			layers.push((SLDLayerType::Main, header, blocks));
+		}
+		if let Some(shadow) = &frame.shadow {
+			let header = get_bounds(width, height, |x, y| shadow.get_pixel(x, y)[0] != 0);
+			let blocks = encode_blocks(&header, |x, y| {
+				let values = gather_block(x, y, width, height, |x, y| shadow.get_pixel(x, y)[0], 0);
+				values
+					.iter()
+					.any(|&value| value != 0)
+					.then(|| encode_bc4_block(&values))
+			});
+			layers.push((SLDLayerType::Shadow, header, blocks));
+		}
+		if let (Some(damage), Some(header)) = (&frame.damage, main_header) {
+			let blocks = encode_blocks(&header, |x, y| {
+				let pixels =
+					gather_block(x, y, width, height, |x, y| damage.get_pixel(x, y).0, [0; 4]);
+				pixels
+					.iter()
+					.any(|pixel| pixel[3] >= 128)
+					.then(|| encode_bc1_block(&pixels))
+			});
+			layers.push((SLDLayerType::Damage, header, blocks));
+		}
+		if let (Some(player_color), Some(header)) = (&frame.player_color, main_header) {
+			let blocks = encode_blocks(&header, |x, y| {
+				let values = gather_block(
+					x,
+					y,
+					width,
+					height,
+					|x, y| player_color.get_pixel(x, y)[0],
+					0,
+				);
+				values
+					.iter()
+					.any(|&value| value != 0)
+					.then(|| encode_bc4_block(&values))
+			});
+			layers.push((SLDLayerType::PlayerColor, header, blocks));
+		}
+
+		let frame_type = layers
+			.iter()
+			.fold(0, |flags, (layer_type, _, _)| flags | layer_type.get_flag());
+		for value in [
+			width,
+			height,
+			u32::from(frame.anchor_x),
+			u32::from(frame.anchor_y),
+		] {
+			bytes.extend_from_slice(&u16::try_from(value).unwrap().to_le_bytes());
+		}
+		bytes.extend_from_slice(&[frame_type, 0]);
+		bytes.extend_from_slice(&u16::try_from(index).unwrap().to_le_bytes());
+
+		let mut encoded = Vec::<(SLDLayerType, EncodedLayer)>::new();
+		for (layer_type, mut header, blocks) in layers {
+			let previous_layer = previous
+				.iter()
+				.find(|(previous_type, _)| *previous_type == layer_type)
+				.map(|(_, layer)| layer)
+				.filter(|_| options.reuse_previous);
+			let transparent = if layer_type.is_bc1() {
+				encode_bc1_block(&[[0; 4]; BC_BLOCK_PIXELS])
+			}
+			else {
+				[0; 8]
+			};
+
+			let (cmds, seen) = encode_cmds(&mut header, &blocks, previous_layer, transparent);
+			bytes.extend(encode_layer(layer_type, &header, &cmds));
+			encoded.push((
+				layer_type,
+				EncodedLayer {
+					header,
+					blocks: seen,
+				},
+			));
+		}
+		previous = encoded;
+	}
+
+	return bytes;
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::sld::layer::SLDLayerImage;
+	use crate::sld::layer::SLDLayerType;
+	use crate::sld::sld::parse_sld;
+	use crate::sld::writer::encode_sld;
+	use crate::sld::writer::SLDFrameImages;
+	use crate::sld::writer::SLDWriterOptions;
+
+	/// Test that encoded frames decode to the input images.
+	#[test]
+	fn encode_and_decode_sld() {
+		let mut main = image::RgbaImage::new(10, 6);
+		let mut player_color = image::GrayImage::new(10, 6);
+		for y in 1..6 {
+			for x in 2..9 {
+				main.put_pixel(x, y, image::Rgba([255, 0, 0, 255]));
+			}
+			player_color.put_pixel(2, y, image::Luma([255]));
+		}
+		let frame = SLDFrameImages {
+			main: Some(main.clone()),
+			player_color: Some(player_color.clone()),
+			anchor_x: 5,
+			anchor_y: 4,
+			..SLDFrameImages::default()
+		};
+		let frames = [frame.clone(), frame];
+
+		let plain = encode_sld(&frames, SLDWriterOptions::default());
+		let reused = encode_sld(
+			&frames,
+			SLDWriterOptions {
+				reuse_previous: true,
+			},
+		);
+		assert!(reused.len() < plain.len());
+
+		for bytes in [plain, reused] {
+			let sld = parse_sld(&bytes).unwrap();
+			for frame in &sld.frames {
+				assert_eq!((frame.header.anchor_x, frame.header.anchor_y), (5, 4));
+
+				let layer = frame.get_layer(SLDLayerType::Main).unwrap();
+				let SLDLayerImage::Rgba(image) = &layer.image
+				else {
+					panic!("main layer should be an RGBA image");
+				};
+				assert_eq!(image, &main);
+
+				let layer = frame.get_layer(SLDLayerType::PlayerColor).unwrap();
+				let SLDLayerImage::Gray(image) = &layer.image
+				else {
+					panic!("player color layer should be a gray image");
+				};
+				assert_eq!(image, &player_color);
+			}
+		}
+	}
+}
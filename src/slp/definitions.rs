@@ -1,5 +1,27 @@
 // Copyright 2023-2023 the slutils-rs authors.
 
+use crate::slp::types::SLPVersion;
+
+/// SLP version used by Age of Empires and Age of Empires II.
+pub const SLP_VERSION_2_0N: SLPVersion = *b"2.0N";
+
+/// SLP version used by Age of Empires: Definitive Edition.
+pub const SLP_VERSION_3_0: SLPVersion = *b"3.0\0";
+
+/// SLP version used by Age of Empires II: Definitive Edition.
+pub const SLP_VERSION_4_0X: SLPVersion = *b"4.0X";
+
+/// SLP version used by Age of Empires II: Definitive Edition.
+pub const SLP_VERSION_4_1X: SLPVersion = *b"4.1X";
+
+/// SLP version used by Age of Empires II: Definitive Edition for LZ4
+/// compressed files.
+pub const SLP_VERSION_4_2P: SLPVersion = *b"4.2P";
+
+/// Size of the header in front of the compressed data of a 4.2P file
+/// (in bytes). It stores the version and the decompressed size.
+pub const SLP_COMPRESSED_HEADER_SIZE: usize = 8;
+
 /// Size of the SLP version field (in bytes).
 pub const SLP_VERSION_SIZE: usize = 4;
 
@@ -14,3 +36,51 @@ pub const SLP_FRAME_BOUNDS_SIZE: usize = 4;
 
 /// Size of an SLP frame command offset field (in bytes).
 pub const SLP_FRAME_CMD_OFFSET_SIZE: usize = 4;
+
+/// Row bound value marking a completely transparent row.
+pub const SLP_ROW_TRANSPARENT: u16 = 0x8000;
+
+/// Command drawing up to 63 palette colors.
+pub const SLP_CMD_LESSER_DRAW: u8 = 0x00;
+
+/// Command skipping up to 63 transparent pixels.
+pub const SLP_CMD_LESSER_SKIP: u8 = 0x01;
+
+/// Command drawing up to 4095 palette colors.
+pub const SLP_CMD_BIG_DRAW: u8 = 0x02;
+
+/// Command skipping up to 4095 transparent pixels.
+pub const SLP_CMD_BIG_SKIP: u8 = 0x03;
+
+/// Command drawing player colors.
+pub const SLP_CMD_PLAYER_DRAW: u8 = 0x06;
+
+/// Command filling pixels with a palette color.
+pub const SLP_CMD_FILL: u8 = 0x07;
+
+/// Command filling pixels with a player color.
+pub const SLP_CMD_PLAYER_FILL: u8 = 0x0A;
+
+/// Command drawing shadow pixels.
+pub const SLP_CMD_SHADOW: u8 = 0x0B;
+
+/// Command drawing a single player color outline pixel.
+pub const SLP_CMD_OUTLINE_1: u8 = 0x4E;
+
+/// Command drawing multiple player color outline pixels.
+pub const SLP_CMD_OUTLINE_1_MULTI: u8 = 0x5E;
+
+/// Command drawing a single black outline pixel.
+pub const SLP_CMD_OUTLINE_2: u8 = 0x6E;
+
+/// Command drawing multiple black outline pixels.
+pub const SLP_CMD_OUTLINE_2_MULTI: u8 = 0x7E;
+
+/// Command ending a row.
+pub const SLP_CMD_END_OF_ROW: u8 = 0x0F;
+
+/// Frame property flags marking a frame with 32-bit RGBA colors.
+pub const SLP_FRAME_PROPERTY_32BIT: u32 = 0x07;
+
+/// Number of palette entries reserved for each player's colors.
+pub const SLP_PLAYER_COLOR_BLOCK_SIZE: u8 = 16;
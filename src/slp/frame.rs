@@ -6,6 +6,7 @@ use std::fmt;
 use crate::slp::definitions::SLP_FRAME_BOUNDS_SIZE;
 use crate::slp::definitions::SLP_FRAME_CMD_OFFSET_SIZE;
 use crate::slp::frame_info::SLPFrameInfo;
+use crate::slp::frame_info::SLPFrameType;
 use crate::slp::pixel::PalettePixel;
 use crate::slp::pixel::RGBAPixel;
 use crate::slp::pixel::SLPPixelType;
@@ -14,11 +15,14 @@ use crate::slp::row_bound::SLPRowBoundData;
 use crate::slp::types::SLPRowOffset;
 use crate::slp::unpack::UnpackFixedSize;
 use crate::slp::unpack::UnpackFrameData;
+use crate::util::image::AlphaMode;
 use crate::util::image::RGBAImageConvertible;
 use crate::util::matrix::Matrix2D;
+use crate::util::pixel::unpremultiply;
 use crate::util::pixel::RGBAConvertible;
 
 /// SLP frame data.
+#[derive(Debug, Clone)]
 pub struct SLPFrameData {
 	/// Bounds table data.
 	bounds_table: Vec<SLPRowBoundData>,
@@ -28,7 +32,102 @@ pub struct SLPFrameData {
 	row_data: Vec<Vec<u8>>,
 }
 
+impl SLPFrameData {
+	/// Create new SLP frame data.
+	///
+	/// # Arguments
+	///
+	/// * `bounds_table` - Bounds table data.
+	/// * `cmd_table` - Command table.
+	/// * `row_data` - Row commands data.
+	///
+	/// # Returns
+	///
+	/// New SLP frame data.
+	pub const fn new(
+		bounds_table: Vec<SLPRowBoundData>,
+		cmd_table: Vec<SLPRowOffset>,
+		row_data: Vec<Vec<u8>>,
+	) -> Self {
+		return Self {
+			bounds_table,
+			cmd_table,
+			row_data,
+		};
+	}
+
+	/// Read the raw data of a frame.
+	///
+	/// The commands of each row are kept as stored, including the end of row
+	/// command. Bytes between the end of a row and the start of the next row
+	/// are kept with the row.
+	///
+	/// # Arguments
+	///
+	/// * `buffer` - The bytes of the SLP file.
+	/// * `frame_info` - Frame info of the frame.
+	///
+	/// # Returns
+	///
+	/// Raw frame data.
+	pub fn from_buffer(
+		buffer: &[u8],
+		frame_info: &SLPFrameInfo,
+	) -> Self {
+		let color_size = if frame_info.is_32bit() { 4 } else { 1 };
+		let cmd_table = SLPFrame::<PalettePixel>::decode_cmd_table(buffer, frame_info);
+		let bounds_offset = frame_info.data.bounds_table_offset as usize;
+		let raw_bounds = (0..cmd_table.len())
+			.map(|i| {
+				SLPRowBoundData::from_buffer(buffer, bounds_offset + i * SLP_FRAME_BOUNDS_SIZE)
+			})
+			.collect();
+
+		let mut row_data = Vec::<Vec<u8>>::with_capacity(cmd_table.len());
+		for (i, &row_offset) in cmd_table.iter().enumerate() {
+			let start = row_offset as usize;
+			let mut end = start + get_row_cmds_size(buffer, start, color_size);
+			if let Some(&next) = cmd_table.get(i + 1) {
+				if next as usize >= end {
+					end = next as usize;
+				}
+			}
+			row_data.push(buffer[start..end].to_vec());
+		}
+
+		return Self::new(raw_bounds, cmd_table, row_data);
+	}
+
+	/// Get the bounds table data.
+	///
+	/// # Returns
+	///
+	/// Bounds of each row as stored in the file.
+	pub fn get_bounds_table(&self) -> &[SLPRowBoundData] {
+		return &self.bounds_table;
+	}
+
+	/// Get the command table.
+	///
+	/// # Returns
+	///
+	/// Offsets of the first command of each row.
+	pub fn get_cmd_table(&self) -> &[SLPRowOffset] {
+		return &self.cmd_table;
+	}
+
+	/// Get the row commands data.
+	///
+	/// # Returns
+	///
+	/// Encoded commands of each row, including the end of row command.
+	pub fn get_row_data(&self) -> &[Vec<u8>] {
+		return &self.row_data;
+	}
+}
+
 /// SLP frame.
+#[derive(Debug, Clone)]
 pub struct SLPFrame<T> {
 	/// Frame data.
 	data: Option<SLPFrameData>,
@@ -40,7 +139,7 @@ pub struct SLPFrame<T> {
 	pixels: Vec<Vec<T>>,
 }
 
-impl SLPFrame<PalettePixel> {
+impl<T> SLPFrame<T> {
 	/// Create a new SLP frame.
 	///
 	/// # Arguments
@@ -55,7 +154,7 @@ impl SLPFrame<PalettePixel> {
 	pub fn new(
 		bounds_table: Vec<SLPRowBound>,
 		cmd_table: Vec<SLPRowOffset>,
-		row_data: Vec<Vec<PalettePixel>>,
+		row_data: Vec<Vec<T>>,
 	) -> Self {
 		Self {
 			data: None,
@@ -64,6 +163,58 @@ impl SLPFrame<PalettePixel> {
 			pixels: row_data,
 		}
 	}
+
+	/// Get the raw data of the frame.
+	///
+	/// # Returns
+	///
+	/// Raw frame data or `None` if it was not kept when parsing or the frame
+	/// was created from pixels.
+	pub const fn get_data(&self) -> Option<&SLPFrameData> {
+		return self.data.as_ref();
+	}
+
+	/// Set the raw data of the frame.
+	///
+	/// The data must encode the pixels of the frame. Writers store it
+	/// instead of encoding the pixels again.
+	///
+	/// # Arguments
+	///
+	/// * `data` - Raw frame data.
+	pub fn set_data(
+		&mut self,
+		data: SLPFrameData,
+	) {
+		self.data = Some(data);
+	}
+
+	/// Get the pixels in the frame.
+	///
+	/// # Returns
+	///
+	/// Rows of pixels.
+	pub fn get_pixels(&self) -> &[Vec<T>] {
+		return &self.pixels;
+	}
+
+	/// Get the width of the frame.
+	///
+	/// # Returns
+	///
+	/// Number of pixels in a row.
+	pub fn get_width(&self) -> usize {
+		return self.pixels.first().map_or(0, Vec::len);
+	}
+
+	/// Get the height of the frame.
+	///
+	/// # Returns
+	///
+	/// Number of rows.
+	pub const fn get_height(&self) -> usize {
+		return self.pixels.len();
+	}
 }
 
 /// Get the number of pixels following certain row commands.
@@ -99,6 +250,75 @@ fn cmd_or_next(
 	}
 }
 
+/// Get the size of the commands of a row.
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer to read from.
+/// * `first_cmd_offset` - Offset of the row's first command in the buffer.
+/// * `color_size` - Size of a color, 1 for palette frames and 4 for 32-bit
+///   frames.
+///
+/// # Returns
+///
+/// Number of bytes up to and including the end of row command.
+///
+/// # Panics
+///
+/// Panics if the row contains an unknown command or is not terminated.
+pub fn get_row_cmds_size(
+	buffer: &[u8],
+	first_cmd_offset: usize,
+	color_size: usize,
+) -> usize {
+	let mut dpos = first_cmd_offset;
+	loop {
+		let cmd = buffer[dpos];
+		let lower_nibble = cmd & 0x0F;
+		if lower_nibble == 0x0F {
+			return dpos + 1 - first_cmd_offset;
+		}
+
+		let count_or_next = |n: u8| match cmd >> n {
+			0 => (buffer[dpos + 1] as usize, 2),
+			count => (count as usize, 1),
+		};
+		dpos += match cmd & 0b0000_0011 {
+			// Lesser draw
+			0b0000_0000 => 1 + (cmd >> 2) as usize * color_size,
+			// Lesser skip
+			0b0000_0001 => count_or_next(2).1,
+			_ => match lower_nibble {
+				// Big draw
+				0x02 => {
+					let count = (usize::from(cmd & 0xF0) << 4) | usize::from(buffer[dpos + 1]);
+					2 + count * color_size
+				}
+				// Big skip
+				0x03 => 2,
+				// Player color
+				0x06 => {
+					let (count, size) = count_or_next(4);
+					size + count
+				}
+				// fill palette color
+				0x07 => count_or_next(4).1 + color_size,
+				// fill player color
+				0x0A => count_or_next(4).1 + 1,
+				// shadow fill
+				0x0B => count_or_next(4).1,
+				// Extended command
+				0x0E => match cmd & 0xF0 {
+					// outline multi draws
+					0x50 | 0x70 => 2,
+					_ => 1,
+				},
+				_ => panic!("Unknown slp draw command: {cmd:#x} at dpos {dpos:#x}"),
+			},
+		};
+	}
+}
+
 impl UnpackFrameData<PalettePixel> for SLPFrame<PalettePixel> {
 	fn from_buffer(
 		buffer: &[u8],
@@ -106,7 +326,16 @@ impl UnpackFrameData<PalettePixel> for SLPFrame<PalettePixel> {
 	) -> Self {
 		let bounds_table = Self::decode_bounds_table(buffer, frame_info);
 		let cmd_table = Self::decode_cmd_table(buffer, frame_info);
-		let row_data = Self::decode_frame(buffer, frame_info, &bounds_table, &cmd_table);
+		let mut row_data = Self::decode_frame(buffer, frame_info, &bounds_table, &cmd_table);
+
+		if frame_info.frame_type == SLPFrameType::Shadow {
+			// shadow frames store the shadow intensity as the color value
+			for pixel in row_data.iter_mut().flatten() {
+				if pixel.pixel_type == SLPPixelType::Palette {
+					pixel.pixel_type = SLPPixelType::ShadowV4;
+				}
+			}
+		}
 
 		return Self::new(bounds_table, cmd_table, row_data);
 	}
@@ -270,7 +499,7 @@ impl UnpackFrameData<PalettePixel> for SLPFrame<PalettePixel> {
 						0x02 => {
 							dpos += 1;
 							nextbyte = *buffer.get(dpos).unwrap();
-							pixel_count = ((higher_nibble << 4) + nextbyte) as u32;
+							pixel_count = (u32::from(higher_nibble) << 4) | u32::from(nextbyte);
 
 							for _ in 0..pixel_count {
 								dpos += 1;
@@ -282,7 +511,7 @@ impl UnpackFrameData<PalettePixel> for SLPFrame<PalettePixel> {
 						0x03 => {
 							dpos += 1;
 							nextbyte = *buffer.get(dpos).unwrap();
-							pixel_count = ((higher_nibble << 4) + nextbyte) as u32;
+							pixel_count = (u32::from(higher_nibble) << 4) | u32::from(nextbyte);
 
 							for _ in 0..pixel_count {
 								pixels.push(PalettePixel::new(SLPPixelType::Transparent, 0));
@@ -386,16 +615,247 @@ impl UnpackFrameData<PalettePixel> for SLPFrame<PalettePixel> {
 	}
 }
 
-impl RGBAImageConvertible for SLPFrame<PalettePixel> {
-	fn to_rgba_matrix(&self) -> Matrix2D<[u8; 4]> {
-		let height = self.pixels.len();
-		let width = self.pixels.get(0).unwrap().len();
-		let mut matrix = Matrix2D::<[u8; 4]>::zeros(width, height);
+impl UnpackFrameData<RGBAPixel> for SLPFrame<RGBAPixel> {
+	fn from_buffer(
+		buffer: &[u8],
+		frame_info: &SLPFrameInfo,
+	) -> Self {
+		let bounds_table = Self::decode_bounds_table(buffer, frame_info);
+		let cmd_table = Self::decode_cmd_table(buffer, frame_info);
+		let row_data = Self::decode_frame(buffer, frame_info, &bounds_table, &cmd_table);
+
+		return Self::new(bounds_table, cmd_table, row_data);
+	}
+
+	fn decode_bounds_table(
+		buffer: &[u8],
+		frame_info: &SLPFrameInfo,
+	) -> Vec<SLPRowBound> {
+		// bounds are stored the same way for all pixel formats
+		return SLPFrame::<PalettePixel>::decode_bounds_table(buffer, frame_info);
+	}
+
+	fn decode_cmd_table(
+		buffer: &[u8],
+		frame_info: &SLPFrameInfo,
+	) -> Vec<SLPRowOffset> {
+		// command offsets are stored the same way for all pixel formats
+		return SLPFrame::<PalettePixel>::decode_cmd_table(buffer, frame_info);
+	}
+
+	fn decode_frame(
+		buffer: &[u8],
+		frame_info: &SLPFrameInfo,
+		bounds_table: &[SLPRowBound],
+		cmd_table: &[SLPRowOffset],
+	) -> Vec<Vec<RGBAPixel>> {
+		let width = usize::try_from(frame_info.data.width).unwrap();
+
+		let mut row_data = Vec::<Vec<RGBAPixel>>::new();
+		for (bounds, row_offset) in bounds_table.iter().zip(cmd_table) {
+			let row = Self::decode_row(buffer, bounds, *row_offset as usize, width);
+			row_data.push(row);
+		}
+
+		return row_data;
+	}
+
+	fn decode_row(
+		buffer: &[u8],
+		bounds: &SLPRowBound,
+		first_cmd_offset: usize,
+		expected_size: usize,
+	) -> Vec<RGBAPixel> {
+		let transparent = RGBAPixel::new(SLPPixelType::Transparent, 0, 0, 0, 0);
+
+		if bounds.full_row {
+			return vec![transparent; expected_size];
+		}
+
+		let left = bounds.get_left() as usize;
+		let right = bounds.get_right() as usize;
+
+		let mut row = vec![transparent; left];
+		row.append(&mut Self::decode_row_cmds(
+			buffer,
+			first_cmd_offset,
+			expected_size - (left + right),
+		));
+		row.resize(row.len() + right, transparent);
+
+		return row;
+	}
+
+	/// Decode the commands for a single row in a 32-bit frame.
+	///
+	/// Colors are stored as 4 bytes in BGRA order. Player colors are stored
+	/// as 1 byte palette indices. Colors following a premultiplied alpha
+	/// command (0x9E) are converted to straight alpha until an original alpha
+	/// command (0xAE) is read.
+	#[allow(clippy::too_many_lines)]
+	fn decode_row_cmds(
+		buffer: &[u8],
+		first_cmd_offset: usize,
+		expected_size: usize,
+	) -> Vec<RGBAPixel> {
+		let mut pixels = Vec::<RGBAPixel>::new();
+
+		let mut dpos: usize = first_cmd_offset;
+		let mut premultiplied = false;
+
+		let read_color = |pos: usize, premultiplied: bool| -> RGBAPixel {
+			let bgra = &buffer[pos..pos + 4];
+			let mut rgba = [bgra[2], bgra[1], bgra[0], bgra[3]];
+			if premultiplied {
+				rgba = unpremultiply(rgba);
+			}
+			RGBAPixel::new(SLPPixelType::Palette, rgba[0], rgba[1], rgba[2], rgba[3])
+		};
+		let player = |index: u8| RGBAPixel::new(SLPPixelType::PlayerV4, index, 0, 0, 255);
+		let special = |pixel_type| RGBAPixel::new(pixel_type, 0, 0, 0, 255);
+
+		loop {
+			assert!(
+				pixels.len() <= expected_size,
+				"Expected {} pixels, but read {} without reaching end or row. dpos = {:#x}",
+				expected_size,
+				pixels.len(),
+				dpos
+			);
+
+			let cmd = buffer[dpos];
+			let lower_nibble = cmd & 0x0F;
+
+			if lower_nibble == 0x0F {
+				// End of row
+				break;
+			}
+
+			match cmd & 0b0000_0011 {
+				// Lesser draw
+				0b0000_0000 => {
+					for _ in 0..cmd >> 2 {
+						pixels.push(read_color(dpos + 1, premultiplied));
+						dpos += 4;
+					}
+				}
+				// Lesser skip
+				0b0000_0001 => {
+					let count;
+					(count, dpos) = cmd_or_next(buffer, cmd, 2, dpos);
+					pixels.resize(
+						pixels.len() + count as usize,
+						RGBAPixel::new(SLPPixelType::Transparent, 0, 0, 0, 0),
+					);
+				}
+				_ => match lower_nibble {
+					// Big draw
+					0x02 => {
+						dpos += 1;
+						let count = (u32::from(cmd & 0xF0) << 4) | u32::from(buffer[dpos]);
+						for _ in 0..count {
+							pixels.push(read_color(dpos + 1, premultiplied));
+							dpos += 4;
+						}
+					}
+					// Big skip
+					0x03 => {
+						dpos += 1;
+						let count = (u32::from(cmd & 0xF0) << 4) | u32::from(buffer[dpos]);
+						pixels.resize(
+							pixels.len() + count as usize,
+							RGBAPixel::new(SLPPixelType::Transparent, 0, 0, 0, 0),
+						);
+					}
+					// Player color
+					0x06 => {
+						let count;
+						(count, dpos) = cmd_or_next(buffer, cmd, 4, dpos);
+						for _ in 0..count {
+							dpos += 1;
+							pixels.push(player(buffer[dpos]));
+						}
+					}
+					// fill color
+					0x07 => {
+						let count;
+						(count, dpos) = cmd_or_next(buffer, cmd, 4, dpos);
+						let color = read_color(dpos + 1, premultiplied);
+						dpos += 4;
+						pixels.resize(pixels.len() + count as usize, color);
+					}
+					// fill player color
+					0x0A => {
+						let count;
+						(count, dpos) = cmd_or_next(buffer, cmd, 4, dpos);
+						dpos += 1;
+						pixels.resize(pixels.len() + count as usize, player(buffer[dpos]));
+					}
+					// shadow fill
+					0x0B => {
+						let count;
+						(count, dpos) = cmd_or_next(buffer, cmd, 4, dpos);
+						pixels.resize(
+							pixels.len() + count as usize,
+							special(SLPPixelType::ShadowV4),
+						);
+					}
+					// Extended command
+					0x0E => match cmd & 0xF0 {
+						// xflip, table switches and dither have no effect on
+						// the pixels
+						0x00 | 0x10 | 0x20 | 0x30 | 0x80 => {}
+						// outline 1 draw
+						0x40 => pixels.push(special(SLPPixelType::Special1)),
+						// outline 1 multi draw
+						0x50 => {
+							dpos += 1;
+							pixels.resize(
+								pixels.len() + buffer[dpos] as usize,
+								special(SLPPixelType::Special1),
+							);
+						}
+						// outline 2 draw
+						0x60 => pixels.push(special(SLPPixelType::Special2)),
+						// outline 2 multi draw
+						0x70 => {
+							dpos += 1;
+							pixels.resize(
+								pixels.len() + buffer[dpos] as usize,
+								special(SLPPixelType::Special2),
+							);
+						}
+						// premultiplied alpha
+						0x90 => premultiplied = true,
+						// original alpha
+						0xA0 => premultiplied = false,
+						_ => {
+							panic!("Unknown extended slp draw command: {cmd:#x} at dpos {dpos:#x}")
+						}
+					},
+					_ => panic!("Unknown slp draw command: {cmd:#x} at dpos {dpos:#x}"),
+				},
+			}
+
+			dpos += 1;
+		}
+
+		return pixels;
+	}
+}
+
+impl<T: RGBAConvertible> RGBAImageConvertible for SLPFrame<T> {
+	fn to_rgba_matrix(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+	) -> Matrix2D<[u8; 4]> {
+		let height = self.get_height();
+		let width = self.get_width();
+		let mut matrix = Matrix2D::<[u8; 4]>::zeros(height, width);
 
 		for (i, row) in self.pixels.iter().enumerate() {
 			for (j, pixel) in row.iter().enumerate() {
-				// TODO: lookup table from palette
-				let rgba = pixel.to_rgba(HashMap::<usize, [u8; 4]>::new());
+				let rgba = pixel.to_rgba(lookup);
 				matrix[(i, j)] = rgba;
 			}
 		}
@@ -403,13 +863,15 @@ impl RGBAImageConvertible for SLPFrame<PalettePixel> {
 		return matrix;
 	}
 
-	fn to_rgba_bytes(&self) -> Vec<u8> {
+	fn to_rgba_bytes(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+	) -> Vec<u8> {
 		let mut bytes = Vec::<u8>::new();
 
-		for row in self.pixels.iter() {
-			for pixel in row.iter() {
-				// TODO: lookup table from palette
-				let rgba = pixel.to_rgba(HashMap::<usize, [u8; 4]>::new());
+		for row in &self.pixels {
+			for pixel in row {
+				let rgba = pixel.to_rgba(lookup);
 				bytes.push(rgba[0]);
 				bytes.push(rgba[1]);
 				bytes.push(rgba[2]);
@@ -420,10 +882,50 @@ impl RGBAImageConvertible for SLPFrame<PalettePixel> {
 		return bytes;
 	}
 
-	fn to_image(&self) -> image::RgbaImage {
-		let height = self.pixels.len();
-		let width = self.pixels.get(0).unwrap().len();
-		let image = image::RgbaImage::from_raw(width as u32, height as u32, self.to_rgba_bytes());
+	fn to_image(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+	) -> image::RgbaImage {
+		let height = self.get_height();
+		let width = self.get_width();
+		let image = image::RgbaImage::from_raw(
+			u32::try_from(width).unwrap(),
+			u32::try_from(height).unwrap(),
+			self.to_rgba_bytes(lookup),
+		);
+
+		return image.unwrap();
+	}
+
+	fn to_rgba_bytes_with_alpha(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+		alpha_mode: AlphaMode,
+	) -> Vec<u8> {
+		if alpha_mode == AlphaMode::Straight {
+			return self.to_rgba_bytes(lookup);
+		}
+
+		return self
+			.pixels
+			.iter()
+			.flatten()
+			.flat_map(|pixel| pixel.to_premultiplied_rgba(lookup))
+			.collect();
+	}
+
+	fn to_image_with_alpha(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+		alpha_mode: AlphaMode,
+	) -> image::RgbaImage {
+		let height = self.get_height();
+		let width = self.get_width();
+		let image = image::RgbaImage::from_raw(
+			u32::try_from(width).unwrap(),
+			u32::try_from(height).unwrap(),
+			self.to_rgba_bytes_with_alpha(lookup, alpha_mode),
+		);
 
 		return image.unwrap();
 	}
@@ -494,3 +996,85 @@ impl fmt::Display for SLPFrame<RGBAPixel> {
 		write!(f, "{}", out)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use crate::slp::frame::SLPFrame;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::RGBAPixel;
+	use crate::slp::pixel::SLPPixelType;
+	use crate::slp::unpack::UnpackFrameData;
+	use crate::util::image::AlphaMode;
+	use crate::util::image::RGBAImageConvertible;
+
+	/// Test decoding 32-bit colors with premultiplied and original alpha.
+	#[test]
+	fn decode_rgba_row_alpha_modes() {
+		let buffer = [
+			// premultiplied alpha
+			&[0x9E][..],
+			// lesser draw 1 pixel (BGRA)
+			&[0x04, 25, 50, 100, 128],
+			// original alpha
+			&[0xAE],
+			// lesser draw 1 pixel (BGRA)
+			&[0x04, 25, 50, 100, 128],
+			// shadow fill 2 pixels, end of row
+			&[0x2B, 0x0F],
+		]
+		.concat();
+		let pixels = SLPFrame::<RGBAPixel>::decode_row_cmds(&buffer, 0, 4);
+
+		assert_eq!(
+			pixels,
+			vec![
+				RGBAPixel::new(SLPPixelType::Palette, 199, 100, 50, 128),
+				RGBAPixel::new(SLPPixelType::Palette, 100, 50, 25, 128),
+				RGBAPixel::new(SLPPixelType::ShadowV4, 0, 0, 0, 255),
+				RGBAPixel::new(SLPPixelType::ShadowV4, 0, 0, 0, 255),
+			]
+		);
+	}
+
+	/// Test decoding big draw and big skip counts above 255.
+	#[test]
+	fn decode_big_counts() {
+		let buffer = [
+			// big draw 300 pixels
+			&[0x12, 0x2C][..],
+			&[7; 300],
+			// big skip 260 pixels, end of row
+			&[0x13, 0x04, 0x0F],
+		]
+		.concat();
+		let pixels = SLPFrame::<PalettePixel>::decode_row_cmds(&buffer, 0, 560);
+
+		assert_eq!(pixels.len(), 560);
+		assert!(pixels[..300]
+			.iter()
+			.all(|&p| p == PalettePixel::new(SLPPixelType::Palette, 7)));
+		assert!(pixels[300..]
+			.iter()
+			.all(|p| p.pixel_type == SLPPixelType::Transparent));
+	}
+
+	/// Test that premultiplying keeps the marker colors of special pixels.
+	#[test]
+	fn premultiply_palette_colors() {
+		let frame = SLPFrame::new(
+			Vec::new(),
+			Vec::new(),
+			vec![vec![
+				PalettePixel::new(SLPPixelType::Palette, 1),
+				PalettePixel::new(SLPPixelType::Player, 200),
+			]],
+		);
+		let lookup = HashMap::from([(1, [200, 100, 50, 128])]);
+
+		let image = frame.to_image_with_alpha(&lookup, AlphaMode::Premultiplied);
+		assert_eq!(image.get_pixel(0, 0).0, [100, 50, 25, 128]);
+		assert_eq!(image.get_pixel(1, 0).0, [0, 200, 0, 254]);
+	}
+}
@@ -5,10 +5,14 @@ use std::io::Cursor;
 
 use byteorder::{LittleEndian, ReadBytesExt};
 
+use crate::slp::definitions::SLP_FRAME_INFO_SIZE;
+use crate::slp::definitions::SLP_FRAME_PROPERTY_32BIT;
+use crate::slp::pack::PackFixedSize;
 use crate::slp::types::SLPVersion;
 use crate::slp::unpack::UnpackFixedSize;
 
 /// SLP frame type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SLPFrameType {
 	Main,
 	Shadow,
@@ -27,23 +31,24 @@ impl fmt::Display for SLPFrameType {
 }
 
 /// Frame info data in an SLP file.
+#[derive(Debug, Clone, Copy)]
 pub struct SLPFrameInfoData {
 	/// Offset of the command table.
 	pub cmd_table_offset: u32,
 	/// Offset of the bounds table.
 	pub bounds_table_offset: u32,
 	/// Offset of the palette.
-	palette_offset: u32,
+	pub palette_offset: u32,
 	/// Properties.
-	properties: u32,
+	pub properties: u32,
 	/// Width of the frame.
 	pub width: i32,
 	/// Height of the frame.
 	pub height: i32,
 	/// X coordinate of the anchor point.
-	anchor_x: i32,
+	pub anchor_x: i32,
 	/// Y coordinate of the anchor point.
-	anchor_y: i32,
+	pub anchor_y: i32,
 }
 
 impl SLPFrameInfoData {
@@ -165,6 +170,22 @@ impl UnpackFixedSize for SLPFrameInfoData {
 	}
 }
 
+impl PackFixedSize for SLPFrameInfoData {
+	fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = Vec::<u8>::with_capacity(SLP_FRAME_INFO_SIZE);
+		bytes.extend_from_slice(&self.cmd_table_offset.to_le_bytes());
+		bytes.extend_from_slice(&self.bounds_table_offset.to_le_bytes());
+		bytes.extend_from_slice(&self.palette_offset.to_le_bytes());
+		bytes.extend_from_slice(&self.properties.to_le_bytes());
+		bytes.extend_from_slice(&self.width.to_le_bytes());
+		bytes.extend_from_slice(&self.height.to_le_bytes());
+		bytes.extend_from_slice(&self.anchor_x.to_le_bytes());
+		bytes.extend_from_slice(&self.anchor_y.to_le_bytes());
+
+		return bytes;
+	}
+}
+
 impl fmt::Display for SLPFrameInfoData {
 	fn fmt(
 		&self,
@@ -187,6 +208,7 @@ impl fmt::Display for SLPFrameInfoData {
 }
 
 /// Frame info in an SLP file.
+#[derive(Debug, Clone, Copy)]
 pub struct SLPFrameInfo {
 	/// Frame info data.
 	pub data: SLPFrameInfoData,
@@ -265,6 +287,19 @@ impl SLPFrameInfo {
 			slp_version,
 		}
 	}
+
+	/// Check if the frame stores 32-bit RGBA colors instead of palette
+	/// indices.
+	///
+	/// 32-bit frames only exist in SLP versions 3.0 and newer.
+	///
+	/// # Returns
+	///
+	/// true if the frame is a 32-bit frame, else false.
+	pub const fn is_32bit(&self) -> bool {
+		return self.slp_version[0] >= b'3'
+			&& self.data.properties & SLP_FRAME_PROPERTY_32BIT == SLP_FRAME_PROPERTY_32BIT;
+	}
 }
 
 impl fmt::Display for SLPFrameInfo {
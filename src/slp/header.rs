@@ -5,11 +5,14 @@ use std::{io::Cursor, string::String};
 
 use byteorder::{LittleEndian, ReadBytesExt};
 
+use crate::slp::definitions::SLP_HEADER_SIZE;
 use crate::slp::definitions::SLP_VERSION_SIZE;
+use crate::slp::pack::PackFixedSize;
 use crate::slp::types::SLPVersion;
 use crate::slp::unpack::UnpackFixedSize;
 
 /// Header data in an SLP file.
+#[derive(Debug, Clone, Copy)]
 pub struct SLPHeaderData {
 	/// Version string.
 	pub version: SLPVersion,
@@ -31,7 +34,7 @@ impl SLPHeaderData {
 	/// # Returns
 	///
 	/// New SLP header.
-	pub fn new(
+	pub const fn new(
 		version: SLPVersion,
 		num_frames: u32,
 		comment: [u8; 24],
@@ -73,10 +76,23 @@ impl UnpackFixedSize for SLPHeaderData {
 	}
 }
 
+impl PackFixedSize for SLPHeaderData {
+	fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = self.version.to_vec();
+		bytes.extend_from_slice(&self.num_frames.to_le_bytes());
+		bytes.extend_from_slice(&self.comment);
+
+		return bytes;
+	}
+}
+
 /// Header in an SLP file.
+#[derive(Debug, Clone, Copy)]
 pub struct SLPHeader {
 	/// Header data.
 	pub data: SLPHeaderData,
+	/// Header data for SLP 4.x files.
+	pub data_v4: Option<SLP4HeaderData>,
 }
 
 impl SLPHeader {
@@ -98,6 +114,7 @@ impl SLPHeader {
 	) -> Self {
 		Self {
 			data: SLPHeaderData::new(version, num_frames, comment),
+			data_v4: None,
 		}
 	}
 
@@ -110,8 +127,29 @@ impl SLPHeader {
 	/// # Returns
 	///
 	/// New SLP header.
-	pub fn from_data(data: SLPHeaderData) -> Self {
-		Self { data }
+	pub const fn from_data(data: SLPHeaderData) -> Self {
+		Self {
+			data,
+			data_v4: None,
+		}
+	}
+
+	/// Create a new SLP header from existing SLP 4.x header data.
+	///
+	/// SLP 4.x headers have no comment, so the comment is left empty.
+	///
+	/// # Arguments
+	///
+	/// * `data_v4` - SLP 4.x header data.
+	///
+	/// # Returns
+	///
+	/// New SLP header.
+	pub const fn from_data_v4(data_v4: SLP4HeaderData) -> Self {
+		Self {
+			data: SLPHeaderData::new(data_v4.version, data_v4.num_frames as u32, [0; 24]),
+			data_v4: Some(data_v4),
+		}
 	}
 
 	/// Get the version string.
@@ -147,6 +185,23 @@ impl fmt::Display for SLPHeader {
 		&self,
 		f: &mut fmt::Formatter<'_>,
 	) -> fmt::Result {
+		if let Some(data_v4) = self.data_v4 {
+			return write!(
+				f,
+				"version: {}\nnum_frames: {}\nframe_type: {:#x}\nnum_directions: \
+				 {}\nframes_per_direction: {}\npalette_id: {}\noffset_main: \
+				 {:#x}\noffset_secondary: {:#x}",
+				self.get_version(),
+				data_v4.num_frames,
+				data_v4.frame_type,
+				data_v4.num_directions,
+				data_v4.frames_per_direction,
+				data_v4.palette_id,
+				data_v4.offset_main,
+				data_v4.offset_secondary
+			);
+		}
+
 		write!(
 			f,
 			"version: {}\nnum_frames: {}\ncomment: {}",
@@ -157,20 +212,50 @@ impl fmt::Display for SLPHeader {
 	}
 }
 
+/// Header data in an SLP file with version 4.0X or 4.1X.
+#[derive(Debug, Clone, Copy)]
 pub struct SLP4HeaderData {
-	version: SLPVersion,
-	num_frames: u16,
-	frame_type: u16,
-	num_directions: u16,
-	frames_per_direction: u16,
-	palette_id: u32,
-	offset_main: u32,
-	offset_secondary: u32,
-	pad: [u8; 8],
+	/// Version string.
+	pub version: SLPVersion,
+	/// Number of main frames.
+	pub num_frames: u16,
+	/// Frame type flags.
+	pub frame_type: u16,
+	/// Number of stored animation directions.
+	pub num_directions: u16,
+	/// Number of frames in each direction.
+	pub frames_per_direction: u16,
+	/// ID of the palette used by the frames.
+	pub palette_id: u32,
+	/// Offset of the main frame info table.
+	pub offset_main: u32,
+	/// Offset of the secondary frame info table. 0 if there are no secondary
+	/// frames.
+	pub offset_secondary: u32,
+	/// Padding.
+	pub pad: [u8; 8],
 }
 
 impl SLP4HeaderData {
-	pub fn new(
+	/// Create a new SLP 4.x header.
+	///
+	/// # Arguments
+	///
+	/// * `version` - Version string.
+	/// * `num_frames` - Number of main frames.
+	/// * `frame_type` - Frame type flags.
+	/// * `num_directions` - Number of stored animation directions.
+	/// * `frames_per_direction` - Number of frames in each direction.
+	/// * `palette_id` - ID of the palette used by the frames.
+	/// * `offset_main` - Offset of the main frame info table.
+	/// * `offset_secondary` - Offset of the secondary frame info table.
+	/// * `pad` - Padding.
+	///
+	/// # Returns
+	///
+	/// New SLP 4.x header.
+	#[allow(clippy::too_many_arguments)]
+	pub const fn new(
 		version: SLPVersion,
 		num_frames: u16,
 		frame_type: u16,
@@ -180,8 +265,8 @@ impl SLP4HeaderData {
 		offset_main: u32,
 		offset_secondary: u32,
 		pad: [u8; 8],
-	) -> SLP4HeaderData {
-		SLP4HeaderData {
+	) -> Self {
+		Self {
 			version,
 			num_frames,
 			frame_type,
@@ -193,4 +278,65 @@ impl SLP4HeaderData {
 			pad,
 		}
 	}
+
+	/// Check if the file contains secondary frames.
+	///
+	/// # Returns
+	///
+	/// true if there is a secondary frame info table, else false.
+	pub const fn has_secondary_frames(&self) -> bool {
+		return self.offset_secondary != 0;
+	}
+}
+
+impl PackFixedSize for SLP4HeaderData {
+	fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = self.version.to_vec();
+		bytes.extend_from_slice(&self.num_frames.to_le_bytes());
+		bytes.extend_from_slice(&self.frame_type.to_le_bytes());
+		bytes.extend_from_slice(&self.num_directions.to_le_bytes());
+		bytes.extend_from_slice(&self.frames_per_direction.to_le_bytes());
+		bytes.extend_from_slice(&self.palette_id.to_le_bytes());
+		bytes.extend_from_slice(&self.offset_main.to_le_bytes());
+		bytes.extend_from_slice(&self.offset_secondary.to_le_bytes());
+		bytes.extend_from_slice(&self.pad);
+
+		return bytes;
+	}
+}
+
+impl UnpackFixedSize for SLP4HeaderData {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + SLP_HEADER_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		let version: SLPVersion = bytes[0..SLP_VERSION_SIZE].try_into().unwrap();
+
+		let mut byte_reader = Cursor::new(&bytes[4..24]);
+		let num_frames = byte_reader.read_u16::<LittleEndian>().unwrap();
+		let frame_type = byte_reader.read_u16::<LittleEndian>().unwrap();
+		let num_directions = byte_reader.read_u16::<LittleEndian>().unwrap();
+		let frames_per_direction = byte_reader.read_u16::<LittleEndian>().unwrap();
+		let palette_id = byte_reader.read_u32::<LittleEndian>().unwrap();
+		let offset_main = byte_reader.read_u32::<LittleEndian>().unwrap();
+		let offset_secondary = byte_reader.read_u32::<LittleEndian>().unwrap();
+
+		let pad: [u8; 8] = bytes[24..32].try_into().unwrap();
+
+		return Self::new(
+			version,
+			num_frames,
+			frame_type,
+			num_directions,
+			frames_per_direction,
+			palette_id,
+			offset_main,
+			offset_secondary,
+			pad,
+		);
+	}
 }
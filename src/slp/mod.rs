@@ -1,11 +1,15 @@
 // Copyright 2023-2023 the slutils-rs authors.
 
 pub mod definitions;
-mod frame;
-mod frame_info;
-mod header;
-mod pixel;
-mod row_bound;
+pub mod frame;
+pub mod frame_info;
+pub mod header;
+pub mod optimize;
+pub mod pack;
+pub mod pixel;
+pub mod row_bound;
 pub mod slp;
-mod types;
+pub mod types;
 pub mod unpack;
+pub mod writer;
+pub mod writer_v4;
@@ -0,0 +1,351 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::io;
+
+use crate::slp::definitions::SLP_VERSION_4_2P;
+use crate::slp::frame::SLPFrame;
+use crate::slp::frame_info::SLPFrameInfo;
+use crate::slp::pixel::PalettePixel;
+use crate::slp::pixel::RGBAPixel;
+use crate::slp::pixel::SLPPixelType;
+use crate::slp::slp::check_slp_header;
+use crate::slp::slp::parse_slp_with_options;
+use crate::slp::slp::SLPFile;
+use crate::slp::slp::SLPParseOptions;
+use crate::slp::writer::encode_slp_with_options;
+use crate::slp::writer::SLPWriterOptions;
+use crate::slp::writer_v4::encode_slp4;
+use crate::slp::writer_v4::SLP4WriterOptions;
+
+/// Options for optimizing SLP files.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OptimizeOptions {
+	/// Crop the transparent borders of the frames. This changes the frame
+	/// sizes and anchors.
+	pub crop_frames: bool,
+}
+
+/// Result of optimizing an SLP file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptimizeReport {
+	/// Size of the original file in bytes.
+	pub original_size: usize,
+	/// Size of the optimized file in bytes.
+	pub optimized_size: usize,
+}
+
+impl OptimizeReport {
+	/// Get the number of bytes saved by the optimization.
+	///
+	/// # Returns
+	///
+	/// Difference between the original and the optimized size.
+	pub const fn get_bytes_saved(&self) -> usize {
+		return self.original_size.saturating_sub(self.optimized_size);
+	}
+}
+
+/// Crop the transparent borders of a frame.
+///
+/// The anchor of the frame info is moved, so the frame is drawn at the same
+/// position.
+///
+/// # Arguments
+///
+/// * `frame` - Frame to crop.
+/// * `frame_info` - Frame info of the frame.
+/// * `is_transparent` - Returns true for transparent pixels.
+///
+/// # Returns
+///
+/// Cropped frame. Frames without visible pixels are returned unchanged.
+///
+/// # Panics
+///
+/// Panics if the frame is too large.
+fn crop_frame<T, F>(
+	frame: &SLPFrame<T>,
+	frame_info: &mut SLPFrameInfo,
+	is_transparent: F,
+) -> SLPFrame<T>
+where
+	T: Copy,
+	F: Fn(&T) -> bool,
+{
+	let pixels = frame.get_pixels();
+	let is_empty_row = |row: &Vec<T>| row.iter().all(&is_transparent);
+	let Some(top) = pixels.iter().position(|row| !is_empty_row(row))
+	else {
+		return SLPFrame::new(Vec::new(), Vec::new(), pixels.to_vec());
+	};
+	let bottom = pixels
+		.iter()
+		.rposition(|row| !is_empty_row(row))
+		.unwrap_or(top);
+	let left = pixels
+		.iter()
+		.filter_map(|row| row.iter().position(|pixel| !is_transparent(pixel)))
+		.min()
+		.unwrap_or(0);
+	let right = pixels
+		.iter()
+		.filter_map(|row| row.iter().rposition(|pixel| !is_transparent(pixel)))
+		.max()
+		.unwrap_or(left);
+
+	let rows = pixels[top..=bottom]
+		.iter()
+		.map(|row| row[left..=right].to_vec())
+		.collect::<Vec<Vec<T>>>();
+
+	frame_info.data.width = i32::try_from(right - left + 1).unwrap();
+	frame_info.data.height = i32::try_from(bottom - top + 1).unwrap();
+	frame_info.data.anchor_x -= i32::try_from(left).unwrap();
+	frame_info.data.anchor_y -= i32::try_from(top).unwrap();
+
+	return SLPFrame::new(Vec::new(), Vec::new(), rows);
+}
+
+/// Crop the frames of a frame table.
+///
+/// # Arguments
+///
+/// * `frames` - Frames to crop.
+/// * `frame_infos` - Frame infos of the frames.
+/// * `is_transparent` - Returns true for transparent pixels.
+fn crop_frames<T, F>(
+	frames: &mut [SLPFrame<T>],
+	frame_infos: &mut [SLPFrameInfo],
+	is_transparent: F,
+) where
+	T: Copy,
+	F: Fn(&T) -> bool,
+{
+	for (frame, frame_info) in frames.iter_mut().zip(frame_infos) {
+		*frame = crop_frame(frame, frame_info, &is_transparent);
+	}
+}
+
+/// Drop the raw data of frames kept from parsing.
+///
+/// # Arguments
+///
+/// * `frames` - Frames to reset.
+fn drop_frame_data<T: Copy>(frames: &mut [SLPFrame<T>]) {
+	for frame in frames {
+		*frame = SLPFrame::new(Vec::new(), Vec::new(), frame.get_pixels().to_vec());
+	}
+}
+
+/// Optimize the frames of an SLP file for encoding.
+///
+/// Raw frame data kept from parsing is dropped, so writers encode every row
+/// with the smallest commands. If enabled, transparent borders of all
+/// frames are cropped, so no bounds or row commands are stored for them.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file.
+/// * `options` - Optimization options.
+///
+/// # Panics
+///
+/// Panics if a frame is too large.
+pub fn optimize_slp(
+	slp: &mut SLPFile,
+	options: &OptimizeOptions,
+) {
+	if !options.crop_frames {
+		drop_frame_data(&mut slp.frames);
+		drop_frame_data(&mut slp.rgba_frames);
+		drop_frame_data(&mut slp.shadow_frames);
+		return;
+	}
+
+	crop_frames(
+		&mut slp.frames,
+		&mut slp.frame_infos,
+		|pixel: &PalettePixel| pixel.pixel_type == SLPPixelType::Transparent,
+	);
+	crop_frames(
+		&mut slp.rgba_frames,
+		&mut slp.frame_infos,
+		|pixel: &RGBAPixel| pixel.pixel_type == SLPPixelType::Transparent,
+	);
+	crop_frames(
+		&mut slp.shadow_frames,
+		&mut slp.shadow_frame_infos,
+		|pixel: &PalettePixel| pixel.pixel_type == SLPPixelType::Transparent,
+	);
+}
+
+/// Optimize the encoding of an SLP file.
+///
+/// The file is decoded, optimized with [`optimize_slp`] and encoded again
+/// with shared rows and frames. Compressed 4.2P files stay compressed. If
+/// the result is not smaller, the original file is kept. SLP 3.0 files with
+/// 32-bit frames cannot be encoded again and are kept as well.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the SLP file.
+/// * `options` - Optimization options.
+///
+/// # Returns
+///
+/// The bytes of the optimized SLP file and the size difference.
+///
+/// # Errors
+///
+/// Returns an error if the file is not an SLP file or its header is
+/// truncated.
+pub fn optimize_slp_file(
+	bytes: Vec<u8>,
+	options: &OptimizeOptions,
+) -> io::Result<(Vec<u8>, OptimizeReport)> {
+	let compressed = check_slp_header(&bytes)? == SLP_VERSION_4_2P;
+	let original_size = bytes.len();
+
+	let mut slp = parse_slp_with_options(&bytes, SLPParseOptions::default());
+	optimize_slp(&mut slp, options);
+	let optimized = match slp.header.data_v4.as_ref() {
+		Some(data_v4) => {
+			let options = SLP4WriterOptions {
+				compress: compressed,
+				share_data: true,
+				..SLP4WriterOptions::from_header(data_v4)
+			};
+			Some(encode_slp4(&slp, options))
+		}
+		// the 2.0N layout writer only stores palette frames
+		None if !slp.rgba_frames.is_empty() => None,
+		None => Some(encode_slp_with_options(
+			&slp,
+			SLPWriterOptions { share_data: true },
+		)),
+	};
+
+	let Some(optimized) = optimized.filter(|optimized| optimized.len() < original_size)
+	else {
+		let report = OptimizeReport {
+			original_size,
+			optimized_size: original_size,
+		};
+		return Ok((bytes, report));
+	};
+
+	let report = OptimizeReport {
+		original_size,
+		optimized_size: optimized.len(),
+	};
+	return Ok((optimized, report));
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::slp::optimize::optimize_slp_file;
+	use crate::slp::optimize::OptimizeOptions;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::SLPPixelType;
+	use crate::slp::slp::parse_slp;
+	use crate::slp::slp::parse_slp_with_options;
+	use crate::slp::slp::SLPParseOptions;
+
+	/// Test that optimized files are smaller and decode to the same image.
+	#[test]
+	fn optimize_draw_commands() {
+		let transparent = PalettePixel::new(SLPPixelType::Transparent, 0);
+		let color = PalettePixel::new(SLPPixelType::Palette, 9);
+		let mut row = vec![transparent; 2];
+		row.extend([color; 10]);
+
+		// transparent top row and rows drawn with a draw command
+		let mut bytes = b"2.0N".to_vec();
+		bytes.extend_from_slice(&1u32.to_le_bytes());
+		bytes.extend_from_slice(&[0; 24]);
+		for value in [76i32, 64, 0, 0, 12, 3, 6, 3] {
+			bytes.extend_from_slice(&value.to_le_bytes());
+		}
+		bytes.extend_from_slice(&[0, 0x80, 0, 0x80, 2, 0, 0, 0, 2, 0, 0, 0]);
+		bytes.extend_from_slice(&[88, 0, 0, 0, 89, 0, 0, 0, 101, 0, 0, 0]);
+		bytes.push(0x0F);
+		for _ in 0..2 {
+			bytes.push(0x28);
+			bytes.extend_from_slice(&[9; 10]);
+			bytes.push(0x0F);
+		}
+		let original = parse_slp(&bytes);
+		assert_eq!(
+			original.frames[0].get_pixels(),
+			[vec![transparent; 12], row.clone(), row]
+		);
+
+		let options = SLPParseOptions {
+			retain_frame_data: true,
+		};
+
+		// the layout of the frame is kept by default
+		let (optimized, report) =
+			optimize_slp_file(bytes.clone(), &OptimizeOptions::default()).unwrap();
+		assert_eq!(report.original_size, bytes.len());
+		assert_eq!(report.optimized_size, optimized.len());
+		assert!(report.get_bytes_saved() > 0);
+		let slp = parse_slp_with_options(&optimized, options);
+		assert_eq!(slp.frames[0].get_pixels(), original.frames[0].get_pixels());
+		let info = &slp.frame_infos[0].data;
+		assert_eq!((info.anchor_x, info.anchor_y), (6, 3));
+
+		let crop = OptimizeOptions { crop_frames: true };
+		let (optimized, report) = optimize_slp_file(bytes.clone(), &crop).unwrap();
+		assert_eq!(report.optimized_size, optimized.len());
+		assert_eq!(report.get_bytes_saved(), 30);
+		let slp = parse_slp_with_options(&optimized, options);
+		assert_eq!(
+			slp.frames[0].get_pixels(),
+			[vec![color; 10], vec![color; 10]]
+		);
+		let info = &slp.frame_infos[0].data;
+		assert_eq!((info.anchor_x, info.anchor_y), (4, 2));
+
+		// both rows share their commands
+		let cmd_table = slp.frames[0].get_data().unwrap().get_cmd_table();
+		assert_eq!(cmd_table[0], cmd_table[1]);
+
+		// optimizing again keeps the file
+		let (again, report) = optimize_slp_file(optimized.clone(), &crop).unwrap();
+		assert_eq!(report.get_bytes_saved(), 0);
+		assert_eq!(again, optimized);
+	}
+
+	/// Test that SLP 3.0 files with 32-bit frames are kept.
+	#[test]
+	fn optimize_rgba_frames() {
+		let mut bytes = b"3.0\0".to_vec();
+		bytes.extend_from_slice(&1u32.to_le_bytes());
+		bytes.extend_from_slice(&[0; 24]);
+		// 32-bit frame with a single row
+		for value in [68i32, 64, 0, 0x07, 2, 1, 0, 0] {
+			bytes.extend_from_slice(&value.to_le_bytes());
+		}
+		bytes.extend_from_slice(&[0, 0, 0, 0, 72, 0, 0, 0]);
+		// draw 2 pixels (BGRA), end of row
+		bytes.extend_from_slice(&[0x08, 1, 2, 3, 255, 1, 2, 3, 255, 0x0F]);
+		let original = parse_slp(&bytes);
+		assert_eq!(original.rgba_frames.len(), 1);
+
+		let (optimized, report) =
+			optimize_slp_file(bytes.clone(), &OptimizeOptions::default()).unwrap();
+		assert_eq!(report.original_size, bytes.len());
+		assert_eq!(report.get_bytes_saved(), 0);
+		assert_eq!(optimized, bytes);
+	}
+
+	/// Test that short and non-SLP files return an error.
+	#[test]
+	fn optimize_invalid_files() {
+		let options = OptimizeOptions::default();
+		assert!(optimize_slp_file(b"2.0".to_vec(), &options).is_err());
+		assert!(optimize_slp_file(b"2.0N\x01\x00".to_vec(), &options).is_err());
+		assert!(optimize_slp_file(b"SMPX\x02\x00".to_vec(), &options).is_err());
+	}
+}
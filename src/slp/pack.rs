@@ -0,0 +1,11 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+/// Pack a fixed size object for writing it to a file.
+pub trait PackFixedSize {
+	/// Convert the object to its byte representation in a file.
+	///
+	/// # Returns
+	///
+	/// Bytes of the object.
+	fn to_bytes(&self) -> Vec<u8>;
+}
@@ -2,9 +2,12 @@
 
 use std::{collections::HashMap, fmt};
 
+use crate::slp::definitions::SLP_PLAYER_COLOR_BLOCK_SIZE;
+use crate::util::pixel::premultiply;
 use crate::util::pixel::RGBAConvertible;
 
 /// Pixel type in an SLP frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SLPPixelType {
 	/// 8-bit palette index
 	Palette,
@@ -26,6 +29,7 @@ pub enum SLPPixelType {
 }
 
 /// Pixel in an SLP frame using palette indices for colors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PalettePixel {
 	/// Pixel type
 	pub pixel_type: SLPPixelType,
@@ -49,15 +53,43 @@ impl PalettePixel {
 	) -> Self {
 		Self { pixel_type, index }
 	}
+
+	/// Get the palette index of the pixel as drawn for a specific player.
+	///
+	/// Player color pixels store an offset into the player color block of
+	/// the palette. Each player uses a block of 16 palette entries.
+	///
+	/// # Arguments
+	///
+	/// * `player` - Player number.
+	///
+	/// # Returns
+	///
+	/// Palette index of the pixel color.
+	pub const fn get_player_index(
+		&self,
+		player: u8,
+	) -> u8 {
+		match self.pixel_type {
+			SLPPixelType::Player | SLPPixelType::PlayerV4 => self
+				.index
+				.wrapping_add(player.wrapping_mul(SLP_PLAYER_COLOR_BLOCK_SIZE)),
+			_ => self.index,
+		}
+	}
 }
 
 impl RGBAConvertible for PalettePixel {
 	fn to_rgba(
 		&self,
-		_lookup: HashMap<usize, [u8; 4]>,
+		lookup: &HashMap<usize, [u8; 4]>,
 	) -> [u8; 4] {
 		match self.pixel_type {
-			SLPPixelType::Palette => [self.index, self.index, self.index, 255],
+			// fall back to grayscale if the index is not in the lookup table
+			SLPPixelType::Palette => lookup
+				.get(&(self.index as usize))
+				.copied()
+				.unwrap_or([self.index, self.index, self.index, 255]),
 			SLPPixelType::Transparent => [0, 0, 0, 0],
 			SLPPixelType::Shadow | SLPPixelType::ShadowV4 => [0, 0, 0, 100],
 			SLPPixelType::Player | SLPPixelType::PlayerV4 => [0, self.index, 0, 254],
@@ -65,6 +97,19 @@ impl RGBAConvertible for PalettePixel {
 			SLPPixelType::Special2 => [0, 0, 0, 250],
 		}
 	}
+
+	fn to_premultiplied_rgba(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+	) -> [u8; 4] {
+		let rgba = self.to_rgba(lookup);
+		// the colors of the other pixel types encode the pixel type
+		if self.pixel_type != SLPPixelType::Palette {
+			return rgba;
+		}
+
+		return premultiply(rgba);
+	}
 }
 
 impl fmt::Display for PalettePixel {
@@ -84,6 +129,9 @@ impl fmt::Display for PalettePixel {
 }
 
 /// Pixel in an SLP frame using RGBA colors.
+///
+/// Player color pixels store their palette index in the red component.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RGBAPixel {
 	/// Pixel type
 	pub pixel_type: SLPPixelType,
@@ -126,3 +174,33 @@ impl RGBAPixel {
 		}
 	}
 }
+
+impl RGBAConvertible for RGBAPixel {
+	fn to_rgba(
+		&self,
+		_lookup: &HashMap<usize, [u8; 4]>,
+	) -> [u8; 4] {
+		match self.pixel_type {
+			SLPPixelType::Palette => [self.r, self.g, self.b, self.a],
+			SLPPixelType::Transparent => [0, 0, 0, 0],
+			SLPPixelType::Shadow | SLPPixelType::ShadowV4 => [0, 0, 0, 100],
+			// player color pixels store the palette index in the red component
+			SLPPixelType::Player | SLPPixelType::PlayerV4 => [0, self.r, 0, 254],
+			SLPPixelType::Special1 => [0, 0, 0, 252],
+			SLPPixelType::Special2 => [0, 0, 0, 250],
+		}
+	}
+
+	fn to_premultiplied_rgba(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+	) -> [u8; 4] {
+		let rgba = self.to_rgba(lookup);
+		// the colors of the other pixel types encode the pixel type
+		if self.pixel_type != SLPPixelType::Palette {
+			return rgba;
+		}
+
+		return premultiply(rgba);
+	}
+}
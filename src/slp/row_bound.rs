@@ -1,8 +1,12 @@
 // Copyright 2023-2023 the slutils-rs authors.
 
+use crate::slp::definitions::SLP_FRAME_BOUNDS_SIZE;
+use crate::slp::definitions::SLP_ROW_TRANSPARENT;
+use crate::slp::pack::PackFixedSize;
 use crate::slp::unpack::UnpackFixedSize;
 
 /// Bounds data for a row in an SLP frame.
+#[derive(Debug, Clone, Copy)]
 pub struct SLPRowBoundData {
 	/// Number of transparent pixels prepended on the left.
 	pub left: u16,
@@ -30,6 +34,7 @@ impl SLPRowBoundData {
 }
 
 /// Bounds for a row in an SLP frame.
+#[derive(Debug, Clone, Copy)]
 pub struct SLPRowBound {
 	/// Bounds data.
 	pub data: SLPRowBoundData,
@@ -86,6 +91,22 @@ impl SLPRowBound {
 	}
 }
 
+impl UnpackFixedSize for SLPRowBoundData {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + SLP_FRAME_BOUNDS_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		let left = u16::from_le_bytes([bytes[0], bytes[1]]);
+		let right = u16::from_le_bytes([bytes[2], bytes[3]]);
+
+		return Self::new(left, right);
+	}
+}
+
 impl UnpackFixedSize for SLPRowBound {
 	fn from_buffer(
 		buffer: &[u8],
@@ -94,7 +115,7 @@ impl UnpackFixedSize for SLPRowBound {
 		let left = u16::from_le_bytes([buffer[offset], buffer[offset + 1]]);
 		let right = u16::from_le_bytes([buffer[offset + 2], buffer[offset + 3]]);
 
-		if left == 0x8000 || right == 0x8000 {
+		if left == SLP_ROW_TRANSPARENT || right == SLP_ROW_TRANSPARENT {
 			// row is completely transparent
 			return Self::new(0, 0, true);
 		}
@@ -106,7 +127,7 @@ impl UnpackFixedSize for SLPRowBound {
 		let left = u16::from_le_bytes([bytes[0], bytes[1]]);
 		let right = u16::from_le_bytes([bytes[2], bytes[3]]);
 
-		if left == 0x8000 || right == 0x8000 {
+		if left == SLP_ROW_TRANSPARENT || right == SLP_ROW_TRANSPARENT {
 			// row is completely transparent
 			return Self::new(0, 0, true);
 		}
@@ -114,3 +135,12 @@ impl UnpackFixedSize for SLPRowBound {
 		return Self::new(left, right, false);
 	}
 }
+
+impl PackFixedSize for SLPRowBoundData {
+	fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = self.left.to_le_bytes().to_vec();
+		bytes.extend_from_slice(&self.right.to_le_bytes());
+
+		return bytes;
+	}
+}
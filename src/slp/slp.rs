@@ -1,29 +1,280 @@
 // Copyright 2023-2023 the slutils-rs authors.
 
+use std::io;
+use std::ops::Range;
+
+use crate::format::detect::detect_format;
+use crate::format::detect::GraphicFormat;
+use crate::slp::definitions::SLP_COMPRESSED_HEADER_SIZE;
 use crate::slp::definitions::SLP_FRAME_INFO_SIZE;
 use crate::slp::definitions::SLP_HEADER_SIZE;
+use crate::slp::definitions::SLP_VERSION_4_0X;
+use crate::slp::definitions::SLP_VERSION_4_1X;
+use crate::slp::definitions::SLP_VERSION_4_2P;
+use crate::slp::definitions::SLP_VERSION_SIZE;
 use crate::slp::frame::SLPFrame;
+use crate::slp::frame::SLPFrameData;
 use crate::slp::frame_info::SLPFrameInfo;
 use crate::slp::frame_info::SLPFrameInfoData;
 use crate::slp::frame_info::SLPFrameType;
+use crate::slp::header::SLP4HeaderData;
 use crate::slp::header::SLPHeader;
 use crate::slp::header::SLPHeaderData;
 use crate::slp::pixel::PalettePixel;
+use crate::slp::pixel::RGBAPixel;
+use crate::slp::types::SLPVersion;
 use crate::slp::unpack::UnpackFixedSize;
 use crate::slp::unpack::UnpackFrameData;
+use crate::util::lz4::decompress_block;
 
 /// SLP file.
+#[derive(Debug)]
 pub struct SLPFile {
 	/// SLP header.
 	pub header: SLPHeader,
 	/// SLP frame infos.
 	pub frame_infos: Vec<SLPFrameInfo>,
-	/// SLP frames.
+	/// SLP frames with palette indices.
 	pub frames: Vec<SLPFrame<PalettePixel>>,
+	/// SLP frames with 32-bit RGBA colors. Files store either palette or
+	/// 32-bit main frames, so only one of `frames` and `rgba_frames` is
+	/// filled.
+	pub rgba_frames: Vec<SLPFrame<RGBAPixel>>,
+	/// Frame infos of the shadow frames (SLP 4.x only).
+	pub shadow_frame_infos: Vec<SLPFrameInfo>,
+	/// Shadow frames (SLP 4.x only).
+	pub shadow_frames: Vec<SLPFrame<PalettePixel>>,
+}
+
+impl SLPFile {
+	/// Get the frame indices belonging to an animation direction.
+	///
+	/// Frames are assumed to be split evenly between directions, i.e. the
+	/// first `num_frames / num_directions` frames belong to the first
+	/// direction, the next ones to the second direction and so on. SLP 4.x
+	/// files store the number of directions and frames per direction in the
+	/// header, which take precedence over `num_directions`.
+	///
+	/// # Arguments
+	///
+	/// * `direction` - Index of the direction.
+	/// * `num_directions` - Number of directions stored in the file.
+	///
+	/// # Returns
+	///
+	/// Range of frame indices for the direction.
+	///
+	/// # Panics
+	///
+	/// Panics if the direction index is out of range.
+	pub fn get_direction_frames(
+		&self,
+		direction: usize,
+		num_directions: usize,
+	) -> Range<usize> {
+		let num_frames = self.frames.len().max(self.rgba_frames.len());
+		let (num_directions, frames_per_direction) = match self.header.data_v4 {
+			Some(data_v4) if data_v4.num_directions > 0 => (
+				usize::from(data_v4.num_directions),
+				usize::from(data_v4.frames_per_direction),
+			),
+			_ => (num_directions, num_frames / num_directions.max(1)),
+		};
+		assert!(
+			direction < num_directions,
+			"Direction {direction} out of range for {num_directions} directions"
+		);
+
+		let start = direction * frames_per_direction;
+
+		return start..start + frames_per_direction;
+	}
+
+	/// Create a 2.0N SLP file from palette frames.
+	///
+	/// The frame infos have the size of the frames and the anchor at the top
+	/// left corner.
+	///
+	/// # Arguments
+	///
+	/// * `frames` - Frames of the file.
+	///
+	/// # Returns
+	///
+	/// SLP file with the frames.
+	///
+	/// # Panics
+	///
+	/// Panics if a frame is too large for a frame info.
+	#[cfg(test)]
+	pub fn from_palette_frames(frames: Vec<SLPFrame<PalettePixel>>) -> Self {
+		let frame_infos = frames
+			.iter()
+			.map(|frame| {
+				SLPFrameInfo::new(
+					0,
+					0,
+					0,
+					0,
+					i32::try_from(frame.get_width()).unwrap(),
+					i32::try_from(frame.get_height()).unwrap(),
+					0,
+					0,
+					SLPFrameType::Main,
+					*b"2.0N",
+				)
+			})
+			.collect();
+
+		return Self {
+			header: SLPHeader::new(*b"2.0N", u32::try_from(frames.len()).unwrap(), [0; 24]),
+			frame_infos,
+			frames,
+			rgba_frames: Vec::new(),
+			shadow_frame_infos: Vec::new(),
+			shadow_frames: Vec::new(),
+		};
+	}
+}
+
+/// Options for parsing SLP files.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SLPParseOptions {
+	/// Keep the raw data of the frames, so writers can store unmodified
+	/// frames byte for byte.
+	pub retain_frame_data: bool,
+}
+
+/// Parse a frame info table.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the SLP file.
+/// * `table_offset` - Offset of the frame info table.
+/// * `num_frames` - Number of frames in the table.
+/// * `frame_type` - Type of the frames in the table.
+/// * `version` - SLP version.
+///
+/// # Returns
+///
+/// The parsed frame infos.
+fn parse_frame_infos(
+	bytes: &[u8],
+	table_offset: usize,
+	num_frames: u32,
+	frame_type: SLPFrameType,
+	version: SLPVersion,
+) -> Vec<SLPFrameInfo> {
+	let mut frame_infos = Vec::<SLPFrameInfo>::new();
+	for i in 0..num_frames {
+		let offset = table_offset + (i as usize) * SLP_FRAME_INFO_SIZE;
+		let info_data = SLPFrameInfoData::from_buffer(bytes, offset);
+		frame_infos.push(SLPFrameInfo::from_data(info_data, frame_type, version));
+	}
+
+	return frame_infos;
+}
+
+/// Decode the frames referenced by frame infos.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the SLP file.
+/// * `frame_infos` - Frame infos of the frames.
+/// * `options` - Parsing options.
+///
+/// # Returns
+///
+/// The decoded frames.
+fn decode_frames<T>(
+	bytes: &[u8],
+	frame_infos: &[SLPFrameInfo],
+	options: SLPParseOptions,
+) -> Vec<SLPFrame<T>>
+where
+	SLPFrame<T>: UnpackFrameData<T>,
+{
+	let mut frame_datas = Vec::<SLPFrame<T>>::new();
+	for frame_info in frame_infos {
+		let mut frame = SLPFrame::<T>::from_buffer(bytes, frame_info);
+		if options.retain_frame_data {
+			frame.set_data(SLPFrameData::from_buffer(bytes, frame_info));
+		}
+		frame_datas.push(frame);
+	}
+
+	return frame_datas;
+}
+
+/// Decompress an SLP file with version 4.2P.
+///
+/// The version is followed by the decompressed size and an LZ4 block that
+/// contains an SLP 4.1X file.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the compressed SLP file.
+///
+/// # Returns
+///
+/// The bytes of the decompressed SLP file.
+///
+/// # Panics
+///
+/// Panics if the file is truncated or the compressed data is invalid.
+pub fn decompress_slp(bytes: &[u8]) -> Vec<u8> {
+	let size = u32::from_le_bytes(
+		bytes[SLP_VERSION_SIZE..SLP_COMPRESSED_HEADER_SIZE]
+			.try_into()
+			.unwrap(),
+	);
+
+	return decompress_block(&bytes[SLP_COMPRESSED_HEADER_SIZE..], size as usize);
+}
+
+/// Check that the bytes start with the header of an SLP file.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the file.
+///
+/// # Returns
+///
+/// Version of the SLP file.
+///
+/// # Errors
+///
+/// Returns an error if the file is not an SLP file or its header is
+/// truncated.
+pub fn check_slp_header(bytes: &[u8]) -> io::Result<SLPVersion> {
+	let Some(GraphicFormat::SLP(version)) = detect_format(bytes)
+	else {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"File is not an SLP file",
+		));
+	};
+
+	let header_size = if version == SLP_VERSION_4_2P {
+		SLP_COMPRESSED_HEADER_SIZE
+	}
+	else {
+		SLP_HEADER_SIZE
+	};
+	if bytes.len() < header_size {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"SLP file is truncated",
+		));
+	}
+
+	return Ok(version);
 }
 
 /// Parse a single SLP file.
 ///
+/// Compressed 4.2P files are decompressed first.
+///
 /// # Arguments
 ///
 /// * `bytes` - The bytes of the SLP file.
@@ -31,31 +282,107 @@ pub struct SLPFile {
 /// # Returns
 ///
 /// The parsed SLP file.
-pub fn parse_slp(bytes: Vec<u8>) -> SLPFile {
-	let header_data = SLPHeaderData::from_buffer(&bytes, 0);
-	let header = SLPHeader::from_data(header_data);
+pub fn parse_slp(bytes: &[u8]) -> SLPFile {
+	return parse_slp_with_options(bytes, SLPParseOptions::default());
+}
 
-	let mut frame_infos = Vec::<SLPFrameInfo>::new();
-	for i in 0..header.get_num_frames() {
-		let offset = SLP_HEADER_SIZE + (i as usize) * SLP_FRAME_INFO_SIZE;
-		let info_data = SLPFrameInfoData::from_buffer(&bytes, offset);
-		frame_infos.push(SLPFrameInfo::from_data(
-			info_data,
-			SLPFrameType::Main,
-			header.data.version,
-		));
+/// Parse a single SLP file with options.
+///
+/// Compressed 4.2P files are decompressed first.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the SLP file.
+/// * `options` - Parsing options.
+///
+/// # Returns
+///
+/// The parsed SLP file.
+///
+/// # Panics
+///
+/// Panics if the file is truncated.
+pub fn parse_slp_with_options(
+	bytes: &[u8],
+	options: SLPParseOptions,
+) -> SLPFile {
+	let version: SLPVersion = bytes[0..SLP_VERSION_SIZE].try_into().unwrap();
+	if version == SLP_VERSION_4_2P {
+		return parse_slp_with_options(&decompress_slp(bytes), options);
 	}
 
-	let mut frame_datas = Vec::<SLPFrame<PalettePixel>>::new();
-	for i in 0..header.get_num_frames() {
-		let frame_info = frame_infos.get(i as usize).unwrap();
-		let frame = SLPFrame::from_buffer(&bytes, frame_info);
-		frame_datas.push(frame);
+	let header = match version {
+		SLP_VERSION_4_0X | SLP_VERSION_4_1X => {
+			SLPHeader::from_data_v4(SLP4HeaderData::from_buffer(bytes, 0))
+		}
+		_ => SLPHeader::from_data(SLPHeaderData::from_buffer(bytes, 0)),
+	};
+
+	let main_offset = header
+		.data_v4
+		.map_or(SLP_HEADER_SIZE, |data_v4| data_v4.offset_main as usize);
+	let frame_infos = parse_frame_infos(
+		bytes,
+		main_offset,
+		header.get_num_frames(),
+		SLPFrameType::Main,
+		version,
+	);
+
+	let (frames, rgba_frames) = if frame_infos.first().is_some_and(SLPFrameInfo::is_32bit) {
+		(
+			Vec::new(),
+			decode_frames::<RGBAPixel>(bytes, &frame_infos, options),
+		)
 	}
+	else {
+		(
+			decode_frames::<PalettePixel>(bytes, &frame_infos, options),
+			Vec::new(),
+		)
+	};
+
+	let shadow_frame_infos = match header.data_v4 {
+		Some(data_v4) if data_v4.has_secondary_frames() => parse_frame_infos(
+			bytes,
+			data_v4.offset_secondary as usize,
+			header.get_num_frames(),
+			SLPFrameType::Shadow,
+			version,
+		),
+		_ => Vec::new(),
+	};
+	let shadow_frames = decode_frames::<PalettePixel>(bytes, &shadow_frame_infos, options);
 
 	SLPFile {
 		header,
 		frame_infos,
-		frames: frame_datas,
+		frames,
+		rgba_frames,
+		shadow_frame_infos,
+		shadow_frames,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::slp::frame::SLPFrame;
+	use crate::slp::header::SLP4HeaderData;
+	use crate::slp::header::SLPHeader;
+	use crate::slp::slp::SLPFile;
+
+	/// Test splitting palette and 32-bit frames into directions.
+	#[test]
+	fn direction_frames() {
+		let frame = SLPFrame::new(Vec::new(), Vec::new(), Vec::new());
+		let mut slp = SLPFile::from_palette_frames(vec![frame; 6]);
+		assert_eq!(slp.get_direction_frames(1, 3), 2..4);
+
+		// the header of 4.x files stores the directions
+		let data_v4 = SLP4HeaderData::new(*b"4.1X", 6, 0, 2, 3, 0, 32, 0, [0; 8]);
+		slp.header = SLPHeader::from_data_v4(data_v4);
+		slp.rgba_frames = vec![SLPFrame::new(Vec::new(), Vec::new(), Vec::new()); 6];
+		slp.frames = Vec::new();
+		assert_eq!(slp.get_direction_frames(1, 3), 3..6);
 	}
 }
@@ -0,0 +1,953 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::collections::HashMap;
+
+use crate::slp::definitions::SLP_CMD_BIG_DRAW;
+use crate::slp::definitions::SLP_CMD_BIG_SKIP;
+use crate::slp::definitions::SLP_CMD_END_OF_ROW;
+use crate::slp::definitions::SLP_CMD_FILL;
+use crate::slp::definitions::SLP_CMD_LESSER_DRAW;
+use crate::slp::definitions::SLP_CMD_LESSER_SKIP;
+use crate::slp::definitions::SLP_CMD_OUTLINE_1;
+use crate::slp::definitions::SLP_CMD_OUTLINE_1_MULTI;
+use crate::slp::definitions::SLP_CMD_OUTLINE_2;
+use crate::slp::definitions::SLP_CMD_OUTLINE_2_MULTI;
+use crate::slp::definitions::SLP_CMD_PLAYER_DRAW;
+use crate::slp::definitions::SLP_CMD_PLAYER_FILL;
+use crate::slp::definitions::SLP_CMD_SHADOW;
+use crate::slp::definitions::SLP_FRAME_BOUNDS_SIZE;
+use crate::slp::definitions::SLP_FRAME_CMD_OFFSET_SIZE;
+use crate::slp::definitions::SLP_FRAME_INFO_SIZE;
+use crate::slp::definitions::SLP_HEADER_SIZE;
+use crate::slp::definitions::SLP_ROW_TRANSPARENT;
+use crate::slp::frame::SLPFrame;
+use crate::slp::frame::SLPFrameData;
+use crate::slp::frame_info::SLPFrameInfo;
+use crate::slp::frame_info::SLPFrameInfoData;
+use crate::slp::pack::PackFixedSize;
+use crate::slp::pixel::PalettePixel;
+use crate::slp::pixel::RGBAPixel;
+use crate::slp::pixel::SLPPixelType;
+use crate::slp::row_bound::SLPRowBoundData;
+use crate::slp::slp::SLPFile;
+use crate::slp::types::SLPRowOffset;
+
+/// Largest count stored in the upper bits of a lesser draw or skip command.
+const LESSER_MAX_COUNT: usize = 0x3F;
+
+/// Largest count stored in the upper nibble of a command.
+const NIBBLE_MAX_COUNT: usize = 0x0F;
+
+/// Largest count stored in a byte following a command.
+const BYTE_MAX_COUNT: usize = 0xFF;
+
+/// Largest count of a big draw or skip command.
+const BIG_MAX_COUNT: usize = 0x0FFF;
+
+/// Opcodes and count limits for drawing a run of colors.
+#[derive(Debug, Clone, Copy)]
+struct ColorCommands {
+	/// Largest count stored in the draw command itself.
+	draw_small_max: usize,
+	/// Largest count of a draw command.
+	draw_max: usize,
+	/// Fill opcode.
+	fill: u8,
+}
+
+impl ColorCommands {
+	/// Commands for palette colors.
+	const PALETTE: Self = Self {
+		draw_small_max: LESSER_MAX_COUNT,
+		draw_max: BIG_MAX_COUNT,
+		fill: SLP_CMD_FILL,
+	};
+
+	/// Commands for player colors.
+	const PLAYER: Self = Self {
+		draw_small_max: NIBBLE_MAX_COUNT,
+		draw_max: BYTE_MAX_COUNT,
+		fill: SLP_CMD_PLAYER_FILL,
+	};
+
+	/// Write a draw command.
+	///
+	/// # Arguments
+	///
+	/// * `colors` - Colors to draw.
+	/// * `bytes` - Output buffer.
+	#[allow(clippy::cast_possible_truncation)]
+	fn write_draw<const N: usize>(
+		self,
+		colors: &[[u8; N]],
+		bytes: &mut Vec<u8>,
+	) {
+		let count = colors.len();
+		if self.fill == SLP_CMD_FILL {
+			if count <= LESSER_MAX_COUNT {
+				bytes.push(SLP_CMD_LESSER_DRAW | (count as u8) << 2);
+			}
+			else {
+				bytes.push(SLP_CMD_BIG_DRAW | ((count >> 8) as u8) << 4);
+				bytes.push(count as u8);
+			}
+		}
+		else {
+			write_nibble_count(SLP_CMD_PLAYER_DRAW, count, bytes);
+		}
+		bytes.extend_from_slice(colors.as_flattened());
+	}
+
+	/// Write a fill command.
+	///
+	/// # Arguments
+	///
+	/// * `color` - Color to fill with.
+	/// * `count` - Number of pixels.
+	/// * `bytes` - Output buffer.
+	fn write_fill<const N: usize>(
+		self,
+		color: [u8; N],
+		count: usize,
+		bytes: &mut Vec<u8>,
+	) {
+		write_nibble_count(self.fill, count, bytes);
+		bytes.extend_from_slice(&color);
+	}
+}
+
+/// Write a command whose count is stored in its upper nibble or, if it does
+/// not fit, in the next byte.
+///
+/// # Arguments
+///
+/// * `cmd` - Command opcode.
+/// * `count` - Number of pixels, at most 255.
+/// * `bytes` - Output buffer.
+#[allow(clippy::cast_possible_truncation)]
+fn write_nibble_count(
+	cmd: u8,
+	count: usize,
+	bytes: &mut Vec<u8>,
+) {
+	if count <= NIBBLE_MAX_COUNT {
+		bytes.push(cmd | (count as u8) << 4);
+	}
+	else {
+		bytes.push(cmd);
+		bytes.push(count as u8);
+	}
+}
+
+/// Size of a command with its count in the upper nibble or the next byte.
+///
+/// # Arguments
+///
+/// * `count` - Number of pixels.
+///
+/// # Returns
+///
+/// Size of the command without pixel data.
+const fn get_nibble_count_size(count: usize) -> usize {
+	if count <= NIBBLE_MAX_COUNT {
+		return 1;
+	}
+
+	return 2;
+}
+
+/// Encode a run of colors with the fewest bytes.
+///
+/// Each prefix of the run is encoded with the smallest combination of draw
+/// and fill commands ending at it. Draws cost their colors plus one or two
+/// command bytes. Fills cost a single color plus one or two command bytes,
+/// but only cover pixels with the same color.
+///
+/// # Arguments
+///
+/// * `colors` - Colors of the run, either palette indices or BGRA colors.
+/// * `commands` - Opcodes used for the run.
+/// * `bytes` - Output buffer.
+fn encode_color_run<const N: usize>(
+	colors: &[[u8; N]],
+	commands: ColorCommands,
+	bytes: &mut Vec<u8>,
+) {
+	for chunk in colors.chunks(commands.draw_max) {
+		let len = chunk.len();
+		// cost of the prefix and the start and kind of its last command
+		let mut cost = vec![0usize; len + 1];
+		let mut last = vec![(0usize, false); len + 1];
+		// start of the cheapest big draw ending at the current pixel
+		let mut big_start: Option<usize> = None;
+		let mut same_start = 0;
+		for end in 1..=len {
+			if end >= 2 && chunk[end - 1] != chunk[end - 2] {
+				same_start = end - 1;
+			}
+			if end > commands.draw_small_max {
+				let start = end - commands.draw_small_max - 1;
+				if big_start.is_none_or(|best| cost[start] + best * N < cost[best] + start * N) {
+					big_start = Some(start);
+				}
+			}
+
+			let mut best = (usize::MAX, (0, false));
+			let small_start = end.saturating_sub(commands.draw_small_max);
+			for (start, prefix) in cost.iter().enumerate().take(end).skip(small_start) {
+				let candidate = prefix + (end - start) * N + 1;
+				if candidate < best.0 {
+					best = (candidate, (start, false));
+				}
+			}
+			if let Some(start) = big_start {
+				let candidate = cost[start] + (end - start) * N + 2;
+				if candidate < best.0 {
+					best = (candidate, (start, false));
+				}
+			}
+			let fill_start = same_start.max(end.saturating_sub(BYTE_MAX_COUNT));
+			for (start, prefix) in cost.iter().enumerate().take(end).skip(fill_start) {
+				let candidate = prefix + get_nibble_count_size(end - start) + N;
+				if candidate < best.0 {
+					best = (candidate, (start, true));
+				}
+			}
+
+			cost[end] = best.0;
+			last[end] = best.1;
+		}
+
+		let mut runs = Vec::<(usize, usize, bool)>::new();
+		let mut end = len;
+		while end > 0 {
+			let (start, fill) = last[end];
+			runs.push((start, end, fill));
+			end = start;
+		}
+		for (start, end, fill) in runs.into_iter().rev() {
+			if fill {
+				commands.write_fill(chunk[start], end - start, bytes);
+			}
+			else {
+				commands.write_draw(&chunk[start..end], bytes);
+			}
+		}
+	}
+}
+
+/// Encode a run of pixels that have no color data.
+///
+/// # Arguments
+///
+/// * `pixel_type` - Type of the pixels.
+/// * `count` - Number of pixels.
+/// * `bytes` - Output buffer.
+#[allow(clippy::cast_possible_truncation)]
+fn encode_plain_run(
+	pixel_type: SLPPixelType,
+	count: usize,
+	bytes: &mut Vec<u8>,
+) {
+	let mut remaining = count;
+	while remaining > 0 {
+		let (single, multi) = match pixel_type {
+			SLPPixelType::Transparent => {
+				let chunk = remaining.min(BIG_MAX_COUNT);
+				if chunk <= LESSER_MAX_COUNT {
+					bytes.push(SLP_CMD_LESSER_SKIP | (chunk as u8) << 2);
+				}
+				else {
+					bytes.push(SLP_CMD_BIG_SKIP | ((chunk >> 8) as u8) << 4);
+					bytes.push(chunk as u8);
+				}
+				remaining -= chunk;
+				continue;
+			}
+			SLPPixelType::Shadow => {
+				let chunk = remaining.min(BYTE_MAX_COUNT);
+				write_nibble_count(SLP_CMD_SHADOW, chunk, bytes);
+				remaining -= chunk;
+				continue;
+			}
+			SLPPixelType::Special1 => (SLP_CMD_OUTLINE_1, SLP_CMD_OUTLINE_1_MULTI),
+			_ => (SLP_CMD_OUTLINE_2, SLP_CMD_OUTLINE_2_MULTI),
+		};
+
+		let chunk = remaining.min(BYTE_MAX_COUNT);
+		if chunk == 1 {
+			bytes.push(single);
+		}
+		else {
+			bytes.push(multi);
+			bytes.push(chunk as u8);
+		}
+		remaining -= chunk;
+	}
+}
+
+/// Encode rows of pixels to row commands.
+pub trait EncodeRow: Sized {
+	/// Number of bytes per color in the row commands.
+	const COLOR_SIZE: usize;
+
+	/// Encode the pixels of a row to row commands.
+	///
+	/// Transparent pixels at the row borders are stored in the row bounds,
+	/// so only the pixels between them are encoded. The commands end with an
+	/// end of row command.
+	///
+	/// # Arguments
+	///
+	/// * `row` - Pixels of the row.
+	///
+	/// # Returns
+	///
+	/// Row bounds and row commands.
+	fn encode_row(row: &[Self]) -> (SLPRowBoundData, Vec<u8>);
+}
+
+/// Encode the pixels of a row in runs of pixels using the same commands.
+///
+/// # Arguments
+///
+/// * `row` - Pixels of the row.
+/// * `get_class` - Get the pixel type whose commands encode a pixel.
+/// * `encode_run` - Encode a run of pixels with the same class.
+///
+/// # Returns
+///
+/// Row bounds and row commands.
+///
+/// # Panics
+///
+/// Panics if a border is wider than the row bounds can store.
+fn encode_row_runs<T, C, E>(
+	row: &[T],
+	get_class: C,
+	encode_run: E,
+) -> (SLPRowBoundData, Vec<u8>)
+where
+	C: Fn(&T) -> SLPPixelType,
+	E: Fn(SLPPixelType, &[T], &mut Vec<u8>),
+{
+	let is_transparent = |pixel: &T| get_class(pixel) == SLPPixelType::Transparent;
+	let Some(first) = row.iter().position(|pixel| !is_transparent(pixel))
+	else {
+		let bounds = SLPRowBoundData::new(SLP_ROW_TRANSPARENT, SLP_ROW_TRANSPARENT);
+		return (bounds, vec![SLP_CMD_END_OF_ROW]);
+	};
+	let last = row
+		.iter()
+		.rposition(|pixel| !is_transparent(pixel))
+		.unwrap_or(first);
+
+	let bounds = SLPRowBoundData::new(
+		u16::try_from(first).unwrap(),
+		u16::try_from(row.len() - last - 1).unwrap(),
+	);
+
+	let mut bytes = Vec::<u8>::new();
+	let inner = &row[first..=last];
+	let mut start = 0;
+	while start < inner.len() {
+		let class = get_class(&inner[start]);
+		let end = inner[start..]
+			.iter()
+			.position(|pixel| get_class(pixel) != class)
+			.map_or(inner.len(), |len| start + len);
+
+		encode_run(class, &inner[start..end], &mut bytes);
+		start = end;
+	}
+	bytes.push(SLP_CMD_END_OF_ROW);
+
+	return (bounds, bytes);
+}
+
+/// Encode the pixels of a palette row to row commands.
+///
+/// Shadow frames of SLP 4.x store shadow intensities as palette colors and
+/// 4.x player colors use the regular player commands.
+///
+/// # Arguments
+///
+/// * `row` - Pixels of the row.
+///
+/// # Returns
+///
+/// Row bounds and row commands.
+pub fn encode_row(row: &[PalettePixel]) -> (SLPRowBoundData, Vec<u8>) {
+	let get_class = |pixel: &PalettePixel| match pixel.pixel_type {
+		SLPPixelType::Palette | SLPPixelType::ShadowV4 => SLPPixelType::Palette,
+		SLPPixelType::Player | SLPPixelType::PlayerV4 => SLPPixelType::Player,
+		other => other,
+	};
+
+	return encode_row_runs(row, get_class, |class, run, bytes| {
+		let indices = run
+			.iter()
+			.map(|pixel| [pixel.index])
+			.collect::<Vec<[u8; 1]>>();
+		match class {
+			SLPPixelType::Palette => encode_color_run(&indices, ColorCommands::PALETTE, bytes),
+			SLPPixelType::Player => encode_color_run(&indices, ColorCommands::PLAYER, bytes),
+			_ => encode_plain_run(class, run.len(), bytes),
+		}
+	});
+}
+
+/// Encode the pixels of a 32-bit row to row commands.
+///
+/// Colors are stored as 4 bytes in BGRA order with straight alpha. Player
+/// colors store their palette index from the red component.
+///
+/// # Arguments
+///
+/// * `row` - Pixels of the row.
+///
+/// # Returns
+///
+/// Row bounds and row commands.
+pub fn encode_rgba_row(row: &[RGBAPixel]) -> (SLPRowBoundData, Vec<u8>) {
+	let get_class = |pixel: &RGBAPixel| match pixel.pixel_type {
+		SLPPixelType::Shadow | SLPPixelType::ShadowV4 => SLPPixelType::Shadow,
+		SLPPixelType::Player | SLPPixelType::PlayerV4 => SLPPixelType::Player,
+		other => other,
+	};
+
+	return encode_row_runs(row, get_class, |class, run, bytes| match class {
+		SLPPixelType::Palette => {
+			let colors = run
+				.iter()
+				.map(|pixel| [pixel.b, pixel.g, pixel.r, pixel.a])
+				.collect::<Vec<[u8; 4]>>();
+			encode_color_run(&colors, ColorCommands::PALETTE, bytes);
+		}
+		SLPPixelType::Player => {
+			let indices = run.iter().map(|pixel| [pixel.r]).collect::<Vec<[u8; 1]>>();
+			encode_color_run(&indices, ColorCommands::PLAYER, bytes);
+		}
+		_ => encode_plain_run(class, run.len(), bytes),
+	});
+}
+
+impl EncodeRow for PalettePixel {
+	const COLOR_SIZE: usize = 1;
+
+	fn encode_row(row: &[Self]) -> (SLPRowBoundData, Vec<u8>) {
+		return encode_row(row);
+	}
+}
+
+impl EncodeRow for RGBAPixel {
+	const COLOR_SIZE: usize = 4;
+
+	fn encode_row(row: &[Self]) -> (SLPRowBoundData, Vec<u8>) {
+		return encode_rgba_row(row);
+	}
+}
+
+/// Compute the command table for rows stored behind the frame tables.
+///
+/// # Arguments
+///
+/// * `row_data` - Commands of each row.
+/// * `offset` - Offset of the bounds table in the file.
+///
+/// # Returns
+///
+/// Offsets of the first command of each row.
+///
+/// # Panics
+///
+/// Panics if the offsets exceed the 32-bit range.
+fn get_cmd_table(
+	row_data: &[Vec<u8>],
+	offset: usize,
+) -> Vec<SLPRowOffset> {
+	let height = row_data.len();
+	let mut row_offset = offset + height * (SLP_FRAME_BOUNDS_SIZE + SLP_FRAME_CMD_OFFSET_SIZE);
+	let mut cmd_table = Vec::<SLPRowOffset>::with_capacity(height);
+	for row in row_data {
+		cmd_table.push(u32::try_from(row_offset).unwrap());
+		row_offset += row.len();
+	}
+
+	return cmd_table;
+}
+
+/// Encode the pixels of a frame to frame data.
+///
+/// The bounds table is followed by the command table and the row commands.
+///
+/// # Arguments
+///
+/// * `frame` - Frame to encode.
+/// * `offset` - Offset of the bounds table in the file.
+///
+/// # Returns
+///
+/// Frame data with command offsets relative to the start of the file.
+///
+/// # Panics
+///
+/// Panics if the frame data exceeds the 32-bit offset range.
+pub fn encode_frame_data<T: EncodeRow>(
+	frame: &SLPFrame<T>,
+	offset: usize,
+) -> SLPFrameData {
+	let (bounds_table, row_data): (Vec<SLPRowBoundData>, Vec<Vec<u8>>) = frame
+		.get_pixels()
+		.iter()
+		.map(|row| T::encode_row(row))
+		.unzip();
+	let cmd_table = get_cmd_table(&row_data, offset);
+
+	return SLPFrameData::new(bounds_table, cmd_table, row_data);
+}
+
+/// Get the frame data of a frame for storing it at an offset.
+///
+/// Frames with raw data kept from parsing store their rows unchanged. Other
+/// frames are encoded from their pixels.
+///
+/// # Arguments
+///
+/// * `frame` - Frame to store.
+/// * `offset` - Offset of the bounds table in the file.
+///
+/// # Returns
+///
+/// Frame data with command offsets relative to the start of the file.
+pub fn get_frame_data<T: EncodeRow>(
+	frame: &SLPFrame<T>,
+	offset: usize,
+) -> SLPFrameData {
+	return frame.get_data().map_or_else(
+		|| encode_frame_data(frame, offset),
+		|data| {
+			SLPFrameData::new(
+				data.get_bounds_table().to_vec(),
+				get_cmd_table(data.get_row_data(), offset),
+				data.get_row_data().to_vec(),
+			)
+		},
+	);
+}
+
+/// Append frame data to the bytes of a file.
+///
+/// # Arguments
+///
+/// * `data` - Frame data.
+/// * `bytes` - Bytes of the file.
+pub fn write_frame_data(
+	data: &SLPFrameData,
+	bytes: &mut Vec<u8>,
+) {
+	for bounds in data.get_bounds_table() {
+		bytes.extend_from_slice(&bounds.to_bytes());
+	}
+	for offset in data.get_cmd_table() {
+		bytes.extend_from_slice(&offset.to_le_bytes());
+	}
+	for row in data.get_row_data() {
+		bytes.extend_from_slice(row);
+	}
+}
+
+/// Create the frame info data for an encoded frame.
+///
+/// Palette offset, properties and anchor are kept from the original frame
+/// info.
+///
+/// # Arguments
+///
+/// * `frame` - Encoded frame.
+/// * `frame_info` - Original frame info.
+/// * `offset` - Offset of the bounds table in the file.
+///
+/// # Returns
+///
+/// Frame info data pointing to the encoded frame.
+///
+/// # Panics
+///
+/// Panics if the frame is too large or the offset exceeds the 32-bit range.
+pub fn get_frame_info_data<T>(
+	frame: &SLPFrame<T>,
+	frame_info: &SLPFrameInfo,
+	offset: usize,
+) -> SLPFrameInfoData {
+	let height = frame.get_height();
+
+	return SLPFrameInfoData::new(
+		u32::try_from(offset + height * SLP_FRAME_BOUNDS_SIZE).unwrap(),
+		u32::try_from(offset).unwrap(),
+		frame_info.data.palette_offset,
+		frame_info.data.properties,
+		i32::try_from(frame.get_width()).unwrap(),
+		i32::try_from(height).unwrap(),
+		frame_info.data.anchor_x,
+		frame_info.data.anchor_y,
+	);
+}
+
+/// Layout of the frame data in a file.
+///
+/// Frames are stored one after another. If data is shared, rows with the
+/// same commands are stored once and referenced by all frames using them.
+/// Frames with the same bounds and rows also share their bounds and command
+/// tables.
+#[derive(Debug, Clone)]
+pub struct FrameLayout {
+	/// Offset of the first frame in the file.
+	start: usize,
+	/// Bytes of the frame data.
+	bytes: Vec<u8>,
+	/// Share identical rows and frames.
+	share_data: bool,
+	/// Offsets of stored rows by color size and commands.
+	rows: HashMap<(usize, Vec<u8>), SLPRowOffset>,
+	/// Command and bounds table offsets of stored frames by color size,
+	/// bounds and row commands.
+	frames: HashMap<(usize, Vec<u8>), (u32, u32)>,
+}
+
+impl FrameLayout {
+	/// Create an empty frame layout.
+	///
+	/// # Arguments
+	///
+	/// * `start` - Offset of the first frame in the file.
+	/// * `share_data` - Share identical rows and frames.
+	///
+	/// # Returns
+	///
+	/// New frame layout.
+	pub fn new(
+		start: usize,
+		share_data: bool,
+	) -> Self {
+		return Self {
+			start,
+			bytes: Vec::new(),
+			share_data,
+			rows: HashMap::new(),
+			frames: HashMap::new(),
+		};
+	}
+
+	/// Get the offset behind the stored frame data.
+	///
+	/// # Returns
+	///
+	/// Offset in the file.
+	pub const fn get_offset(&self) -> usize {
+		return self.start + self.bytes.len();
+	}
+
+	/// Get the bytes of the stored frame data.
+	///
+	/// # Returns
+	///
+	/// Frame data starting at the offset of the first frame.
+	pub fn get_bytes(&self) -> &[u8] {
+		return &self.bytes;
+	}
+
+	/// Store a frame.
+	///
+	/// # Arguments
+	///
+	/// * `frame` - Frame to store.
+	/// * `frame_info` - Original frame info of the frame.
+	///
+	/// # Returns
+	///
+	/// Frame info data pointing to the stored frame.
+	///
+	/// # Panics
+	///
+	/// Panics if the frame data exceeds the 32-bit offset range.
+	pub fn add_frame<T: EncodeRow>(
+		&mut self,
+		frame: &SLPFrame<T>,
+		frame_info: &SLPFrameInfo,
+	) -> SLPFrameInfoData {
+		let offset = self.get_offset();
+		let data = get_frame_data(frame, offset);
+		let mut info = get_frame_info_data(frame, frame_info, offset);
+		if !self.share_data {
+			write_frame_data(&data, &mut self.bytes);
+			return info;
+		}
+
+		let mut frame_key = Vec::<u8>::new();
+		for bounds in data.get_bounds_table() {
+			frame_key.extend_from_slice(&bounds.to_bytes());
+		}
+		for row in data.get_row_data() {
+			frame_key.extend_from_slice(&u32::try_from(row.len()).unwrap().to_le_bytes());
+			frame_key.extend_from_slice(row);
+		}
+		let frame_key = (T::COLOR_SIZE, frame_key);
+		if let Some(&(cmd_table_offset, bounds_table_offset)) = self.frames.get(&frame_key) {
+			info.cmd_table_offset = cmd_table_offset;
+			info.bounds_table_offset = bounds_table_offset;
+			return info;
+		}
+
+		let num_rows = data.get_row_data().len();
+		let mut row_offset =
+			offset + num_rows * (SLP_FRAME_BOUNDS_SIZE + SLP_FRAME_CMD_OFFSET_SIZE);
+		let mut cmd_table = Vec::<SLPRowOffset>::with_capacity(num_rows);
+		let mut new_rows = Vec::<&Vec<u8>>::new();
+		for row in data.get_row_data() {
+			let row_key = (T::COLOR_SIZE, row.clone());
+			let stored = *self.rows.entry(row_key).or_insert_with(|| {
+				let stored = u32::try_from(row_offset).unwrap();
+				row_offset += row.len();
+				new_rows.push(row);
+				stored
+			});
+			cmd_table.push(stored);
+		}
+
+		for bounds in data.get_bounds_table() {
+			self.bytes.extend_from_slice(&bounds.to_bytes());
+		}
+		for row_offset in &cmd_table {
+			self.bytes.extend_from_slice(&row_offset.to_le_bytes());
+		}
+		for row in new_rows {
+			self.bytes.extend_from_slice(row);
+		}
+
+		let _ = self
+			.frames
+			.insert(frame_key, (info.cmd_table_offset, info.bounds_table_offset));
+		return info;
+	}
+
+	/// Store the frames of a frame table.
+	///
+	/// # Arguments
+	///
+	/// * `frames` - Frames to store.
+	/// * `frame_infos` - Original frame infos of the frames.
+	///
+	/// # Returns
+	///
+	/// Frame info data of each frame.
+	///
+	/// # Panics
+	///
+	/// Panics if a frame info is missing for a frame.
+	pub fn add_frames<T: EncodeRow>(
+		&mut self,
+		frames: &[SLPFrame<T>],
+		frame_infos: &[SLPFrameInfo],
+	) -> Vec<SLPFrameInfoData> {
+		assert!(
+			frame_infos.len() == frames.len(),
+			"Expected {} frame infos, found {}",
+			frames.len(),
+			frame_infos.len()
+		);
+
+		return frames
+			.iter()
+			.zip(frame_infos)
+			.map(|(frame, frame_info)| self.add_frame(frame, frame_info))
+			.collect();
+	}
+}
+
+/// Options for writing SLP files with the 2.0N layout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SLPWriterOptions {
+	/// Store identical rows and frames only once.
+	pub share_data: bool,
+}
+
+/// Encode an SLP file with the 2.0N layout.
+///
+/// The header is followed by the frame info table and the data of each
+/// frame. Version, comment and the frame info properties are kept from the
+/// file. Row bounds are computed from the transparent pixels at the row
+/// borders. Frames with raw data kept from parsing are stored unchanged, so
+/// only edited frames are encoded again.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file to encode.
+///
+/// # Returns
+///
+/// Bytes of the SLP file.
+///
+/// # Panics
+///
+/// Panics if the file has an SLP 4.x header, 32-bit frames or a frame info
+/// is missing for a frame.
+pub fn encode_slp(slp: &SLPFile) -> Vec<u8> {
+	return encode_slp_with_options(slp, SLPWriterOptions::default());
+}
+
+/// Encode an SLP file with the 2.0N layout and options.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file to encode.
+/// * `options` - Writer options.
+///
+/// # Returns
+///
+/// Bytes of the SLP file.
+///
+/// # Panics
+///
+/// Panics if the file has an SLP 4.x header, 32-bit frames or a frame info
+/// is missing for a frame.
+pub fn encode_slp_with_options(
+	slp: &SLPFile,
+	options: SLPWriterOptions,
+) -> Vec<u8> {
+	assert!(
+		slp.header.data_v4.is_none() && slp.rgba_frames.is_empty(),
+		"Only SLP files with the 2.0N layout and palette frames can be encoded"
+	);
+
+	let mut header = slp.header.data;
+	header.num_frames = u32::try_from(slp.frames.len()).unwrap();
+
+	let offset = SLP_HEADER_SIZE + slp.frames.len() * SLP_FRAME_INFO_SIZE;
+	let mut layout = FrameLayout::new(offset, options.share_data);
+	let frame_infos = layout.add_frames(&slp.frames, &slp.frame_infos);
+
+	let mut bytes = header.to_bytes();
+	for frame_info in &frame_infos {
+		bytes.extend_from_slice(&frame_info.to_bytes());
+	}
+	bytes.extend_from_slice(layout.get_bytes());
+
+	return bytes;
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::slp::frame::SLPFrame;
+	use crate::slp::header::SLPHeader;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::SLPPixelType;
+	use crate::slp::slp::parse_slp;
+	use crate::slp::slp::parse_slp_with_options;
+	use crate::slp::slp::SLPFile;
+	use crate::slp::slp::SLPParseOptions;
+	use crate::slp::writer::encode_row;
+	use crate::slp::writer::encode_slp;
+
+	/// Test that rows are encoded with the smallest commands.
+	#[test]
+	fn encode_row_commands() {
+		let palette = |index| PalettePixel::new(SLPPixelType::Palette, index);
+		let mut row = vec![PalettePixel::new(SLPPixelType::Transparent, 0); 2];
+		row.extend([palette(1), palette(2)]);
+		row.extend([palette(5); 20]);
+		row.push(PalettePixel::new(SLPPixelType::Player, 3));
+		row.extend([PalettePixel::new(SLPPixelType::Shadow, 0); 3]);
+		row.push(PalettePixel::new(SLPPixelType::Special1, 0));
+		row.extend([PalettePixel::new(SLPPixelType::Transparent, 0); 70]);
+		row.extend([PalettePixel::new(SLPPixelType::Special2, 0); 2]);
+		row.push(PalettePixel::new(SLPPixelType::Transparent, 0));
+
+		let (bounds, bytes) = encode_row(&row);
+		assert_eq!((bounds.left, bounds.right), (2, 1));
+		assert_eq!(
+			bytes,
+			[
+				0x08, 1, 2, // lesser draw
+				0x07, 20, 5, // fill
+				0x16, 3,    // player draw
+				0x3B, // shadow
+				0x4E, // outline 1
+				0x03, 70, // big skip
+				0x7E, 2, // outline 2 multi
+				0x0F
+			]
+		);
+
+		let (bounds, bytes) = encode_row(&[PalettePixel::new(SLPPixelType::Transparent, 0); 4]);
+		assert_eq!((bounds.left, bounds.right), (0x8000, 0x8000));
+		assert_eq!(bytes, [0x0F]);
+	}
+
+	/// Test that an encoded SLP file decodes to the same pixels.
+	#[test]
+	fn write_and_read_slp() {
+		let width = 300;
+		let mut rows = Vec::<Vec<PalettePixel>>::new();
+		rows.push(vec![PalettePixel::new(SLPPixelType::Transparent, 0); width]);
+		rows.push(
+			(0..width)
+				.map(|x| PalettePixel::new(SLPPixelType::Palette, u8::try_from(x % 7).unwrap()))
+				.collect(),
+		);
+		let mut row = vec![PalettePixel::new(SLPPixelType::Player, 4); 20];
+		row.resize(width - 1, PalettePixel::new(SLPPixelType::Transparent, 0));
+		row.push(PalettePixel::new(SLPPixelType::Palette, 9));
+		rows.push(row);
+
+		let mut slp =
+			SLPFile::from_palette_frames(vec![SLPFrame::new(Vec::new(), Vec::new(), rows.clone())]);
+		slp.header = SLPHeader::new(*b"2.0N", 1, *b"ArtDesk 1.00 SLP Writer\0");
+		slp.frame_infos[0].data.properties = 0x10;
+		slp.frame_infos[0].data.anchor_x = -5;
+		slp.frame_infos[0].data.anchor_y = 7;
+
+		let bytes = encode_slp(&slp);
+		let decoded = parse_slp(&bytes);
+		assert_eq!(decoded.header.get_comment(), "ArtDesk 1.00 SLP Writer\0");
+		assert_eq!(decoded.frame_infos[0].data.properties, 0x10);
+		assert_eq!(decoded.frame_infos[0].data.anchor_x, -5);
+		assert_eq!(decoded.frames[0].get_pixels(), rows.as_slice());
+
+		// encoding is stable
+		assert_eq!(encode_slp(&decoded), bytes);
+	}
+
+	/// Test that unmodified frames are stored byte for byte.
+	#[test]
+	fn repack_retained_frames() {
+		let mut bytes = b"2.0N".to_vec();
+		bytes.extend_from_slice(&2u32.to_le_bytes());
+		bytes.extend_from_slice(&[0; 24]);
+		for (offset, anchor) in [(96u32, 1i32), (110, 2)] {
+			bytes.extend_from_slice(&(offset + 4).to_le_bytes());
+			bytes.extend_from_slice(&offset.to_le_bytes());
+			bytes.extend_from_slice(&[0; 8]);
+			bytes.extend_from_slice(&4i32.to_le_bytes());
+			bytes.extend_from_slice(&1i32.to_le_bytes());
+			bytes.extend_from_slice(&anchor.to_le_bytes());
+			bytes.extend_from_slice(&0i32.to_le_bytes());
+		}
+		// same color drawn 4 times, a fill would be smaller
+		bytes.extend_from_slice(&[0, 0, 0, 0, 104, 0, 0, 0, 0x10, 5, 5, 5, 5, 0x0F]);
+		bytes.extend_from_slice(&[0, 0, 0, 0, 118, 0, 0, 0, 0x10, 6, 6, 6, 6, 0x0F]);
+
+		let options = SLPParseOptions {
+			retain_frame_data: true,
+		};
+		let mut slp = parse_slp_with_options(&bytes, options);
+		assert_eq!(encode_slp(&slp), bytes);
+
+		// only the edited frame changes
+		let pixels = slp.frames[1].get_pixels().to_vec();
+		slp.frames[1] = SLPFrame::new(Vec::new(), Vec::new(), pixels);
+		let repacked = encode_slp(&slp);
+		assert_eq!(repacked[..118], bytes[..118]);
+		assert_eq!(repacked[118..], [0x47, 6, 0x0F]);
+	}
+}
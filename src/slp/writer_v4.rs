@@ -0,0 +1,265 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::slp::definitions::SLP_COMPRESSED_HEADER_SIZE;
+use crate::slp::definitions::SLP_FRAME_INFO_SIZE;
+use crate::slp::definitions::SLP_FRAME_PROPERTY_32BIT;
+use crate::slp::definitions::SLP_HEADER_SIZE;
+use crate::slp::definitions::SLP_VERSION_4_1X;
+use crate::slp::definitions::SLP_VERSION_4_2P;
+use crate::slp::header::SLP4HeaderData;
+use crate::slp::pack::PackFixedSize;
+use crate::slp::slp::SLPFile;
+use crate::slp::types::SLPVersion;
+use crate::slp::writer::FrameLayout;
+use crate::util::lz4::compress_block;
+
+/// Options for writing SLP 4.x files.
+#[derive(Debug, Clone, Copy)]
+pub struct SLP4WriterOptions {
+	/// SLP version of the uncompressed file, 4.0X or 4.1X.
+	pub version: SLPVersion,
+	/// Frame type flags.
+	pub frame_type: u16,
+	/// Number of stored animation directions. The frames are split evenly
+	/// between them.
+	pub num_directions: u16,
+	/// ID of the palette used by the frames.
+	pub palette_id: u32,
+	/// Compress the file with LZ4 and store it as version 4.2P.
+	pub compress: bool,
+	/// Store identical rows and frames only once.
+	pub share_data: bool,
+}
+
+impl SLP4WriterOptions {
+	/// Create writer options that keep the values of an SLP 4.x header.
+	///
+	/// # Arguments
+	///
+	/// * `data_v4` - SLP 4.x header data.
+	///
+	/// # Returns
+	///
+	/// Writer options without compression.
+	pub const fn from_header(data_v4: &SLP4HeaderData) -> Self {
+		Self {
+			version: data_v4.version,
+			frame_type: data_v4.frame_type,
+			num_directions: data_v4.num_directions,
+			palette_id: data_v4.palette_id,
+			compress: false,
+			share_data: false,
+		}
+	}
+}
+
+impl Default for SLP4WriterOptions {
+	fn default() -> Self {
+		Self {
+			version: SLP_VERSION_4_1X,
+			frame_type: 0,
+			num_directions: 1,
+			palette_id: 0,
+			compress: false,
+			share_data: false,
+		}
+	}
+}
+
+/// Compress an SLP 4.x file to version 4.2P.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the uncompressed SLP file.
+///
+/// # Returns
+///
+/// The bytes of the compressed SLP file.
+///
+/// # Panics
+///
+/// Panics if the file is larger than 4 GiB.
+pub fn compress_slp(bytes: &[u8]) -> Vec<u8> {
+	let block = compress_block(bytes);
+
+	let mut compressed = Vec::<u8>::with_capacity(SLP_COMPRESSED_HEADER_SIZE + block.len());
+	compressed.extend_from_slice(&SLP_VERSION_4_2P);
+	compressed.extend_from_slice(&u32::try_from(bytes.len()).unwrap().to_le_bytes());
+	compressed.extend_from_slice(&block);
+
+	return compressed;
+}
+
+/// Encode an SLP file with the 4.x layout.
+///
+/// The header is followed by the main frame info table, the secondary
+/// (shadow) frame info table if there are shadow frames and the data of the
+/// main and shadow frames. Main frames are either palette or 32-bit frames.
+/// The 32-bit property flags of the main frame infos are set accordingly.
+///
+/// # Arguments
+///
+/// * `slp` - SLP file to encode.
+/// * `options` - Writer options.
+///
+/// # Returns
+///
+/// Bytes of the SLP file.
+///
+/// # Panics
+///
+/// Panics if the file has both palette and 32-bit frames, the number of
+/// shadow frames does not match the main frames, the frames cannot be split
+/// evenly between the directions or there are more than 65535 frames.
+pub fn encode_slp4(
+	slp: &SLPFile,
+	options: SLP4WriterOptions,
+) -> Vec<u8> {
+	assert!(
+		slp.frames.is_empty() || slp.rgba_frames.is_empty(),
+		"SLP files store either palette or 32-bit main frames"
+	);
+	let num_frames = slp.frames.len().max(slp.rgba_frames.len());
+	let has_shadows = !slp.shadow_frames.is_empty();
+	assert!(
+		!has_shadows || slp.shadow_frames.len() == num_frames,
+		"Expected {} shadow frames, found {}",
+		num_frames,
+		slp.shadow_frames.len()
+	);
+	assert!(
+		options.num_directions > 0
+			&& num_frames.is_multiple_of(usize::from(options.num_directions)),
+		"{} frames cannot be split between {} directions",
+		num_frames,
+		options.num_directions
+	);
+
+	let table_size = num_frames * SLP_FRAME_INFO_SIZE;
+	let offset_secondary = if has_shadows {
+		SLP_HEADER_SIZE + table_size
+	}
+	else {
+		0
+	};
+	let offset = SLP_HEADER_SIZE + table_size * (1 + usize::from(has_shadows));
+
+	let mut layout = FrameLayout::new(offset, options.share_data);
+	let mut main_infos = if slp.rgba_frames.is_empty() {
+		layout.add_frames(&slp.frames, &slp.frame_infos)
+	}
+	else {
+		layout.add_frames(&slp.rgba_frames, &slp.frame_infos)
+	};
+	for info in &mut main_infos {
+		if slp.rgba_frames.is_empty() {
+			info.properties &= !SLP_FRAME_PROPERTY_32BIT;
+		}
+		else {
+			info.properties |= SLP_FRAME_PROPERTY_32BIT;
+		}
+	}
+	let shadow_infos = layout.add_frames(&slp.shadow_frames, &slp.shadow_frame_infos);
+
+	let num_frames = u16::try_from(num_frames).unwrap();
+	let header = SLP4HeaderData::new(
+		options.version,
+		num_frames,
+		options.frame_type,
+		options.num_directions,
+		num_frames / options.num_directions,
+		options.palette_id,
+		u32::try_from(SLP_HEADER_SIZE).unwrap(),
+		u32::try_from(offset_secondary).unwrap(),
+		[0; 8],
+	);
+
+	let mut bytes = header.to_bytes();
+	for info in main_infos.iter().chain(&shadow_infos) {
+		bytes.extend_from_slice(&info.to_bytes());
+	}
+	bytes.extend_from_slice(layout.get_bytes());
+
+	if options.compress {
+		return compress_slp(&bytes);
+	}
+
+	return bytes;
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::slp::frame::SLPFrame;
+	use crate::slp::frame_info::SLPFrameInfo;
+	use crate::slp::frame_info::SLPFrameType;
+	use crate::slp::header::SLPHeader;
+	use crate::slp::pixel::PalettePixel;
+	use crate::slp::pixel::RGBAPixel;
+	use crate::slp::pixel::SLPPixelType;
+	use crate::slp::slp::parse_slp;
+	use crate::slp::slp::SLPFile;
+	use crate::slp::writer_v4::encode_slp4;
+	use crate::slp::writer_v4::SLP4WriterOptions;
+
+	/// Test that 32-bit frames with shadow frames survive encoding.
+	#[test]
+	fn write_and_read_slp4() {
+		let transparent = RGBAPixel::new(SLPPixelType::Transparent, 0, 0, 0, 0);
+		let color = RGBAPixel::new(SLPPixelType::Palette, 200, 100, 50, 128);
+		let rows = vec![
+			vec![transparent, color, color, color, color, transparent],
+			vec![
+				RGBAPixel::new(SLPPixelType::PlayerV4, 3, 0, 0, 255),
+				RGBAPixel::new(SLPPixelType::ShadowV4, 0, 0, 0, 255),
+				RGBAPixel::new(SLPPixelType::Special1, 0, 0, 0, 255),
+				RGBAPixel::new(SLPPixelType::Palette, 1, 2, 3, 255),
+				transparent,
+				transparent,
+			],
+		];
+		let shadow_rows = vec![vec![
+			PalettePixel::new(SLPPixelType::Transparent, 0),
+			PalettePixel::new(SLPPixelType::ShadowV4, 40),
+		]];
+
+		let frame_info = |frame_type, width, height| {
+			SLPFrameInfo::new(0, 0, 0, 0, width, height, 3, 4, frame_type, *b"4.1X")
+		};
+		let slp = SLPFile {
+			header: SLPHeader::new(*b"4.1X", 2, [0; 24]),
+			frame_infos: vec![frame_info(SLPFrameType::Main, 6, 2); 2],
+			frames: Vec::new(),
+			rgba_frames: vec![SLPFrame::new(Vec::new(), Vec::new(), rows.clone()); 2],
+			shadow_frame_infos: vec![frame_info(SLPFrameType::Shadow, 2, 1); 2],
+			shadow_frames: vec![SLPFrame::new(Vec::new(), Vec::new(), shadow_rows.clone()); 2],
+		};
+		let options = SLP4WriterOptions {
+			num_directions: 2,
+			palette_id: 55,
+			..Default::default()
+		};
+
+		for compress in [false, true] {
+			let bytes = encode_slp4(
+				&slp,
+				SLP4WriterOptions {
+					compress,
+					..options
+				},
+			);
+			assert_eq!(&bytes[0..4], if compress { b"4.2P" } else { b"4.1X" });
+
+			let decoded = parse_slp(&bytes);
+			let data_v4 = decoded.header.data_v4.unwrap();
+			assert_eq!(data_v4.num_directions, 2);
+			assert_eq!(data_v4.frames_per_direction, 1);
+			assert_eq!(data_v4.palette_id, 55);
+			assert!(decoded.frame_infos[0].is_32bit());
+			assert_eq!(decoded.rgba_frames[1].get_pixels(), rows.as_slice());
+			assert_eq!(
+				decoded.shadow_frames[1].get_pixels(),
+				shadow_rows.as_slice()
+			);
+		}
+	}
+}
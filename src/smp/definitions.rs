@@ -0,0 +1,37 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+/// Signature at the start of SMP files.
+pub const SMP_SIGNATURE: &[u8; 4] = b"SMP$";
+
+/// Size of the SMP header.
+pub const SMP_HEADER_SIZE: usize = 64;
+
+/// Size of a frame offset in the frame offset table.
+pub const SMP_FRAME_OFFSET_SIZE: usize = 4;
+
+/// Size of the layer count at the start of a frame.
+pub const SMP_LAYER_COUNT_SIZE: usize = 4;
+
+/// Size of a layer info.
+pub const SMP_LAYER_INFO_SIZE: usize = 32;
+
+/// Size of a row bound in the outline table.
+pub const SMP_ROW_BOUNDS_SIZE: usize = 4;
+
+/// Size of a row offset in the command table.
+pub const SMP_CMD_OFFSET_SIZE: usize = 4;
+
+/// Row bound value marking a completely transparent row.
+pub const SMP_TRANSPARENT_ROW: u16 = 0xFFFF;
+
+/// Size of a main layer pixel.
+pub const SMP_PIXEL_SIZE: usize = 4;
+
+/// Layer type flag of main graphics layers.
+pub const SMP_LAYER_TYPE_MAIN: u32 = 0x02;
+
+/// Layer type flag of shadow layers.
+pub const SMP_LAYER_TYPE_SHADOW: u32 = 0x04;
+
+/// Layer type flag of outline layers.
+pub const SMP_LAYER_TYPE_OUTLINE: u32 = 0x08;
@@ -0,0 +1,283 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::slp::frame::SLPFrame;
+use crate::slp::pixel::PalettePixel;
+use crate::slp::pixel::SLPPixelType;
+use crate::slp::row_bound::SLPRowBound;
+use crate::slp::types::SLPRowOffset;
+use crate::slp::unpack::UnpackFixedSize;
+use crate::smp::definitions::SMP_CMD_OFFSET_SIZE;
+use crate::smp::definitions::SMP_LAYER_COUNT_SIZE;
+use crate::smp::definitions::SMP_LAYER_INFO_SIZE;
+use crate::smp::definitions::SMP_PIXEL_SIZE;
+use crate::smp::definitions::SMP_ROW_BOUNDS_SIZE;
+use crate::smp::definitions::SMP_TRANSPARENT_ROW;
+use crate::smp::layer::SMPLayerInfo;
+use crate::smp::layer::SMPLayerType;
+use crate::smp::pixel::SMPPixel;
+
+/// Layer of a layered sprite frame.
+#[derive(Debug, Clone)]
+pub struct SMPLayer {
+	/// Layer info.
+	pub info: SMPLayerInfo,
+	/// Decoded layer pixels.
+	pub frame: SLPFrame<SMPPixel>,
+}
+
+impl SMPLayer {
+	/// Get the type of the layer.
+	///
+	/// # Returns
+	///
+	/// Layer type or `None` if the type flag is unknown.
+	pub const fn get_layer_type(&self) -> Option<SMPLayerType> {
+		return self.info.get_layer_type();
+	}
+
+	/// Convert the layer pixels to palette pixels.
+	///
+	/// # Returns
+	///
+	/// Rows of palette pixels.
+	pub fn to_palette_pixels(&self) -> Vec<Vec<PalettePixel>> {
+		return self
+			.frame
+			.get_pixels()
+			.iter()
+			.map(|row| row.iter().map(|pixel| pixel.to_palette_pixel()).collect())
+			.collect();
+	}
+}
+
+/// Frame in an SMP file, consisting of one or more layers.
+#[derive(Debug, Clone)]
+pub struct SMPFrame {
+	/// Layers of the frame.
+	pub layers: Vec<SMPLayer>,
+}
+
+impl SMPFrame {
+	/// Get a layer of the frame.
+	///
+	/// # Arguments
+	///
+	/// * `layer_type` - Type of the layer.
+	///
+	/// # Returns
+	///
+	/// Layer or `None` if the frame has no layer of the type.
+	pub fn get_layer(
+		&self,
+		layer_type: SMPLayerType,
+	) -> Option<&SMPLayer> {
+		return self
+			.layers
+			.iter()
+			.find(|layer| layer.get_layer_type() == Some(layer_type));
+	}
+}
+
+/// Decode a row bound of an SMP layer.
+///
+/// SMP marks transparent rows with `0xFFFF` instead of `0x8000`.
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer to read from.
+/// * `offset` - Offset of the row bound in the buffer.
+///
+/// # Returns
+///
+/// Row bound.
+pub fn decode_row_bound(
+	buffer: &[u8],
+	offset: usize,
+) -> SLPRowBound {
+	let left = u16::from_le_bytes([buffer[offset], buffer[offset + 1]]);
+	let right = u16::from_le_bytes([buffer[offset + 2], buffer[offset + 3]]);
+
+	if left == SMP_TRANSPARENT_ROW || right == SMP_TRANSPARENT_ROW {
+		return SLPRowBound::new(0, 0, true);
+	}
+
+	return SLPRowBound::new(left, right, false);
+}
+
+/// Decode the draw commands of a single layer row.
+///
+/// Every command byte stores the opcode in the lower 2 bits and the pixel
+/// count minus one in the upper 6 bits:
+///
+/// * `0b00`: skip pixels
+/// * `0b01`: draw pixels
+/// * `0b10`: draw player color pixels
+/// * `0b11`: end of row
+///
+/// Main layer pixels use 4 bytes (index, palette section, 2 damage
+/// modifiers), shadow pixels 1 byte (intensity) and outline pixels carry no
+/// data.
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer to read from.
+/// * `first_cmd_offset` - Offset of the row's first command in the buffer.
+/// * `layer_type` - Type of the layer.
+///
+/// # Returns
+///
+/// Decoded pixels for the row commands.
+fn decode_row_cmds(
+	buffer: &[u8],
+	first_cmd_offset: usize,
+	layer_type: SMPLayerType,
+) -> Vec<SMPPixel> {
+	let mut pixels = Vec::<SMPPixel>::new();
+	let mut dpos = first_cmd_offset;
+
+	loop {
+		let cmd = buffer[dpos];
+		dpos += 1;
+		let count = (cmd >> 2) as usize + 1;
+
+		match cmd & 0b11 {
+			0b00 => pixels.resize(pixels.len() + count, SMPPixel::transparent()),
+			opcode @ (0b01 | 0b10) => {
+				for _ in 0..count {
+					let pixel = match layer_type {
+						SMPLayerType::Main => {
+							let pixel_type = if opcode == 0b10 {
+								SLPPixelType::Player
+							}
+							else {
+								SLPPixelType::Palette
+							};
+							let pixel = SMPPixel::new(
+								pixel_type,
+								buffer[dpos],
+								buffer[dpos + 1],
+								[buffer[dpos + 2], buffer[dpos + 3]],
+							);
+							dpos += SMP_PIXEL_SIZE;
+							pixel
+						}
+						SMPLayerType::Shadow => {
+							let pixel =
+								SMPPixel::new(SLPPixelType::ShadowV4, buffer[dpos], 0, [0, 0]);
+							dpos += 1;
+							pixel
+						}
+						SMPLayerType::Outline => {
+							SMPPixel::new(SLPPixelType::Special1, 0, 0, [0, 0])
+						}
+					};
+					pixels.push(pixel);
+				}
+			}
+			_ => break,
+		}
+	}
+
+	return pixels;
+}
+
+/// Decode the pixels of a layer.
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer to read from.
+/// * `frame_offset` - Offset of the frame in the buffer. Table offsets in the
+///   layer info are relative to this offset.
+/// * `info` - Layer info.
+///
+/// # Returns
+///
+/// Decoded layer.
+///
+/// # Panics
+///
+/// Panics if the layer type is unknown.
+pub fn decode_layer(
+	buffer: &[u8],
+	frame_offset: usize,
+	info: &SMPLayerInfo,
+) -> SLPFrame<SMPPixel> {
+	let layer_type = info
+		.get_layer_type()
+		.unwrap_or_else(|| panic!("Unknown SMP layer type {:#x}", info.layer_type));
+	let width = info.width as usize;
+	let height = info.height as usize;
+
+	let bounds_table = (0..height)
+		.map(|i| {
+			decode_row_bound(
+				buffer,
+				frame_offset + info.bounds_table_offset as usize + i * SMP_ROW_BOUNDS_SIZE,
+			)
+		})
+		.collect::<Vec<SLPRowBound>>();
+
+	let cmd_table = (0..height)
+		.map(|i| {
+			let offset = frame_offset + info.cmd_table_offset as usize + i * SMP_CMD_OFFSET_SIZE;
+			u32::from_le_bytes(buffer[offset..offset + 4].try_into().unwrap())
+		})
+		.collect::<Vec<SLPRowOffset>>();
+
+	let mut row_data = Vec::<Vec<SMPPixel>>::with_capacity(height);
+	for (bounds, row_offset) in bounds_table.iter().zip(&cmd_table) {
+		let mut row = Vec::<SMPPixel>::with_capacity(width);
+		if !bounds.full_row {
+			row.resize(bounds.get_left() as usize, SMPPixel::transparent());
+			row.append(&mut decode_row_cmds(
+				buffer,
+				frame_offset + *row_offset as usize,
+				layer_type,
+			));
+		}
+		row.resize(width, SMPPixel::transparent());
+		row_data.push(row);
+	}
+
+	return SLPFrame::new(bounds_table, cmd_table, row_data);
+}
+
+/// Decode a frame with all of its layers.
+///
+/// The frame starts with the number of layers, followed by the layer infos.
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer to read from.
+/// * `frame_offset` - Offset of the frame in the buffer.
+///
+/// # Returns
+///
+/// Decoded frame.
+///
+/// # Panics
+///
+/// Panics if a layer type is unknown.
+pub fn decode_frame(
+	buffer: &[u8],
+	frame_offset: usize,
+) -> SMPFrame {
+	let num_layers = u32::from_le_bytes(
+		buffer[frame_offset..frame_offset + SMP_LAYER_COUNT_SIZE]
+			.try_into()
+			.unwrap(),
+	);
+
+	let layers = (0..num_layers as usize)
+		.map(|i| {
+			let info = SMPLayerInfo::from_buffer(
+				buffer,
+				frame_offset + SMP_LAYER_COUNT_SIZE + i * SMP_LAYER_INFO_SIZE,
+			);
+			let frame = decode_layer(buffer, frame_offset, &info);
+			SMPLayer { info, frame }
+		})
+		.collect();
+
+	return SMPFrame { layers };
+}
@@ -0,0 +1,92 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::fmt;
+
+use crate::slp::unpack::UnpackFixedSize;
+use crate::smp::definitions::SMP_HEADER_SIZE;
+
+/// Header in an SMP file.
+#[derive(Debug, Clone, Copy)]
+pub struct SMPHeader {
+	/// File signature, always `SMP$`.
+	pub signature: [u8; 4],
+	/// Format version.
+	pub version: u32,
+	/// Number of frames.
+	pub num_frames: u32,
+	/// Number of facets (directions).
+	pub num_facets: u32,
+	/// Number of frames per facet.
+	pub frames_per_facet: u32,
+	/// Checksum of the file.
+	pub checksum: u32,
+	/// Size of the file.
+	pub file_size: u32,
+	/// Format of the source graphics.
+	pub source_format: u32,
+	/// Comment string.
+	pub comment: [u8; 32],
+}
+
+impl SMPHeader {
+	/// Get the comment string.
+	///
+	/// # Returns
+	///
+	/// Comment string up to the first null byte.
+	pub fn get_comment(&self) -> String {
+		let end = self
+			.comment
+			.iter()
+			.position(|&b| b == 0)
+			.unwrap_or(self.comment.len());
+
+		return String::from_utf8_lossy(&self.comment[..end]).to_string();
+	}
+}
+
+impl UnpackFixedSize for SMPHeader {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + SMP_HEADER_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		let read_u32 = |pos: usize| u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+
+		return Self {
+			signature: bytes[0..4].try_into().unwrap(),
+			version: read_u32(4),
+			num_frames: read_u32(8),
+			num_facets: read_u32(12),
+			frames_per_facet: read_u32(16),
+			checksum: read_u32(20),
+			file_size: read_u32(24),
+			source_format: read_u32(28),
+			comment: bytes[32..64].try_into().unwrap(),
+		};
+	}
+}
+
+impl fmt::Display for SMPHeader {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"version: {:#x}\nnum_frames: {}\nnum_facets: {}\nframes_per_facet: {}\nchecksum: \
+			 {:#x}\nfile_size: {}\nsource_format: {}\ncomment: {}",
+			self.version,
+			self.num_frames,
+			self.num_facets,
+			self.frames_per_facet,
+			self.checksum,
+			self.file_size,
+			self.source_format,
+			self.get_comment()
+		)
+	}
+}
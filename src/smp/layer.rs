@@ -0,0 +1,118 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::fmt;
+
+use crate::slp::unpack::UnpackFixedSize;
+use crate::smp::definitions::SMP_LAYER_INFO_SIZE;
+use crate::smp::definitions::SMP_LAYER_TYPE_MAIN;
+use crate::smp::definitions::SMP_LAYER_TYPE_OUTLINE;
+use crate::smp::definitions::SMP_LAYER_TYPE_SHADOW;
+
+/// Type of a layer in a layered sprite frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SMPLayerType {
+	/// Main graphics with palette and player colors.
+	Main,
+	/// Shadow intensities.
+	Shadow,
+	/// Outline drawn when the object is hidden.
+	Outline,
+}
+
+impl SMPLayerType {
+	/// Get the layer type from a layer type flag.
+	///
+	/// # Arguments
+	///
+	/// * `flag` - Layer type flag.
+	///
+	/// # Returns
+	///
+	/// Layer type or `None` if the flag is unknown.
+	pub const fn from_flag(flag: u32) -> Option<Self> {
+		match flag {
+			SMP_LAYER_TYPE_MAIN => Some(Self::Main),
+			SMP_LAYER_TYPE_SHADOW => Some(Self::Shadow),
+			SMP_LAYER_TYPE_OUTLINE => Some(Self::Outline),
+			_ => None,
+		}
+	}
+}
+
+/// Info about a layer of an SMP frame.
+#[derive(Debug, Clone, Copy)]
+pub struct SMPLayerInfo {
+	/// Width of the layer.
+	pub width: u32,
+	/// Height of the layer.
+	pub height: u32,
+	/// X coordinate of the anchor point.
+	pub anchor_x: i32,
+	/// Y coordinate of the anchor point.
+	pub anchor_y: i32,
+	/// Layer type flag.
+	pub layer_type: u32,
+	/// Offset of the row bounds table, relative to the frame.
+	pub bounds_table_offset: u32,
+	/// Offset of the command table, relative to the frame.
+	pub cmd_table_offset: u32,
+	/// Layer flags.
+	pub flags: u32,
+}
+
+impl SMPLayerInfo {
+	/// Get the type of the layer.
+	///
+	/// # Returns
+	///
+	/// Layer type or `None` if the type flag is unknown.
+	pub const fn get_layer_type(&self) -> Option<SMPLayerType> {
+		return SMPLayerType::from_flag(self.layer_type);
+	}
+}
+
+impl UnpackFixedSize for SMPLayerInfo {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + SMP_LAYER_INFO_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		let read_u32 = |pos: usize| u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+		let read_int = |pos: usize| i32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap());
+
+		return Self {
+			width: read_u32(0),
+			height: read_u32(4),
+			anchor_x: read_int(8),
+			anchor_y: read_int(12),
+			layer_type: read_u32(16),
+			bounds_table_offset: read_u32(20),
+			cmd_table_offset: read_u32(24),
+			flags: read_u32(28),
+		};
+	}
+}
+
+impl fmt::Display for SMPLayerInfo {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"width: {}\nheight: {}\nanchor_x: {}\nanchor_y: {}\nlayer_type: \
+			 {:#x}\nbounds_table_offset: {:#x}\ncmd_table_offset: {:#x}\nflags: {:#x}",
+			self.width,
+			self.height,
+			self.anchor_x,
+			self.anchor_y,
+			self.layer_type,
+			self.bounds_table_offset,
+			self.cmd_table_offset,
+			self.flags
+		)
+	}
+}
@@ -0,0 +1,84 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::collections::HashMap;
+
+use crate::slp::pixel::PalettePixel;
+use crate::slp::pixel::SLPPixelType;
+use crate::util::pixel::RGBAConvertible;
+
+/// Pixel in a layered sprite frame (SMP and SMX).
+///
+/// Main layer pixels reference a color in one of the palette sections
+/// of the graphic. Shadow layer pixels store the shadow intensity in
+/// `index`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SMPPixel {
+	/// Pixel type
+	pub pixel_type: SLPPixelType,
+	/// Palette index
+	pub index: u8,
+	/// Palette section of the color
+	pub section: u8,
+	/// Damage modifiers
+	pub damage_modifier: [u8; 2],
+}
+
+impl SMPPixel {
+	/// Create a new pixel.
+	///
+	/// # Arguments
+	///
+	/// * `pixel_type` - Pixel type
+	/// * `index` - Palette index
+	/// * `section` - Palette section of the color
+	/// * `damage_modifier` - Damage modifiers
+	///
+	/// # Returns
+	///
+	/// New pixel.
+	pub const fn new(
+		pixel_type: SLPPixelType,
+		index: u8,
+		section: u8,
+		damage_modifier: [u8; 2],
+	) -> Self {
+		Self {
+			pixel_type,
+			index,
+			section,
+			damage_modifier,
+		}
+	}
+
+	/// Create a new transparent pixel.
+	///
+	/// # Returns
+	///
+	/// New transparent pixel.
+	pub const fn transparent() -> Self {
+		return Self::new(SLPPixelType::Transparent, 0, 0, [0, 0]);
+	}
+
+	/// Convert the pixel to a palette pixel.
+	///
+	/// The palette section and damage modifiers are dropped.
+	///
+	/// # Returns
+	///
+	/// Palette pixel with the same type and index.
+	pub const fn to_palette_pixel(self) -> PalettePixel {
+		return PalettePixel {
+			pixel_type: self.pixel_type,
+			index: self.index,
+		};
+	}
+}
+
+impl RGBAConvertible for SMPPixel {
+	fn to_rgba(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+	) -> [u8; 4] {
+		return self.to_palette_pixel().to_rgba(lookup);
+	}
+}
@@ -0,0 +1,135 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::io;
+
+use crate::slp::unpack::UnpackFixedSize;
+use crate::smp::definitions::SMP_FRAME_OFFSET_SIZE;
+use crate::smp::definitions::SMP_HEADER_SIZE;
+use crate::smp::definitions::SMP_SIGNATURE;
+use crate::smp::frame::decode_frame;
+use crate::smp::frame::SMPFrame;
+use crate::smp::header::SMPHeader;
+
+/// SMP file.
+#[derive(Debug, Clone)]
+pub struct SMPFile {
+	/// SMP header.
+	pub header: SMPHeader,
+	/// Offsets of the frames in the file.
+	pub frame_offsets: Vec<u32>,
+	/// Layered frames.
+	pub frames: Vec<SMPFrame>,
+}
+
+/// Parse an SMP file.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the SMP file.
+///
+/// # Returns
+///
+/// The parsed SMP file.
+///
+/// # Errors
+///
+/// Returns an error if the file does not start with the SMP signature or
+/// the frame offset table is truncated.
+///
+/// # Panics
+///
+/// Panics if the frame data is truncated.
+pub fn parse_smp(bytes: &[u8]) -> io::Result<SMPFile> {
+	if bytes.len() < SMP_HEADER_SIZE || &bytes[0..4] != SMP_SIGNATURE {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"File is not an SMP file",
+		));
+	}
+	let header = SMPHeader::from_buffer(bytes, 0);
+	if bytes.len() < SMP_HEADER_SIZE + header.num_frames as usize * SMP_FRAME_OFFSET_SIZE {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"SMP frame offset table is truncated",
+		));
+	}
+
+	let frame_offsets = (0..header.num_frames as usize)
+		.map(|i| {
+			let offset = SMP_HEADER_SIZE + i * SMP_FRAME_OFFSET_SIZE;
+			u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+		})
+		.collect::<Vec<u32>>();
+
+	let frames = frame_offsets
+		.iter()
+		.map(|&offset| decode_frame(bytes, offset as usize))
+		.collect();
+
+	return Ok(SMPFile {
+		header,
+		frame_offsets,
+		frames,
+	});
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::slp::pixel::SLPPixelType;
+	use crate::smp::layer::SMPLayerType;
+	use crate::smp::smp::parse_smp;
+
+	/// Test decoding the main and shadow layers of a single frame.
+	#[test]
+	fn decode_smp_layers() {
+		let mut bytes = b"SMP$".to_vec();
+		for value in [0x0B, 1, 1, 1, 0, 0, 0] {
+			bytes.extend_from_slice(&u32::to_le_bytes(value));
+		}
+		bytes.resize(64, 0);
+		// frame offset
+		bytes.extend_from_slice(&68u32.to_le_bytes());
+
+		// frame at 68: 2 layers, layer infos are 32 bytes each
+		let mut frame = 2u32.to_le_bytes().to_vec();
+		for (layer_type, bounds, cmds) in [(0x02u32, 68u32, 76u32), (0x04, 84, 92)] {
+			for value in [3u32, 2, 1, 1, layer_type, bounds, cmds, 0] {
+				frame.extend_from_slice(&value.to_le_bytes());
+			}
+		}
+		// main layer: row 0 starts after 1 pixel, row 1 is transparent
+		frame.extend_from_slice(&[1, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]);
+		frame.extend_from_slice(&100u32.to_le_bytes());
+		frame.extend_from_slice(&0u32.to_le_bytes());
+		// shadow layer: row 0 transparent, row 1 has 3 pixels
+		frame.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0]);
+		frame.extend_from_slice(&0u32.to_le_bytes());
+		frame.extend_from_slice(&111u32.to_le_bytes());
+		frame.resize(100, 0);
+		// main row 0: draw 1 pixel, player color 1 pixel, end of row
+		frame.extend_from_slice(&[0x01, 7, 2, 9, 8, 0x02, 3, 0, 0, 0, 0x03]);
+		// shadow row 1: skip 1, draw 2 intensities, end of row
+		frame.extend_from_slice(&[0x00, 0x05, 40, 80, 0x03]);
+		bytes.extend_from_slice(&frame);
+
+		let smp = parse_smp(&bytes).unwrap();
+		assert_eq!(&smp.header.signature, b"SMP$");
+		assert_eq!(smp.frames.len(), 1);
+
+		let main = smp.frames[0].get_layer(SMPLayerType::Main).unwrap();
+		let row = &main.frame.get_pixels()[0];
+		assert_eq!(row[0].pixel_type, SLPPixelType::Transparent);
+		assert_eq!((row[1].index, row[1].section), (7, 2));
+		assert_eq!(row[1].damage_modifier, [9, 8]);
+		assert_eq!(row[2].pixel_type, SLPPixelType::Player);
+		assert!(main.frame.get_pixels()[1]
+			.iter()
+			.all(|p| p.pixel_type == SLPPixelType::Transparent));
+
+		let shadow = smp.frames[0].get_layer(SMPLayerType::Shadow).unwrap();
+		let row = &shadow.frame.get_pixels()[1];
+		assert_eq!(row[0].pixel_type, SLPPixelType::Transparent);
+		assert_eq!((row[1].index, row[2].index), (40, 80));
+		assert!(smp.frames[0].get_layer(SMPLayerType::Outline).is_none());
+	}
+}
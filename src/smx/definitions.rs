@@ -0,0 +1,40 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+/// Signature at the start of SMX files.
+pub const SMX_SIGNATURE: &[u8; 4] = b"SMPX";
+
+/// Size of the SMX header.
+pub const SMX_HEADER_SIZE: usize = 32;
+
+/// Size of a frame header.
+pub const SMX_FRAME_HEADER_SIZE: usize = 6;
+
+/// Size of a layer header.
+pub const SMX_LAYER_HEADER_SIZE: usize = 16;
+
+/// Size of a row bound in the outline table.
+pub const SMX_ROW_BOUNDS_SIZE: usize = 4;
+
+/// Frame type flag for frames with a main graphics layer.
+pub const SMX_FRAME_MAIN: u8 = 0x01;
+
+/// Frame type flag for frames with a shadow layer.
+pub const SMX_FRAME_SHADOW: u8 = 0x02;
+
+/// Frame type flag for frames with an outline layer.
+pub const SMX_FRAME_OUTLINE: u8 = 0x04;
+
+/// Frame type flag for main layers using 8to5 instead of 4plus1 packing.
+pub const SMX_FRAME_8TO5: u8 = 0x08;
+
+/// Number of pixels in a 4plus1 block.
+pub const SMX_4PLUS1_PIXELS: usize = 4;
+
+/// Size of a 4plus1 block.
+pub const SMX_4PLUS1_SIZE: usize = 5;
+
+/// Number of pixels in an 8to5 block.
+pub const SMX_8TO5_PIXELS: usize = 2;
+
+/// Size of an 8to5 block.
+pub const SMX_8TO5_SIZE: usize = 5;
@@ -0,0 +1,332 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::slp::frame::SLPFrame;
+use crate::slp::pixel::SLPPixelType;
+use crate::slp::row_bound::SLPRowBound;
+use crate::slp::types::SLPRowOffset;
+use crate::slp::unpack::UnpackFixedSize;
+use crate::smp::frame::decode_row_bound;
+use crate::smp::layer::SMPLayerType;
+use crate::smp::pixel::SMPPixel;
+use crate::smx::definitions::SMX_4PLUS1_PIXELS;
+use crate::smx::definitions::SMX_4PLUS1_SIZE;
+use crate::smx::definitions::SMX_8TO5_PIXELS;
+use crate::smx::definitions::SMX_8TO5_SIZE;
+use crate::smx::definitions::SMX_FRAME_HEADER_SIZE;
+use crate::smx::definitions::SMX_LAYER_HEADER_SIZE;
+use crate::smx::definitions::SMX_ROW_BOUNDS_SIZE;
+use crate::smx::header::SMXFrameHeader;
+use crate::smx::layer::SMXLayerInfo;
+
+/// Layer of an SMX frame.
+#[derive(Debug, Clone)]
+pub struct SMXLayer {
+	/// Type of the layer.
+	pub layer_type: SMPLayerType,
+	/// Layer header.
+	pub info: SMXLayerInfo,
+	/// Decoded layer pixels. The command table stores the offset of every
+	/// row in the layer's command stream.
+	pub frame: SLPFrame<SMPPixel>,
+}
+
+/// Frame in an SMX file, consisting of one or more layers.
+#[derive(Debug, Clone)]
+pub struct SMXFrame {
+	/// Frame header.
+	pub header: SMXFrameHeader,
+	/// Layers of the frame in the order main, shadow, outline.
+	pub layers: Vec<SMXLayer>,
+}
+
+impl SMXFrame {
+	/// Get a layer of the frame.
+	///
+	/// # Arguments
+	///
+	/// * `layer_type` - Type of the layer.
+	///
+	/// # Returns
+	///
+	/// Layer or `None` if the frame has no layer of the type.
+	pub fn get_layer(
+		&self,
+		layer_type: SMPLayerType,
+	) -> Option<&SMXLayer> {
+		return self
+			.layers
+			.iter()
+			.find(|layer| layer.layer_type == layer_type);
+	}
+}
+
+/// Unpack the pixel stream of a main layer with 4plus1 packing.
+///
+/// Each block stores the palette indices of 4 pixels followed by one byte
+/// with the 2-bit palette sections of the pixels.
+///
+/// # Arguments
+///
+/// * `data` - Pixel stream.
+///
+/// # Returns
+///
+/// Unpacked pixels. The pixel type is set when the pixels are drawn.
+fn unpack_4plus1(data: &[u8]) -> Vec<SMPPixel> {
+	let mut pixels =
+		Vec::<SMPPixel>::with_capacity(data.len() / SMX_4PLUS1_SIZE * SMX_4PLUS1_PIXELS);
+	for block in data.chunks_exact(SMX_4PLUS1_SIZE) {
+		let sections = block[4];
+		for (i, &index) in block[..SMX_4PLUS1_PIXELS].iter().enumerate() {
+			let section = (sections >> (2 * i)) & 0b11;
+			pixels.push(SMPPixel::new(SLPPixelType::Palette, index, section, [0, 0]));
+		}
+	}
+
+	return pixels;
+}
+
+/// Unpack the pixel stream of a main layer with 8to5 packing.
+///
+/// Each block packs 2 pixels of 20 bits. A pixel stores the palette index
+/// (8 bits), the palette section (2 bits), the first damage modifier
+/// (8 bits) and the second damage modifier (2 bits).
+///
+/// # Arguments
+///
+/// * `data` - Pixel stream.
+///
+/// # Returns
+///
+/// Unpacked pixels. The pixel type is set when the pixels are drawn.
+// all fields are masked to at most 8 bits
+#[allow(clippy::cast_possible_truncation)]
+fn unpack_8to5(data: &[u8]) -> Vec<SMPPixel> {
+	let mut pixels = Vec::<SMPPixel>::with_capacity(data.len() / SMX_8TO5_SIZE * SMX_8TO5_PIXELS);
+	for block in data.chunks_exact(SMX_8TO5_SIZE) {
+		let mut bits = [0; 8];
+		bits[..SMX_8TO5_SIZE].copy_from_slice(block);
+		let bits = u64::from_le_bytes(bits);
+
+		for i in 0..SMX_8TO5_PIXELS {
+			let value = bits >> (20 * i);
+			let field = |shift: u32, mask: u64| ((value >> shift) & mask) as u8;
+			pixels.push(SMPPixel::new(
+				SLPPixelType::Palette,
+				field(0, 0xFF),
+				field(8, 0b11),
+				[field(10, 0xFF), field(18, 0b11)],
+			));
+		}
+	}
+
+	return pixels;
+}
+
+/// Decode the rows of a layer from its command stream.
+///
+/// Every command byte stores the opcode in the lower 2 bits and the pixel
+/// count minus one in the upper 6 bits:
+///
+/// * `0b00`: skip pixels
+/// * `0b01`: draw pixels
+/// * `0b10`: draw player color pixels
+/// * `0b11`: end of row
+///
+/// Transparent rows have no commands.
+///
+/// # Arguments
+///
+/// * `cmds` - Command stream.
+/// * `bounds_table` - Row bounds of the layer.
+/// * `width` - Width of the layer.
+/// * `draw` - Function returning the next drawn pixel for an opcode. It gets
+///   the current position in the command stream for pixel data that is stored
+///   between the commands.
+///
+/// # Returns
+///
+/// Offsets of the rows in the command stream and the decoded rows.
+fn decode_rows<F>(
+	cmds: &[u8],
+	bounds_table: &[SLPRowBound],
+	width: usize,
+	mut draw: F,
+) -> (Vec<SLPRowOffset>, Vec<Vec<SMPPixel>>)
+where
+	F: FnMut(u8, &mut usize) -> SMPPixel,
+{
+	let mut cmd_table = Vec::<SLPRowOffset>::with_capacity(bounds_table.len());
+	let mut row_data = Vec::<Vec<SMPPixel>>::with_capacity(bounds_table.len());
+
+	let mut dpos = 0;
+	for bounds in bounds_table {
+		cmd_table.push(u32::try_from(dpos).unwrap());
+
+		let mut row = Vec::<SMPPixel>::with_capacity(width);
+		if !bounds.full_row {
+			row.resize(bounds.get_left() as usize, SMPPixel::transparent());
+			loop {
+				let cmd = cmds[dpos];
+				dpos += 1;
+				let count = (cmd >> 2) as usize + 1;
+
+				match cmd & 0b11 {
+					0b00 => row.resize(row.len() + count, SMPPixel::transparent()),
+					opcode @ (0b01 | 0b10) => {
+						for _ in 0..count {
+							row.push(draw(opcode, &mut dpos));
+						}
+					}
+					_ => break,
+				}
+			}
+		}
+		row.resize(width, SMPPixel::transparent());
+		row_data.push(row);
+	}
+
+	return (cmd_table, row_data);
+}
+
+/// Read a little endian `u32` length field.
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer to read from.
+/// * `offset` - Offset of the field.
+///
+/// # Returns
+///
+/// Value of the field.
+fn read_length(
+	buffer: &[u8],
+	offset: usize,
+) -> usize {
+	return u32::from_le_bytes(buffer[offset..offset + 4].try_into().unwrap()) as usize;
+}
+
+/// Decode a layer of an SMX frame.
+///
+/// The layer header is followed by the row bounds. Main layers then store
+/// the lengths of the command and pixel streams and both streams. Shadow
+/// and outline layers store a single stream with the shadow intensities
+/// between the commands.
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer to read from.
+/// * `offset` - Offset of the layer header.
+/// * `layer_type` - Type of the layer.
+/// * `is_8to5` - true if the main layer uses 8to5 packing.
+///
+/// # Returns
+///
+/// Decoded layer and the offset after the layer data.
+///
+/// # Panics
+///
+/// Panics if the pixel stream has fewer pixels than the commands draw.
+pub fn decode_layer(
+	buffer: &[u8],
+	offset: usize,
+	layer_type: SMPLayerType,
+	is_8to5: bool,
+) -> (SMXLayer, usize) {
+	let info = SMXLayerInfo::from_buffer(buffer, offset);
+	let width = info.width as usize;
+	let height = info.height as usize;
+
+	let mut pos = offset + SMX_LAYER_HEADER_SIZE;
+	let bounds_table = (0..height)
+		.map(|i| decode_row_bound(buffer, pos + i * SMX_ROW_BOUNDS_SIZE))
+		.collect::<Vec<SLPRowBound>>();
+	pos += height * SMX_ROW_BOUNDS_SIZE;
+
+	let (cmd_table, row_data) = match layer_type {
+		SMPLayerType::Main => {
+			let cmd_length = read_length(buffer, pos);
+			let pixel_length = read_length(buffer, pos + 4);
+			pos += 8;
+
+			let cmds = &buffer[pos..pos + cmd_length];
+			pos += cmd_length;
+			let pixel_data = &buffer[pos..pos + pixel_length];
+			pos += pixel_length;
+
+			let unpacked = if is_8to5 {
+				unpack_8to5(pixel_data)
+			}
+			else {
+				unpack_4plus1(pixel_data)
+			};
+			let mut pixels = unpacked.into_iter();
+
+			decode_rows(cmds, &bounds_table, width, |opcode, _| {
+				let mut pixel = pixels.next().expect("SMX pixel stream is too short");
+				if opcode == 0b10 {
+					pixel.pixel_type = SLPPixelType::Player;
+				}
+				pixel
+			})
+		}
+		SMPLayerType::Shadow | SMPLayerType::Outline => {
+			let data_length = read_length(buffer, pos);
+			pos += 4;
+
+			let data = &buffer[pos..pos + data_length];
+			pos += data_length;
+
+			decode_rows(data, &bounds_table, width, |_, dpos| {
+				if layer_type == SMPLayerType::Outline {
+					return SMPPixel::new(SLPPixelType::Special1, 0, 0, [0, 0]);
+				}
+
+				let intensity = data[*dpos];
+				*dpos += 1;
+				SMPPixel::new(SLPPixelType::ShadowV4, intensity, 0, [0, 0])
+			})
+		}
+	};
+
+	let layer = SMXLayer {
+		layer_type,
+		info,
+		frame: SLPFrame::new(bounds_table, cmd_table, row_data),
+	};
+
+	return (layer, pos);
+}
+
+/// Decode a frame with all of its layers.
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer to read from.
+/// * `offset` - Offset of the frame header.
+///
+/// # Returns
+///
+/// Decoded frame and the offset of the next frame.
+pub fn decode_frame(
+	buffer: &[u8],
+	offset: usize,
+) -> (SMXFrame, usize) {
+	let header = SMXFrameHeader::from_buffer(buffer, offset);
+	let mut pos = offset + SMX_FRAME_HEADER_SIZE;
+
+	let present = [
+		(SMPLayerType::Main, header.has_main()),
+		(SMPLayerType::Shadow, header.has_shadow()),
+		(SMPLayerType::Outline, header.has_outline()),
+	];
+
+	let mut layers = Vec::<SMXLayer>::new();
+	for (layer_type, _) in present.into_iter().filter(|(_, present)| *present) {
+		let (layer, next) = decode_layer(buffer, pos, layer_type, header.is_8to5());
+		layers.push(layer);
+		pos = next;
+	}
+
+	return (SMXFrame { header, layers }, pos);
+}
@@ -0,0 +1,162 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::fmt;
+
+use crate::slp::unpack::UnpackFixedSize;
+use crate::smx::definitions::SMX_FRAME_8TO5;
+use crate::smx::definitions::SMX_FRAME_HEADER_SIZE;
+use crate::smx::definitions::SMX_FRAME_MAIN;
+use crate::smx::definitions::SMX_FRAME_OUTLINE;
+use crate::smx::definitions::SMX_FRAME_SHADOW;
+use crate::smx::definitions::SMX_HEADER_SIZE;
+
+/// Header in an SMX file.
+#[derive(Debug, Clone, Copy)]
+pub struct SMXHeader {
+	/// File signature, always `SMPX`.
+	pub signature: [u8; 4],
+	/// Format version.
+	pub version: u16,
+	/// Number of frames.
+	pub num_frames: u16,
+	/// Size of the SMX file.
+	pub file_size: u32,
+	/// Size of the uncompressed SMP file.
+	pub file_size_smp: u32,
+	/// Comment string.
+	pub comment: [u8; 16],
+}
+
+impl SMXHeader {
+	/// Get the comment string.
+	///
+	/// # Returns
+	///
+	/// Comment string up to the first null byte.
+	pub fn get_comment(&self) -> String {
+		let end = self
+			.comment
+			.iter()
+			.position(|&b| b == 0)
+			.unwrap_or(self.comment.len());
+
+		return String::from_utf8_lossy(&self.comment[..end]).to_string();
+	}
+}
+
+impl UnpackFixedSize for SMXHeader {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + SMX_HEADER_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		return Self {
+			signature: bytes[0..4].try_into().unwrap(),
+			version: u16::from_le_bytes([bytes[4], bytes[5]]),
+			num_frames: u16::from_le_bytes([bytes[6], bytes[7]]),
+			file_size: u32::from_le_bytes(bytes[8..12].try_into().unwrap()),
+			file_size_smp: u32::from_le_bytes(bytes[12..16].try_into().unwrap()),
+			comment: bytes[16..32].try_into().unwrap(),
+		};
+	}
+}
+
+impl fmt::Display for SMXHeader {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"version: {}\nnum_frames: {}\nfile_size: {}\nfile_size_smp: {}\ncomment: {}",
+			self.version,
+			self.num_frames,
+			self.file_size,
+			self.file_size_smp,
+			self.get_comment()
+		)
+	}
+}
+
+/// Header of a frame in an SMX file.
+#[derive(Debug, Clone, Copy)]
+pub struct SMXFrameHeader {
+	/// Frame type flags. They define which layers are present and the
+	/// packing of the main layer.
+	pub frame_type: u8,
+	/// ID of the palette used by the frame.
+	pub palette_number: u8,
+	/// Uncompressed size of the frame.
+	pub uncompressed_size: u32,
+}
+
+impl SMXFrameHeader {
+	/// Check if the frame has a main graphics layer.
+	///
+	/// # Returns
+	///
+	/// true if the layer is present, else false.
+	pub const fn has_main(&self) -> bool {
+		return self.frame_type & SMX_FRAME_MAIN != 0;
+	}
+
+	/// Check if the frame has a shadow layer.
+	///
+	/// # Returns
+	///
+	/// true if the layer is present, else false.
+	pub const fn has_shadow(&self) -> bool {
+		return self.frame_type & SMX_FRAME_SHADOW != 0;
+	}
+
+	/// Check if the frame has an outline layer.
+	///
+	/// # Returns
+	///
+	/// true if the layer is present, else false.
+	pub const fn has_outline(&self) -> bool {
+		return self.frame_type & SMX_FRAME_OUTLINE != 0;
+	}
+
+	/// Check if the main layer uses 8to5 packing.
+	///
+	/// # Returns
+	///
+	/// true if the main layer uses 8to5 packing, false for 4plus1 packing.
+	pub const fn is_8to5(&self) -> bool {
+		return self.frame_type & SMX_FRAME_8TO5 != 0;
+	}
+}
+
+impl UnpackFixedSize for SMXFrameHeader {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + SMX_FRAME_HEADER_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		return Self {
+			frame_type: bytes[0],
+			palette_number: bytes[1],
+			uncompressed_size: u32::from_le_bytes(bytes[2..6].try_into().unwrap()),
+		};
+	}
+}
+
+impl fmt::Display for SMXFrameHeader {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"frame_type: {:#x}\npalette_number: {}\nuncompressed_size: {}",
+			self.frame_type, self.palette_number, self.uncompressed_size
+		)
+	}
+}
@@ -0,0 +1,56 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::fmt;
+
+use crate::slp::unpack::UnpackFixedSize;
+use crate::smx::definitions::SMX_LAYER_HEADER_SIZE;
+
+/// Header of a layer in an SMX frame.
+#[derive(Debug, Clone, Copy)]
+pub struct SMXLayerInfo {
+	/// Width of the layer.
+	pub width: u16,
+	/// Height of the layer.
+	pub height: u16,
+	/// X coordinate of the anchor point.
+	pub anchor_x: i16,
+	/// Y coordinate of the anchor point.
+	pub anchor_y: i16,
+	/// Size of the layer data following the header.
+	pub layer_size: u32,
+	/// Unknown value.
+	pub unknown: u32,
+}
+
+impl UnpackFixedSize for SMXLayerInfo {
+	fn from_buffer(
+		buffer: &[u8],
+		offset: usize,
+	) -> Self {
+		return Self::from_bytes(&buffer[offset..offset + SMX_LAYER_HEADER_SIZE]);
+	}
+
+	fn from_bytes(bytes: &[u8]) -> Self {
+		return Self {
+			width: u16::from_le_bytes([bytes[0], bytes[1]]),
+			height: u16::from_le_bytes([bytes[2], bytes[3]]),
+			anchor_x: i16::from_le_bytes([bytes[4], bytes[5]]),
+			anchor_y: i16::from_le_bytes([bytes[6], bytes[7]]),
+			layer_size: u32::from_le_bytes(bytes[8..12].try_into().unwrap()),
+			unknown: u32::from_le_bytes(bytes[12..16].try_into().unwrap()),
+		};
+	}
+}
+
+impl fmt::Display for SMXLayerInfo {
+	fn fmt(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+	) -> fmt::Result {
+		write!(
+			f,
+			"width: {}\nheight: {}\nanchor_x: {}\nanchor_y: {}\nlayer_size: {}",
+			self.width, self.height, self.anchor_x, self.anchor_y, self.layer_size
+		)
+	}
+}
@@ -0,0 +1,7 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+pub mod definitions;
+pub mod frame;
+pub mod header;
+pub mod layer;
+pub mod smx;
@@ -0,0 +1,136 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use std::io;
+
+use crate::slp::unpack::UnpackFixedSize;
+use crate::smx::definitions::SMX_HEADER_SIZE;
+use crate::smx::definitions::SMX_SIGNATURE;
+use crate::smx::frame::decode_frame;
+use crate::smx::frame::SMXFrame;
+use crate::smx::header::SMXHeader;
+
+/// SMX file.
+#[derive(Debug, Clone)]
+pub struct SMXFile {
+	/// SMX header.
+	pub header: SMXHeader,
+	/// Layered frames.
+	pub frames: Vec<SMXFrame>,
+}
+
+/// Parse an SMX file.
+///
+/// Frames are stored one after another directly behind the header.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes of the SMX file.
+///
+/// # Returns
+///
+/// The parsed SMX file.
+///
+/// # Errors
+///
+/// Returns an error if the file does not start with the SMX signature.
+///
+/// # Panics
+///
+/// Panics if the frame data is truncated.
+pub fn parse_smx(bytes: &[u8]) -> io::Result<SMXFile> {
+	if bytes.len() < SMX_HEADER_SIZE || &bytes[0..4] != SMX_SIGNATURE {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"File is not an SMX file",
+		));
+	}
+	let header = SMXHeader::from_buffer(bytes, 0);
+
+	let mut frames = Vec::<SMXFrame>::with_capacity(header.num_frames as usize);
+	let mut offset = SMX_HEADER_SIZE;
+	for _ in 0..header.num_frames {
+		let (frame, next) = decode_frame(bytes, offset);
+		frames.push(frame);
+		offset = next;
+	}
+
+	return Ok(SMXFile { header, frames });
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::slp::pixel::SLPPixelType;
+	use crate::smp::layer::SMPLayerType;
+	use crate::smx::smx::parse_smx;
+
+	/// Build a layer header with its row bounds.
+	fn layer_header(
+		width: u16,
+		bounds: &[[u8; 4]],
+	) -> Vec<u8> {
+		let mut bytes = width.to_le_bytes().to_vec();
+		bytes.extend_from_slice(&u16::try_from(bounds.len()).unwrap().to_le_bytes());
+		bytes.extend_from_slice(&[0; 12]);
+		bytes.extend(bounds.iter().flatten());
+		return bytes;
+	}
+
+	/// Test decoding 4plus1 and 8to5 main layers and a shadow layer.
+	#[test]
+	fn decode_smx_layers() {
+		let mut bytes = b"SMPX".to_vec();
+		bytes.extend_from_slice(&[2, 0, 2, 0]);
+		bytes.resize(32, 0);
+
+		// frame 0: main (4plus1) and shadow layer
+		bytes.extend_from_slice(&[0x03, 21, 0, 0, 0, 0]);
+		bytes.extend(layer_header(4, &[[0, 0, 0, 0]]));
+		// draw 3 pixels, player color 1 pixel, end of row
+		let cmds = [0x09, 0x02, 0x03];
+		let pixels = [10, 11, 12, 13, 0b1110_0100];
+		bytes.extend_from_slice(&3u32.to_le_bytes());
+		bytes.extend_from_slice(&5u32.to_le_bytes());
+		bytes.extend_from_slice(&cmds);
+		bytes.extend_from_slice(&pixels);
+		bytes.extend(layer_header(4, &[[1, 0, 0, 0], [0xFF, 0xFF, 0xFF, 0xFF]]));
+		// skip 1, draw 2 intensities, end of row
+		let data = [0x00, 0x05, 30, 60, 0x03];
+		bytes.extend_from_slice(&5u32.to_le_bytes());
+		bytes.extend_from_slice(&data);
+
+		// frame 1: main layer with 8to5 packing
+		bytes.extend_from_slice(&[0x09, 21, 0, 0, 0, 0]);
+		bytes.extend(layer_header(2, &[[0, 0, 0, 0]]));
+		let first = 0x7Fu64 | (0b10 << 8) | (0xAB << 10) | (0b01 << 18);
+		let second = 0x80u64 | (0b11 << 8) | (0x12 << 10) | (0b10 << 18);
+		let packed = (first | (second << 20)).to_le_bytes();
+		bytes.extend_from_slice(&2u32.to_le_bytes());
+		bytes.extend_from_slice(&5u32.to_le_bytes());
+		bytes.extend_from_slice(&[0x05, 0x03]);
+		bytes.extend_from_slice(&packed[..5]);
+
+		let smx = parse_smx(&bytes).unwrap();
+		assert_eq!(smx.frames.len(), 2);
+
+		let main = smx.frames[0].get_layer(SMPLayerType::Main).unwrap();
+		let row = &main.frame.get_pixels()[0];
+		assert_eq!(
+			row.iter().map(|p| (p.index, p.section)).collect::<Vec<_>>(),
+			[(10, 0), (11, 1), (12, 2), (13, 3)]
+		);
+		assert_eq!(row[3].pixel_type, SLPPixelType::Player);
+
+		let shadow = smx.frames[0].get_layer(SMPLayerType::Shadow).unwrap();
+		let row = &shadow.frame.get_pixels()[0];
+		assert_eq!(row[1].pixel_type, SLPPixelType::Transparent);
+		assert_eq!((row[2].index, row[3].index), (30, 60));
+		assert_eq!(shadow.frame.get_height(), 2);
+
+		let main = smx.frames[1].get_layer(SMPLayerType::Main).unwrap();
+		let row = &main.frame.get_pixels()[0];
+		assert_eq!((row[0].index, row[0].section), (0x7F, 0b10));
+		assert_eq!(row[0].damage_modifier, [0xAB, 0b01]);
+		assert_eq!((row[1].index, row[1].section), (0x80, 0b11));
+		assert_eq!(row[1].damage_modifier, [0x12, 0b10]);
+	}
+}
@@ -0,0 +1,473 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+/// Width and height of a compressed block in pixels.
+pub const BC_BLOCK_DIM: usize = 4;
+
+/// Number of pixels in a compressed block.
+pub const BC_BLOCK_PIXELS: usize = BC_BLOCK_DIM * BC_BLOCK_DIM;
+
+/// Size of a BC1 block.
+pub const BC1_BLOCK_SIZE: usize = 8;
+
+/// Size of a BC2 or BC3 block.
+pub const BC2_BLOCK_SIZE: usize = 16;
+
+/// Size of a BC4 block.
+pub const BC4_BLOCK_SIZE: usize = 8;
+
+/// Size of a BC5 block.
+pub const BC5_BLOCK_SIZE: usize = 16;
+
+/// Convert an RGB565 color to RGB888.
+///
+/// # Arguments
+///
+/// * `color` - RGB565 color.
+///
+/// # Returns
+///
+/// RGB888 color.
+pub const fn rgb565_to_rgb(color: u16) -> [u8; 3] {
+	let r = ((color >> 11) & 0x1F) as u8;
+	let g = ((color >> 5) & 0x3F) as u8;
+	let b = (color & 0x1F) as u8;
+
+	return [
+		(r << 3) | (r >> 2),
+		(g << 2) | (g >> 4),
+		(b << 3) | (b >> 2),
+	];
+}
+
+/// Interpolate between two colors.
+///
+/// # Arguments
+///
+/// * `a` - First color.
+/// * `b` - Second color.
+/// * `weight_a` - Weight of the first color.
+/// * `weight_b` - Weight of the second color.
+///
+/// # Returns
+///
+/// Interpolated color.
+fn interpolate(
+	a: [u8; 3],
+	b: [u8; 3],
+	weight_a: u16,
+	weight_b: u16,
+) -> [u8; 3] {
+	let total = weight_a + weight_b;
+	return std::array::from_fn(|c| {
+		let value = (u16::from(a[c]) * weight_a + u16::from(b[c]) * weight_b + total / 2) / total;
+		u8::try_from(value).unwrap()
+	});
+}
+
+/// Get the colors a color block can reference.
+///
+/// # Arguments
+///
+/// * `color0` - First endpoint as RGB565.
+/// * `color1` - Second endpoint as RGB565.
+/// * `allow_alpha` - true if the block may use the 3-color mode with a
+///   transparent color (BC1), else false.
+///
+/// # Returns
+///
+/// RGBA colors for the 4 indices.
+pub fn get_color_palette(
+	color0: u16,
+	color1: u16,
+	allow_alpha: bool,
+) -> [[u8; 4]; 4] {
+	let rgb0 = rgb565_to_rgb(color0);
+	let rgb1 = rgb565_to_rgb(color1);
+
+	if color0 > color1 || !allow_alpha {
+		let rgb2 = interpolate(rgb0, rgb1, 2, 1);
+		let rgb3 = interpolate(rgb0, rgb1, 1, 2);
+		return [
+			[rgb0[0], rgb0[1], rgb0[2], 255],
+			[rgb1[0], rgb1[1], rgb1[2], 255],
+			[rgb2[0], rgb2[1], rgb2[2], 255],
+			[rgb3[0], rgb3[1], rgb3[2], 255],
+		];
+	}
+
+	let rgb2 = interpolate(rgb0, rgb1, 1, 1);
+	return [
+		[rgb0[0], rgb0[1], rgb0[2], 255],
+		[rgb1[0], rgb1[1], rgb1[2], 255],
+		[rgb2[0], rgb2[1], rgb2[2], 255],
+		[0, 0, 0, 0],
+	];
+}
+
+/// Decode the color part of a BC1, BC2 or BC3 block.
+///
+/// # Arguments
+///
+/// * `block` - 8 bytes of color data.
+/// * `allow_alpha` - true if the block may use the 3-color mode with a
+///   transparent color (BC1), else false.
+///
+/// # Returns
+///
+/// RGBA colors of the 16 pixels in row-major order.
+pub fn decode_color_block(
+	block: &[u8],
+	allow_alpha: bool,
+) -> [[u8; 4]; BC_BLOCK_PIXELS] {
+	let color0 = u16::from_le_bytes([block[0], block[1]]);
+	let color1 = u16::from_le_bytes([block[2], block[3]]);
+	let palette = get_color_palette(color0, color1, allow_alpha);
+
+	let indices = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+	return std::array::from_fn(|i| palette[((indices >> (2 * i)) & 0b11) as usize]);
+}
+
+/// Decode a BC1 block.
+///
+/// # Arguments
+///
+/// * `block` - 8 bytes of block data.
+///
+/// # Returns
+///
+/// RGBA colors of the 16 pixels in row-major order.
+pub fn decode_bc1_block(block: &[u8]) -> [[u8; 4]; BC_BLOCK_PIXELS] {
+	return decode_color_block(block, true);
+}
+
+/// Get the values a BC4 block can reference.
+///
+/// # Arguments
+///
+/// * `value0` - First endpoint.
+/// * `value1` - Second endpoint.
+///
+/// # Returns
+///
+/// Values for the 8 indices.
+pub fn get_bc4_palette(
+	value0: u8,
+	value1: u8,
+) -> [u8; 8] {
+	let a = u16::from(value0);
+	let b = u16::from(value1);
+
+	let mut palette = [0u16; 8];
+	palette[0] = a;
+	palette[1] = b;
+	if a > b {
+		for i in 1..7u16 {
+			palette[usize::from(i) + 1] = ((7 - i) * a + i * b + 3) / 7;
+		}
+	}
+	else {
+		for i in 1..5u16 {
+			palette[usize::from(i) + 1] = ((5 - i) * a + i * b + 2) / 5;
+		}
+		palette[6] = 0;
+		palette[7] = 255;
+	}
+
+	return palette.map(|value| u8::try_from(value).unwrap_or(u8::MAX));
+}
+
+/// Decode a BC4 block.
+///
+/// # Arguments
+///
+/// * `block` - 8 bytes of block data.
+///
+/// # Returns
+///
+/// Values of the 16 pixels in row-major order.
+pub fn decode_bc4_block(block: &[u8]) -> [u8; BC_BLOCK_PIXELS] {
+	let palette = get_bc4_palette(block[0], block[1]);
+
+	let mut bits = [0; 8];
+	bits[..6].copy_from_slice(&block[2..8]);
+	let indices = u64::from_le_bytes(bits);
+
+	return std::array::from_fn(|i| palette[((indices >> (3 * i)) & 0b111) as usize]);
+}
+
+/// Decode a BC2 block.
+///
+/// The block stores explicit 4-bit alpha values followed by a color block.
+///
+/// # Arguments
+///
+/// * `block` - 16 bytes of block data.
+///
+/// # Returns
+///
+/// RGBA colors of the 16 pixels in row-major order.
+pub fn decode_bc2_block(block: &[u8]) -> [[u8; 4]; BC_BLOCK_PIXELS] {
+	let mut colors = decode_color_block(&block[8..16], false);
+	let mut alpha = [0; 8];
+	alpha.copy_from_slice(&block[0..8]);
+	let alpha = u64::from_le_bytes(alpha);
+	for (i, color) in colors.iter_mut().enumerate() {
+		let value = ((alpha >> (4 * i)) & 0xF) as u8;
+		color[3] = value * 17;
+	}
+
+	return colors;
+}
+
+/// Decode a BC3 block.
+///
+/// The block stores BC4 compressed alpha values followed by a color block.
+///
+/// # Arguments
+///
+/// * `block` - 16 bytes of block data.
+///
+/// # Returns
+///
+/// RGBA colors of the 16 pixels in row-major order.
+pub fn decode_bc3_block(block: &[u8]) -> [[u8; 4]; BC_BLOCK_PIXELS] {
+	let mut colors = decode_color_block(&block[8..16], false);
+	let alpha = decode_bc4_block(&block[0..8]);
+	for (color, value) in colors.iter_mut().zip(alpha) {
+		color[3] = value;
+	}
+
+	return colors;
+}
+
+/// Decode a BC5 block.
+///
+/// The block stores two BC4 blocks for the red and green channels. Blue is
+/// set to 0 and alpha to 255.
+///
+/// # Arguments
+///
+/// * `block` - 16 bytes of block data.
+///
+/// # Returns
+///
+/// RGBA colors of the 16 pixels in row-major order.
+pub fn decode_bc5_block(block: &[u8]) -> [[u8; 4]; BC_BLOCK_PIXELS] {
+	let red = decode_bc4_block(&block[0..8]);
+	let green = decode_bc4_block(&block[8..16]);
+
+	return std::array::from_fn(|i| [red[i], green[i], 0, 255]);
+}
+
+/// Convert an RGB888 color to RGB565.
+///
+/// # Arguments
+///
+/// * `color` - RGB888 color.
+///
+/// # Returns
+///
+/// RGB565 color.
+pub const fn rgb_to_rgb565(color: [u8; 3]) -> u16 {
+	let r = (color[0] as u16 * 31 + 127) / 255;
+	let g = (color[1] as u16 * 63 + 127) / 255;
+	let b = (color[2] as u16 * 31 + 127) / 255;
+
+	return (r << 11) | (g << 5) | b;
+}
+
+/// Get the squared distance between two RGB colors.
+///
+/// # Arguments
+///
+/// * `a` - First color.
+/// * `b` - Second color.
+///
+/// # Returns
+///
+/// Squared distance.
+fn color_distance(
+	a: &[u8],
+	b: &[u8],
+) -> u32 {
+	return (0..3)
+		.map(|c| {
+			let diff = u32::from(a[c].abs_diff(b[c]));
+			diff * diff
+		})
+		.sum();
+}
+
+/// Encode a block of pixels as a BC1 block.
+///
+/// Pixels with an alpha value below 128 are encoded as transparent. The
+/// endpoints are the two opaque colors furthest apart.
+///
+/// # Arguments
+///
+/// * `pixels` - RGBA colors of the 16 pixels in row-major order.
+///
+/// # Returns
+///
+/// 8 bytes of block data.
+pub fn encode_bc1_block(pixels: &[[u8; 4]; BC_BLOCK_PIXELS]) -> [u8; 8] {
+	let opaque = pixels
+		.iter()
+		.filter(|pixel| pixel[3] >= 128)
+		.collect::<Vec<&[u8; 4]>>();
+	if opaque.is_empty() {
+		return [0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF];
+	}
+	let has_alpha = opaque.len() < BC_BLOCK_PIXELS;
+
+	// use the two colors furthest apart as endpoints
+	let mut low = opaque[0];
+	let mut high = opaque[0];
+	let mut max_distance = 0;
+	for (i, a) in opaque.iter().enumerate() {
+		for b in &opaque[i + 1..] {
+			let distance = color_distance(*a, *b);
+			if distance > max_distance {
+				(low, high, max_distance) = (a, b, distance);
+			}
+		}
+	}
+
+	let mut color0 = rgb_to_rgb565([high[0], high[1], high[2]]);
+	let mut color1 = rgb_to_rgb565([low[0], low[1], low[2]]);
+	// 4-color mode needs color0 > color1, 3-color mode color0 <= color1
+	if (color0 < color1) != has_alpha && color0 != color1 {
+		std::mem::swap(&mut color0, &mut color1);
+	}
+	if !has_alpha && color0 == color1 {
+		// all pixels use color0, so the mode does not matter
+		color1 = 0;
+		if color0 == 0 {
+			return [0; 8];
+		}
+	}
+
+	let palette = get_color_palette(color0, color1, true);
+	let mut indices = 0u32;
+	for (i, pixel) in pixels.iter().enumerate() {
+		let index = if pixel[3] < 128 {
+			3
+		}
+		else {
+			(0..4)
+				.filter(|&j| palette[j][3] == 255)
+				.min_by_key(|&j| color_distance(&palette[j], pixel))
+				.unwrap_or(0)
+		};
+		indices |= u32::try_from(index).unwrap_or(0) << (2 * i);
+	}
+
+	let mut block = [0; 8];
+	block[0..2].copy_from_slice(&color0.to_le_bytes());
+	block[2..4].copy_from_slice(&color1.to_le_bytes());
+	block[4..8].copy_from_slice(&indices.to_le_bytes());
+	return block;
+}
+
+/// Encode a block of values as a BC4 block.
+///
+/// Both the 8-value mode and the 6-value mode with exact 0 and 255 are
+/// tried and the one with the smaller error is used.
+///
+/// # Arguments
+///
+/// * `values` - Values of the 16 pixels in row-major order.
+///
+/// # Returns
+///
+/// 8 bytes of block data.
+pub fn encode_bc4_block(values: &[u8; BC_BLOCK_PIXELS]) -> [u8; 8] {
+	let min = values.iter().copied().min().unwrap_or(0);
+	let max = values.iter().copied().max().unwrap_or(0);
+	let inner = values.iter().copied().filter(|&v| v != 0 && v != 255);
+	let inner_min = inner.clone().min().unwrap_or(0);
+	let inner_max = inner.max().unwrap_or(0);
+
+	let mut best = ([0; 8], u32::MAX);
+	for (value0, value1) in [(max, min), (inner_min, inner_max)] {
+		let palette = get_bc4_palette(value0, value1);
+
+		let mut indices = 0u64;
+		let mut error = 0u32;
+		for (i, value) in values.iter().enumerate() {
+			let (index, diff) = palette
+				.iter()
+				.enumerate()
+				.map(|(j, entry)| (j, u32::from(entry.abs_diff(*value))))
+				.min_by_key(|(_, diff)| *diff)
+				.unwrap_or((0, 0));
+			indices |= (index as u64) << (3 * i);
+			error += diff * diff;
+		}
+
+		if error < best.1 {
+			let mut block = [0; 8];
+			block[0] = value0;
+			block[1] = value1;
+			block[2..8].copy_from_slice(&indices.to_le_bytes()[..6]);
+			best = (block, error);
+		}
+	}
+
+	return best.0;
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::util::bcn::decode_bc1_block;
+	use crate::util::bcn::decode_bc2_block;
+	use crate::util::bcn::decode_bc3_block;
+	use crate::util::bcn::decode_bc4_block;
+	use crate::util::bcn::decode_bc5_block;
+	use crate::util::bcn::encode_bc1_block;
+	use crate::util::bcn::encode_bc4_block;
+
+	/// Test decoding blocks of all supported formats.
+	#[test]
+	fn decode_blocks() {
+		// red and blue endpoints, pixels alternate between them
+		let color = [0x00, 0xF8, 0x1F, 0x00, 0x44, 0x44, 0x44, 0x44];
+		let colors = decode_bc1_block(&color);
+		assert_eq!(colors[0], [255, 0, 0, 255]);
+		assert_eq!(colors[1], [0, 0, 255, 255]);
+
+		// 3-color mode, index 3 is transparent
+		let transparent = [0x1F, 0x00, 0x00, 0xF8, 0xFF, 0, 0, 0];
+		let colors = decode_bc1_block(&transparent);
+		assert_eq!(colors[0], [0, 0, 0, 0]);
+		assert_eq!(colors[4], [0, 0, 255, 255]);
+
+		let bc2 = [&[0x0F, 0, 0, 0, 0, 0, 0, 0xF0][..], &color].concat();
+		let colors = decode_bc2_block(&bc2);
+		assert_eq!((colors[0][3], colors[1][3], colors[15][3]), (255, 0, 255));
+
+		let alpha = [200, 100, 0, 0, 0, 0, 0, 0];
+		let colors = decode_bc3_block(&[&alpha[..], &color].concat());
+		assert_eq!(colors[0], [255, 0, 0, 200]);
+		assert_eq!(decode_bc4_block(&alpha), [200; 16]);
+
+		let colors = decode_bc5_block(&[&alpha[..], &[50, 0, 0, 0, 0, 0, 0, 0]].concat());
+		assert_eq!(colors[0], [200, 50, 0, 255]);
+	}
+
+	/// Test that encoded blocks decode to representable input colors.
+	#[test]
+	fn encode_blocks() {
+		let mut pixels = [[255, 0, 0, 255]; 16];
+		pixels[3] = [0, 0, 255, 255];
+		pixels[7] = [0, 0, 0, 0];
+		assert_eq!(decode_bc1_block(&encode_bc1_block(&pixels)), pixels);
+
+		let mut values = [0; 16];
+		values[5] = 255;
+		values[6] = 128;
+		let decoded = decode_bc4_block(&encode_bc4_block(&values));
+		assert_eq!((decoded[0], decoded[5]), (0, 255));
+		assert!(decoded[6].abs_diff(128) <= 20);
+	}
+}
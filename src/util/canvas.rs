@@ -0,0 +1,73 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+use crate::slp::frame_info::SLPFrameInfo;
+
+/// Canvas that multiple frames are drawn on.
+///
+/// The canvas is large enough to fit every frame when the frames are aligned
+/// on their anchor points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnchoredCanvas {
+	/// Width of the canvas.
+	pub width: usize,
+	/// Height of the canvas.
+	pub height: usize,
+	/// X coordinate of the common anchor point.
+	pub anchor_x: i32,
+	/// Y coordinate of the common anchor point.
+	pub anchor_y: i32,
+}
+
+impl AnchoredCanvas {
+	/// Create a canvas that fits all given frames.
+	///
+	/// # Arguments
+	///
+	/// * `frame_infos` - Frame infos of the frames drawn on the canvas.
+	///
+	/// # Returns
+	///
+	/// New canvas.
+	pub fn from_frame_infos<'a, I>(frame_infos: I) -> Self
+	where
+		I: IntoIterator<Item = &'a SLPFrameInfo>,
+	{
+		let mut left: i32 = 0;
+		let mut right: i32 = 0;
+		let mut top: i32 = 0;
+		let mut bottom: i32 = 0;
+
+		for frame_info in frame_infos {
+			left = left.max(frame_info.data.anchor_x);
+			right = right.max(frame_info.data.width - frame_info.data.anchor_x);
+			top = top.max(frame_info.data.anchor_y);
+			bottom = bottom.max(frame_info.data.height - frame_info.data.anchor_y);
+		}
+
+		Self {
+			width: usize::try_from(left + right).unwrap_or(0),
+			height: usize::try_from(top + bottom).unwrap_or(0),
+			anchor_x: left,
+			anchor_y: top,
+		}
+	}
+
+	/// Get the position of a frame's top left corner on the canvas.
+	///
+	/// # Arguments
+	///
+	/// * `frame_info` - Frame info of the frame.
+	///
+	/// # Returns
+	///
+	/// X and Y coordinates of the frame on the canvas.
+	pub const fn get_frame_position(
+		&self,
+		frame_info: &SLPFrameInfo,
+	) -> (i32, i32) {
+		return (
+			self.anchor_x - frame_info.data.anchor_x,
+			self.anchor_y - frame_info.data.anchor_y,
+		);
+	}
+}
@@ -1,14 +1,97 @@
 // Copyright 2023-2023 the slutils-rs authors.
 
+use std::collections::HashMap;
+
 use crate::util::matrix::Matrix2D;
+use crate::util::pixel::premultiply;
+
+/// Alpha representation of RGBA output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlphaMode {
+	/// Color channels are independent of the alpha channel.
+	#[default]
+	Straight,
+	/// Color channels are multiplied by the alpha channel.
+	Premultiplied,
+}
 
 pub trait RGBAImageConvertible {
 	/// Convert to a matrix of RGBA values.
-	fn to_rgba_matrix(&self) -> Matrix2D<[u8; 4]>;
+	///
+	/// # Arguments
+	///
+	/// * `lookup` - Lookup table from palette index to RGBA color.
+	fn to_rgba_matrix(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+	) -> Matrix2D<[u8; 4]>;
 
 	/// Convert to a vector of bytes.
-	fn to_rgba_bytes(&self) -> Vec<u8>;
+	///
+	/// # Arguments
+	///
+	/// * `lookup` - Lookup table from palette index to RGBA color.
+	fn to_rgba_bytes(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+	) -> Vec<u8>;
 
 	/// Convert to an RGBA image.
-	fn to_image(&self) -> image::RgbaImage;
+	///
+	/// # Arguments
+	///
+	/// * `lookup` - Lookup table from palette index to RGBA color.
+	fn to_image(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+	) -> image::RgbaImage;
+
+	/// Convert to a vector of bytes with the given alpha representation.
+	///
+	/// SLP frames only premultiply palette colors. The marker colors of the
+	/// other pixel types stay unchanged, so the output can be imported again.
+	///
+	/// # Arguments
+	///
+	/// * `lookup` - Lookup table from palette index to RGBA color.
+	/// * `alpha_mode` - Alpha representation of the output.
+	fn to_rgba_bytes_with_alpha(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+		alpha_mode: AlphaMode,
+	) -> Vec<u8> {
+		let mut bytes = self.to_rgba_bytes(lookup);
+		if alpha_mode == AlphaMode::Premultiplied {
+			for pixel in bytes.chunks_exact_mut(4) {
+				let color = premultiply([pixel[0], pixel[1], pixel[2], pixel[3]]);
+				pixel.copy_from_slice(&color);
+			}
+		}
+
+		return bytes;
+	}
+
+	/// Convert to an RGBA image with the given alpha representation.
+	///
+	/// SLP frames only premultiply palette colors, see
+	/// [`RGBAImageConvertible::to_rgba_bytes_with_alpha`].
+	///
+	/// # Arguments
+	///
+	/// * `lookup` - Lookup table from palette index to RGBA color.
+	/// * `alpha_mode` - Alpha representation of the output.
+	fn to_image_with_alpha(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+		alpha_mode: AlphaMode,
+	) -> image::RgbaImage {
+		let mut image = self.to_image(lookup);
+		if alpha_mode == AlphaMode::Premultiplied {
+			for pixel in image.pixels_mut() {
+				pixel.0 = premultiply(pixel.0);
+			}
+		}
+
+		return image;
+	}
 }
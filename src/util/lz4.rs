@@ -0,0 +1,227 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+/// Minimum length of a match in an LZ4 sequence.
+pub const LZ4_MIN_MATCH: usize = 4;
+
+/// Number of bytes at the end of a block that are always literals.
+pub const LZ4_LAST_LITERALS: usize = 5;
+
+/// Number of bytes at the end of a block in which no match can start.
+pub const LZ4_MATCH_FIND_LIMIT: usize = 12;
+
+/// Largest offset of a match.
+pub const LZ4_MAX_OFFSET: usize = 0xFFFF;
+
+/// Number of bits of the hashes used to find matches.
+const LZ4_HASH_BITS: u32 = 16;
+
+/// Read an LZ4 length that is extended by additional bytes.
+///
+/// # Arguments
+///
+/// * `bytes` - Compressed bytes.
+/// * `pos` - Position of the first extension byte. Moved behind the length.
+/// * `length` - Length stored in the token nibble.
+///
+/// # Returns
+///
+/// Full length.
+fn read_length(
+	bytes: &[u8],
+	pos: &mut usize,
+	length: usize,
+) -> usize {
+	let mut length = length;
+	if length == 0xF {
+		loop {
+			let extra = bytes[*pos];
+			*pos += 1;
+			length += extra as usize;
+			if extra != u8::MAX {
+				break;
+			}
+		}
+	}
+
+	return length;
+}
+
+/// Write an LZ4 length that does not fit into the token nibble.
+///
+/// # Arguments
+///
+/// * `length` - Full length.
+/// * `output` - Output buffer.
+fn write_length(
+	length: usize,
+	output: &mut Vec<u8>,
+) {
+	if length < 0xF {
+		return;
+	}
+
+	let mut remaining = length - 0xF;
+	while remaining >= u8::MAX as usize {
+		output.push(u8::MAX);
+		remaining -= u8::MAX as usize;
+	}
+	output.push(u8::try_from(remaining).unwrap());
+}
+
+/// Write an LZ4 sequence.
+///
+/// # Arguments
+///
+/// * `literals` - Literal bytes of the sequence.
+/// * `found` - Offset and length of the match, `None` for the last sequence.
+/// * `output` - Output buffer.
+fn write_sequence(
+	literals: &[u8],
+	found: Option<(usize, usize)>,
+	output: &mut Vec<u8>,
+) {
+	let match_length = found.map_or(0, |(_, length)| length - LZ4_MIN_MATCH);
+	let token = u8::try_from(literals.len().min(0xF) << 4 | match_length.min(0xF)).unwrap();
+	output.push(token);
+	write_length(literals.len(), output);
+	output.extend_from_slice(literals);
+
+	if let Some((offset, _)) = found {
+		output.extend_from_slice(&u16::try_from(offset).unwrap().to_le_bytes());
+		write_length(match_length, output);
+	}
+}
+
+/// Compress data to an LZ4 block.
+///
+/// Matches are found greedily with a hash table of the last position of
+/// each 4-byte sequence.
+///
+/// # Arguments
+///
+/// * `bytes` - Data to compress.
+///
+/// # Returns
+///
+/// Compressed block.
+pub fn compress_block(bytes: &[u8]) -> Vec<u8> {
+	let mut output = Vec::<u8>::with_capacity(bytes.len() / 2);
+	let mut table = vec![usize::MAX; 1 << LZ4_HASH_BITS];
+	let match_limit = bytes.len().saturating_sub(LZ4_LAST_LITERALS);
+	let search_limit = bytes.len().saturating_sub(LZ4_MATCH_FIND_LIMIT);
+
+	let mut anchor = 0;
+	let mut pos = 0;
+	while pos < search_limit {
+		let sequence =
+			u32::from_le_bytes([bytes[pos], bytes[pos + 1], bytes[pos + 2], bytes[pos + 3]]);
+		let hash = (sequence.wrapping_mul(2_654_435_761) >> (32 - LZ4_HASH_BITS)) as usize;
+		let candidate = table[hash];
+		table[hash] = pos;
+
+		if candidate == usize::MAX
+			|| pos - candidate > LZ4_MAX_OFFSET
+			|| bytes[candidate..candidate + 4] != bytes[pos..pos + 4]
+		{
+			pos += 1;
+			continue;
+		}
+
+		let mut length = LZ4_MIN_MATCH;
+		while pos + length < match_limit && bytes[candidate + length] == bytes[pos + length] {
+			length += 1;
+		}
+		write_sequence(
+			&bytes[anchor..pos],
+			Some((pos - candidate, length)),
+			&mut output,
+		);
+		pos += length;
+		anchor = pos;
+	}
+	write_sequence(&bytes[anchor..], None, &mut output);
+
+	return output;
+}
+
+/// Decompress an LZ4 block.
+///
+/// A block is a list of sequences. Each sequence starts with a token whose
+/// upper nibble is the number of literals and whose lower nibble is the
+/// match length minus 4. The literals are followed by a 2-byte offset of
+/// the match. The last sequence only contains literals.
+///
+/// # Arguments
+///
+/// * `bytes` - Compressed block.
+/// * `size` - Size of the decompressed data.
+///
+/// # Returns
+///
+/// Decompressed data.
+///
+/// # Panics
+///
+/// Panics if the block is truncated or a match offset is invalid.
+pub fn decompress_block(
+	bytes: &[u8],
+	size: usize,
+) -> Vec<u8> {
+	let mut output = Vec::<u8>::with_capacity(size);
+	let mut pos = 0;
+	while pos < bytes.len() {
+		let token = bytes[pos];
+		pos += 1;
+
+		let literals = read_length(bytes, &mut pos, (token >> 4) as usize);
+		output.extend_from_slice(&bytes[pos..pos + literals]);
+		pos += literals;
+		if pos == bytes.len() {
+			break;
+		}
+
+		let offset = u16::from_le_bytes([bytes[pos], bytes[pos + 1]]) as usize;
+		pos += 2;
+		assert!(
+			offset != 0 && offset <= output.len(),
+			"Invalid LZ4 match offset {offset}"
+		);
+
+		let length = read_length(bytes, &mut pos, (token & 0xF) as usize) + LZ4_MIN_MATCH;
+		let start = output.len() - offset;
+		// matches may overlap the bytes they produce
+		for i in start..start + length {
+			output.push(output[i]);
+		}
+	}
+
+	return output;
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::util::lz4::compress_block;
+	use crate::util::lz4::decompress_block;
+
+	/// Test decompressing a block with an overlapping match.
+	#[test]
+	fn decompress_overlapping_match() {
+		let block = [0x35, b'a', b'b', b'c', 3, 0, 0x10, b'x'];
+
+		assert_eq!(decompress_block(&block, 13), b"abcabcabcabcx");
+	}
+
+	/// Test that compressed data decompresses to the original data.
+	#[test]
+	fn compress_and_decompress() {
+		let mut data = b"slutils ".repeat(40);
+		data.extend((0..600u32).map(|i| u8::try_from(i * 7 % 251).unwrap()));
+		data.extend([0; 300]);
+
+		let block = compress_block(&data);
+		assert!(block.len() < data.len());
+		assert_eq!(decompress_block(&block, data.len()), data);
+
+		assert_eq!(decompress_block(&compress_block(b"abc"), 3), b"abc");
+	}
+}
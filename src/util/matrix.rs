@@ -3,6 +3,7 @@
 use std::ops;
 
 /// Runtime fixed-size 2D matrix
+#[derive(Debug, Clone)]
 pub struct Matrix2D<T> {
 	/// Number of rows.
 	rows: usize,
@@ -1,5 +1,9 @@
 // Copyright 2023-2023 the slutils-rs authors.
 
+pub mod bcn;
+pub mod canvas;
 pub mod image;
+pub mod lz4;
 pub mod matrix;
 pub mod pixel;
+pub mod rect_pack;
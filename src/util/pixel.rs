@@ -5,8 +5,139 @@ use std::collections::HashMap;
 /// Convert a pixel to a RGBA value.
 pub trait RGBAConvertible {
 	/// Convert a pixel to a RGBA value.
+	///
+	/// # Arguments
+	///
+	/// * `lookup` - Lookup table from palette index to RGBA color.
 	fn to_rgba(
 		&self,
-		lookup: HashMap<usize, [u8; 4]>,
+		lookup: &HashMap<usize, [u8; 4]>,
 	) -> [u8; 4];
+
+	/// Convert a pixel to a RGBA value with premultiplied alpha.
+	///
+	/// # Arguments
+	///
+	/// * `lookup` - Lookup table from palette index to RGBA color.
+	fn to_premultiplied_rgba(
+		&self,
+		lookup: &HashMap<usize, [u8; 4]>,
+	) -> [u8; 4] {
+		return premultiply(self.to_rgba(lookup));
+	}
+}
+
+/// Blend a color over another color.
+///
+/// Both colors use straight (non-premultiplied) alpha.
+///
+/// # Arguments
+///
+/// * `dst` - Background color.
+/// * `src` - Foreground color.
+///
+/// # Returns
+///
+/// Blended color.
+pub fn blend_over(
+	dst: [u8; 4],
+	src: [u8; 4],
+) -> [u8; 4] {
+	let src_alpha = u32::from(src[3]);
+	let dst_alpha = u32::from(dst[3]) * (255 - src_alpha) / 255;
+	let out_alpha = src_alpha + dst_alpha;
+
+	if out_alpha == 0 {
+		return [0, 0, 0, 0];
+	}
+
+	let mut out = [0, 0, 0, u8::try_from(out_alpha).unwrap_or(u8::MAX)];
+	for i in 0..3 {
+		let channel = (u32::from(src[i]) * src_alpha + u32::from(dst[i]) * dst_alpha) / out_alpha;
+		out[i] = u8::try_from(channel).unwrap_or(u8::MAX);
+	}
+
+	return out;
+}
+
+/// Convert a color with straight alpha to premultiplied alpha.
+///
+/// # Arguments
+///
+/// * `color` - Color with straight alpha.
+///
+/// # Returns
+///
+/// Color with premultiplied alpha.
+pub fn premultiply(color: [u8; 4]) -> [u8; 4] {
+	let alpha = u32::from(color[3]);
+	let mut out = color;
+	for channel in out.iter_mut().take(3) {
+		*channel = u8::try_from((u32::from(*channel) * alpha + 127) / 255).unwrap_or(u8::MAX);
+	}
+
+	return out;
+}
+
+/// Convert a color with premultiplied alpha to straight alpha.
+///
+/// # Arguments
+///
+/// * `color` - Color with premultiplied alpha.
+///
+/// # Returns
+///
+/// Color with straight alpha.
+pub fn unpremultiply(color: [u8; 4]) -> [u8; 4] {
+	let alpha = u32::from(color[3]);
+	if alpha == 0 {
+		return [0, 0, 0, 0];
+	}
+
+	let mut out = color;
+	for channel in out.iter_mut().take(3) {
+		*channel = u8::try_from((u32::from(*channel) * 255 + alpha / 2) / alpha).unwrap_or(u8::MAX);
+	}
+
+	return out;
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::util::pixel::blend_over;
+	use crate::util::pixel::premultiply;
+	use crate::util::pixel::unpremultiply;
+
+	/// Test blending colors with straight alpha.
+	#[test]
+	fn blend_colors() {
+		// opaque source replaces the background
+		assert_eq!(
+			blend_over([10, 20, 30, 255], [1, 2, 3, 255]),
+			[1, 2, 3, 255]
+		);
+		// transparent source keeps the background
+		assert_eq!(
+			blend_over([10, 20, 30, 255], [1, 2, 3, 0]),
+			[10, 20, 30, 255]
+		);
+		// half transparent black darkens the background
+		assert_eq!(
+			blend_over([200, 100, 50, 255], [0, 0, 0, 128]),
+			[99, 49, 24, 255]
+		);
+		// drawing on an empty background keeps the source
+		assert_eq!(blend_over([0, 0, 0, 0], [0, 0, 0, 128]), [0, 0, 0, 128]);
+	}
+
+	/// Test conversion between straight and premultiplied alpha.
+	#[test]
+	fn premultiply_colors() {
+		assert_eq!(premultiply([200, 100, 50, 255]), [200, 100, 50, 255]);
+		assert_eq!(premultiply([200, 100, 50, 128]), [100, 50, 25, 128]);
+		assert_eq!(premultiply([200, 100, 50, 0]), [0, 0, 0, 0]);
+
+		assert_eq!(unpremultiply([100, 50, 25, 128]), [199, 100, 50, 128]);
+		assert_eq!(unpremultiply([10, 20, 30, 0]), [0, 0, 0, 0]);
+	}
 }
@@ -0,0 +1,276 @@
+// Copyright 2023-2023 the slutils-rs authors.
+
+/// Axis-aligned rectangle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+	/// X coordinate of the top left corner.
+	pub x: u32,
+	/// Y coordinate of the top left corner.
+	pub y: u32,
+	/// Width of the rectangle.
+	pub width: u32,
+	/// Height of the rectangle.
+	pub height: u32,
+}
+
+impl Rect {
+	/// Create a new rectangle.
+	///
+	/// # Arguments
+	///
+	/// * `x` - X coordinate of the top left corner.
+	/// * `y` - Y coordinate of the top left corner.
+	/// * `width` - Width of the rectangle.
+	/// * `height` - Height of the rectangle.
+	///
+	/// # Returns
+	///
+	/// New rectangle.
+	pub const fn new(
+		x: u32,
+		y: u32,
+		width: u32,
+		height: u32,
+	) -> Self {
+		Self {
+			x,
+			y,
+			width,
+			height,
+		}
+	}
+
+	/// Check if another rectangle is completely inside this rectangle.
+	///
+	/// # Arguments
+	///
+	/// * `other` - Other rectangle.
+	///
+	/// # Returns
+	///
+	/// true if `other` is contained in this rectangle, else false.
+	pub const fn contains(
+		&self,
+		other: &Self,
+	) -> bool {
+		return other.x >= self.x
+			&& other.y >= self.y
+			&& other.x + other.width <= self.x + self.width
+			&& other.y + other.height <= self.y + self.height;
+	}
+
+	/// Check if another rectangle overlaps this rectangle.
+	///
+	/// # Arguments
+	///
+	/// * `other` - Other rectangle.
+	///
+	/// # Returns
+	///
+	/// true if the rectangles overlap, else false.
+	pub const fn intersects(
+		&self,
+		other: &Self,
+	) -> bool {
+		return other.x < self.x + self.width
+			&& self.x < other.x + other.width
+			&& other.y < self.y + self.height
+			&& self.y < other.y + other.height;
+	}
+}
+
+/// Rectangle packer using the `MaxRects` algorithm.
+///
+/// Rectangles are placed with the best short side fit heuristic, i.e. into
+/// the free area where the leftover on the shorter side is the smallest.
+#[derive(Debug, Clone)]
+pub struct MaxRectsPacker {
+	/// Width of the packing area.
+	width: u32,
+	/// Height of the packing area.
+	height: u32,
+	/// Maximal free rectangles in the packing area.
+	free_rects: Vec<Rect>,
+}
+
+impl MaxRectsPacker {
+	/// Create a new packer for an empty packing area.
+	///
+	/// # Arguments
+	///
+	/// * `width` - Width of the packing area.
+	/// * `height` - Height of the packing area.
+	///
+	/// # Returns
+	///
+	/// New packer.
+	pub fn new(
+		width: u32,
+		height: u32,
+	) -> Self {
+		Self {
+			width,
+			height,
+			free_rects: vec![Rect::new(0, 0, width, height)],
+		}
+	}
+
+	/// Get the size of the packing area.
+	///
+	/// # Returns
+	///
+	/// Width and height of the packing area.
+	pub const fn get_size(&self) -> (u32, u32) {
+		(self.width, self.height)
+	}
+
+	/// Place a rectangle in the packing area.
+	///
+	/// # Arguments
+	///
+	/// * `width` - Width of the rectangle.
+	/// * `height` - Height of the rectangle.
+	///
+	/// # Returns
+	///
+	/// Position of the placed rectangle or `None` if it does not fit.
+	pub fn insert(
+		&mut self,
+		width: u32,
+		height: u32,
+	) -> Option<Rect> {
+		let mut best: Option<(Rect, u32, u32)> = None;
+		for free in &self.free_rects {
+			if free.width < width || free.height < height {
+				continue;
+			}
+
+			let leftover_x = free.width - width;
+			let leftover_y = free.height - height;
+			let short_side = leftover_x.min(leftover_y);
+			let long_side = leftover_x.max(leftover_y);
+
+			let is_better = best.is_none_or(|(_, best_short, best_long)| {
+				short_side < best_short || (short_side == best_short && long_side < best_long)
+			});
+			if is_better {
+				best = Some((
+					Rect::new(free.x, free.y, width, height),
+					short_side,
+					long_side,
+				));
+			}
+		}
+
+		let (placed, _, _) = best?;
+		self.split_free_rects(&placed);
+		self.prune_free_rects();
+
+		return Some(placed);
+	}
+
+	/// Split all free rectangles that overlap a newly placed rectangle.
+	///
+	/// # Arguments
+	///
+	/// * `placed` - Placed rectangle.
+	fn split_free_rects(
+		&mut self,
+		placed: &Rect,
+	) {
+		let mut new_rects = Vec::<Rect>::new();
+		self.free_rects.retain(|free| {
+			if !free.intersects(placed) {
+				return true;
+			}
+
+			// left part
+			if placed.x > free.x {
+				new_rects.push(Rect::new(free.x, free.y, placed.x - free.x, free.height));
+			}
+			// right part
+			if placed.x + placed.width < free.x + free.width {
+				new_rects.push(Rect::new(
+					placed.x + placed.width,
+					free.y,
+					free.x + free.width - (placed.x + placed.width),
+					free.height,
+				));
+			}
+			// top part
+			if placed.y > free.y {
+				new_rects.push(Rect::new(free.x, free.y, free.width, placed.y - free.y));
+			}
+			// bottom part
+			if placed.y + placed.height < free.y + free.height {
+				new_rects.push(Rect::new(
+					free.x,
+					placed.y + placed.height,
+					free.width,
+					free.y + free.height - (placed.y + placed.height),
+				));
+			}
+
+			return false;
+		});
+
+		self.free_rects.append(&mut new_rects);
+	}
+
+	/// Remove free rectangles that are contained in other free rectangles.
+	fn prune_free_rects(&mut self) {
+		let mut i = 0;
+		while i < self.free_rects.len() {
+			let rect = self.free_rects[i];
+			let contained = self
+				.free_rects
+				.iter()
+				.enumerate()
+				.any(|(j, other)| i != j && other.contains(&rect) && (other != &rect || j < i));
+
+			if contained {
+				let _ = self.free_rects.swap_remove(i);
+			}
+			else {
+				i += 1;
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::util::rect_pack::MaxRectsPacker;
+
+	/// Test that packed rectangles stay inside the area and never overlap.
+	#[test]
+	fn pack_without_overlap() {
+		let mut packer = MaxRectsPacker::new(64, 64);
+		let mut placed = Vec::new();
+
+		for (w, h) in [(32, 32), (16, 48), (32, 16), (8, 8), (16, 16), (24, 8)] {
+			let rect = packer.insert(w, h).expect("rectangle should fit");
+			assert_eq!((rect.width, rect.height), (w, h));
+			assert!(rect.x + rect.width <= 64 && rect.y + rect.height <= 64);
+			placed.push(rect);
+		}
+
+		for (i, a) in placed.iter().enumerate() {
+			for b in &placed[i + 1..] {
+				assert!(!a.intersects(b), "{a:?} overlaps {b:?}");
+			}
+		}
+	}
+
+	/// Test that rectangles are rejected once the area is full.
+	#[test]
+	fn pack_until_full() {
+		let mut packer = MaxRectsPacker::new(32, 32);
+
+		for _ in 0..4 {
+			assert!(packer.insert(16, 16).is_some());
+		}
+		assert!(packer.insert(1, 1).is_none());
+		assert!(MaxRectsPacker::new(8, 8).insert(9, 1).is_none());
+	}
+}